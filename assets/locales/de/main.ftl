@@ -0,0 +1,95 @@
+## Menus
+
+menu-title = Bevy 2D Shooter
+menu-play = Spielen
+menu-options = Optionen
+menu-high-scores = Bestenliste
+menu-credits = Mitwirkende
+menu-quit = Beenden
+menu-back = Zurück
+menu-retry = Nochmal
+menu-main-menu = Hauptmenü
+
+## Start screen
+
+start-instructions =
+    Bewege dich mit WASD und schieße mit der Maus.
+    Zoome mit dem Mausrad (am Desktop) oder mit Z und X.
+
+## Options
+
+options-title = Optionen
+options-language = Sprache: { $language }
+options-music = Musiklautstärke: { $percent } %
+options-sounds = Effektlautstärke: { $percent } %
+options-accessibility = Barrierefreiheit
+
+## Accessibility
+
+accessibility-title = Barrierefreiheit
+accessibility-colors = Farben: { $scheme ->
+        [deuteranopia] Deuteranopie
+        [protanopia] Protanopie
+        [tritanopia] Tritanopie
+       *[default] Standard
+    }
+accessibility-enemy-outlines = Gegnerumrisse: { $enabled ->
+        [true] an
+       *[false] aus
+    }
+accessibility-text-size = Textgröße: { $percent } %
+accessibility-high-contrast = Kontrastreiches HUD: { $enabled ->
+        [true] an
+       *[false] aus
+    }
+accessibility-fire-mode = Feuern: { $mode ->
+        [hold] gedrückt halten
+        [auto] automatisch
+       *[click] pro Klick
+    }
+accessibility-aim-assist = Zielhilfe: { $enabled ->
+        [true] an
+       *[false] aus
+    }
+accessibility-aim-assist-strength = Stärke der Zielhilfe: { $percent } %
+accessibility-screen-shake = Bildschirmwackeln: { $percent } %
+accessibility-reduced-flashes = Weniger Blitze: { $enabled ->
+        [true] an
+       *[false] aus
+    }
+accessibility-game-speed = Spieltempo: { $percent } %
+
+## High scores
+
+high-scores-title = Bestenliste
+high-scores-entry = { $place }. { $score } ({ $kills } Kills)
+high-scores-empty = Noch keine Runden.
+
+## Credits
+
+credits-title = Mitwirkende
+credits-text =
+    Erstellt mit Bevy und Rapier.
+    Schrift: DejaVu Sans.
+    Übersetzungen mit Fluent.
+
+## Game over screen
+
+end-title = Spiel vorbei
+end-score = Punkte: { $score }
+end-kills = Kills: { $kills } für { $points }
+end-combo = Kombo-Bonus: { $bonus } (bester x{ $multiplier })
+end-streak = Serien-Bonus: { $bonus } (beste Serie { $streak })
+end-high-score = Neue Bestleistung, Platz { $place }!
+end-seed = Seed: { $seed }
+
+## HUD
+
+hud-health = ♥ { $points }
+hud-score = ★ { $points }
+hud-multiplier = x{ $multiplier }
+hud-wave = Welle { $wave }
+hud-time = { $minutes }:{ $seconds }
+hud-ammo = ∞
+hud-shots = Schüsse: { $shots }
+hud-fps = { $fps } FPS, { $entities } Entitäten
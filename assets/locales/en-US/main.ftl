@@ -0,0 +1,98 @@
+## Menus
+
+menu-title = Bevy 2D Shooter
+menu-play = Play
+menu-options = Options
+menu-high-scores = High Scores
+menu-credits = Credits
+menu-quit = Quit
+menu-back = Back
+menu-retry = Retry
+menu-main-menu = Main Menu
+
+## Start screen
+
+start-instructions =
+    Use WASD to move and Mouse to shoot.
+    Use mouse wheel (on desktop) or Z and X for camera zoom.
+
+## Options
+
+options-title = Options
+options-language = Language: { $language }
+options-music = Music volume: { $percent }%
+options-sounds = Sound volume: { $percent }%
+options-accessibility = Accessibility
+
+## Accessibility
+
+accessibility-title = Accessibility
+accessibility-colors = Colors: { $scheme ->
+        [deuteranopia] deuteranopia
+        [protanopia] protanopia
+        [tritanopia] tritanopia
+       *[default] default
+    }
+accessibility-enemy-outlines = Enemy outlines: { $enabled ->
+        [true] on
+       *[false] off
+    }
+accessibility-text-size = Text size: { $percent }%
+accessibility-high-contrast = High contrast HUD: { $enabled ->
+        [true] on
+       *[false] off
+    }
+accessibility-fire-mode = Fire: { $mode ->
+        [hold] hold to fire
+        [auto] automatic
+       *[click] click to fire
+    }
+accessibility-aim-assist = Aim assist: { $enabled ->
+        [true] on
+       *[false] off
+    }
+accessibility-aim-assist-strength = Aim assist strength: { $percent }%
+accessibility-screen-shake = Screen shake: { $percent }%
+accessibility-reduced-flashes = Reduced flashes: { $enabled ->
+        [true] on
+       *[false] off
+    }
+accessibility-game-speed = Game speed: { $percent }%
+
+## High scores
+
+high-scores-title = High Scores
+high-scores-entry = { $place }. { $score } ({ $kills ->
+        [one] { $kills } kill
+       *[other] { $kills } kills
+    })
+high-scores-empty = No runs yet.
+
+## Credits
+
+credits-title = Credits
+credits-text =
+    Made with Bevy and Rapier.
+    Font: DejaVu Sans.
+    Translations with Fluent.
+
+## Game over screen
+
+end-title = Game over
+end-score = Score: { $score }
+end-kills = Kills: { $kills } for { $points }
+end-combo = Combo bonus: { $bonus } (best x{ $multiplier })
+end-streak = Streak bonus: { $bonus } (best streak { $streak })
+end-high-score = New high score, place { $place }!
+end-seed = Seed: { $seed }
+
+## HUD
+
+hud-health = ♥ { $points }
+hud-score = ★ { $points }
+hud-multiplier = x{ $multiplier }
+hud-wave = Wave { $wave }
+hud-time = { $minutes }:{ $seconds }
+hud-ammo = ∞
+hud-shots = Shots: { $shots }
+hud-fps = { $fps } FPS, { $entities } entities
@@ -0,0 +1,99 @@
+## Menus
+
+menu-title = Bevy 2D Shooter
+menu-play = Играть
+menu-options = Настройки
+menu-high-scores = Рекорды
+menu-credits = Авторы
+menu-quit = Выход
+menu-back = Назад
+menu-retry = Ещё раз
+menu-main-menu = Главное меню
+
+## Start screen
+
+start-instructions =
+    WASD — движение, мышь — стрельба.
+    Колесо мыши (на компьютере) или Z и X — масштаб камеры.
+
+## Options
+
+options-title = Настройки
+options-language = Язык: { $language }
+options-music = Громкость музыки: { $percent }%
+options-sounds = Громкость звуков: { $percent }%
+options-accessibility = Доступность
+
+## Accessibility
+
+accessibility-title = Доступность
+accessibility-colors = Цвета: { $scheme ->
+        [deuteranopia] дейтеранопия
+        [protanopia] протанопия
+        [tritanopia] тританопия
+       *[default] обычные
+    }
+accessibility-enemy-outlines = Контуры врагов: { $enabled ->
+        [true] вкл.
+       *[false] выкл.
+    }
+accessibility-text-size = Размер текста: { $percent }%
+accessibility-high-contrast = Контрастный интерфейс: { $enabled ->
+        [true] вкл.
+       *[false] выкл.
+    }
+accessibility-fire-mode = Стрельба: { $mode ->
+        [hold] при удержании
+        [auto] автоматически
+       *[click] по щелчку
+    }
+accessibility-aim-assist = Помощь в прицеливании: { $enabled ->
+        [true] вкл.
+       *[false] выкл.
+    }
+accessibility-aim-assist-strength = Сила помощи в прицеливании: { $percent }%
+accessibility-screen-shake = Тряска экрана: { $percent }%
+accessibility-reduced-flashes = Меньше вспышек: { $enabled ->
+        [true] вкл.
+       *[false] выкл.
+    }
+accessibility-game-speed = Скорость игры: { $percent }%
+
+## High scores
+
+high-scores-title = Рекорды
+high-scores-entry = { $place }. { $score } ({ $kills ->
+        [one] { $kills } убийство
+        [few] { $kills } убийства
+       *[many] { $kills } убийств
+    })
+high-scores-empty = Пока нет забегов.
+
+## Credits
+
+credits-title = Авторы
+credits-text =
+    Сделано на Bevy и Rapier.
+    Шрифт: DejaVu Sans.
+    Переводы на Fluent.
+
+## Game over screen
+
+end-title = Игра окончена
+end-score = Очки: { $score }
+end-kills = Убито: { $kills }, очков: { $points }
+end-combo = Бонус за комбо: { $bonus } (лучший x{ $multiplier })
+end-streak = Бонус за серию: { $bonus } (лучшая серия { $streak })
+end-high-score = Новый рекорд, { $place }-е место!
+end-seed = Сид: { $seed }
+
+## HUD
+
+hud-health = ♥ { $points }
+hud-score = ★ { $points }
+hud-multiplier = x{ $multiplier }
+hud-wave = Волна { $wave }
+hud-time = { $minutes }:{ $seconds }
+hud-ammo = ∞
+hud-shots = Выстрелы: { $shots }
+hud-fps = { $fps } FPS, сущностей: { $entities }
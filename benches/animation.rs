@@ -0,0 +1,60 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use bevy_2d_test::{
+    animation::{advance_animations, sync_animation_sprites, AnimationFinished, AnimationFrameEvent, AnimationLibrary, AnimationPlayer, PlayMode, SpriteClip},
+    gameplay::FIXED_TICKS_PER_SECOND,
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const WALK_CLIPS: [&str; 4] = ["walk_bottom", "walk_left", "walk_top", "walk_right"];
+
+fn slime_library() -> AnimationLibrary {
+    let mut library = AnimationLibrary::default();
+    for (i, name) in WALK_CLIPS.iter().enumerate() {
+        library.insert(*name, SpriteClip::new(i * 8 .. i * 8 + 8, PlayMode::Loop).with_event(3, "step"));
+    }
+    library.insert("dying", SpriteClip::new(35 .. 40, PlayMode::Once));
+    library
+}
+
+/// App running only the animation systems, one update per fixed tick.
+fn animation_app(entities: usize) -> App {
+    let mut app = App::new();
+    app
+        .add_plugins((MinimalPlugins, AssetPlugin { watch_for_changes_override: Some(false), ..default() }))
+        .init_asset::<AnimationLibrary>()
+        .add_event::<AnimationFinished>()
+        .add_event::<AnimationFrameEvent>()
+        .add_systems(Update, (advance_animations, sync_animation_sprites).chain())
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(1.0 / FIXED_TICKS_PER_SECOND)));
+
+    let library = slime_library();
+    let players: Vec<AnimationPlayer> = {
+        let handle = app.world_mut().resource_mut::<Assets<AnimationLibrary>>().add(library.clone());
+        WALK_CLIPS.iter().map(|clip| AnimationPlayer::new(handle.clone(), &library, clip)).collect()
+    };
+    let world = app.world_mut();
+    for i in 0 .. entities {
+        let player = players[i % players.len()].clone();
+        let sprite = Sprite {
+            texture_atlas: Some(TextureAtlas { layout: Handle::default(), index: player.atlas_index() }),
+            ..default()
+        };
+        world.spawn((player, sprite));
+    }
+    app
+}
+
+fn play_animations(c: &mut Criterion) {
+    let mut group = c.benchmark_group("play_animations");
+    for entities in [1_000, 10_000, 50_000] {
+        let mut app = animation_app(entities);
+        app.update();
+        group.bench_with_input(BenchmarkId::from_parameter(entities), &entities, |b, _| b.iter(|| app.update()));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, play_animations);
+criterion_main!(benches);
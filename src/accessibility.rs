@@ -0,0 +1,224 @@
+//! Accessibility options of [[AccessibilitySettings]], applied while the game is running.
+//!
+//! Colors of the HUD, minimap, threat indicators and enemy outlines come from the [[Palette]]
+//! of the chosen [[ColorScheme]]. Menu texts are scaled with [[ScaledText]], HUD texts with
+//! [[crate::menu::in_game_menu::HudText]]. Fire modes and aim assist change the live input
+//! (see [[crate::control::collect_live_input]]), so recorded replays don't depend on them.
+
+use bevy::{
+    color::palettes::css::{CRIMSON, DARK_ORANGE, MEDIUM_PURPLE, ORANGE, RED, WHITE, YELLOW},
+    prelude::*,
+};
+
+use crate::{
+    control::InputSource,
+    gameplay::enemy::EnemyState,
+    settings::{AccessibilitySettings, ColorScheme, Settings},
+    GameState,
+};
+
+pub const MIN_TEXT_SCALE: f32 = 0.75;
+pub const MAX_TEXT_SCALE: f32 = 2.0;
+
+pub const MIN_GAME_SPEED: f32 = 0.25;
+pub const MAX_GAME_SPEED: f32 = 1.0;
+
+/// Radius of enemy outlines, a bit larger than enemy colliders.
+const OUTLINE_RADIUS: f32 = 13.0;
+
+/// Colors with a meaning, distinguishable with the color blindness of the [[ColorScheme]].
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct Palette {
+    pub health: Color,
+    /// Health lost recently.
+    pub health_trail: Color,
+    pub ammo: Color,
+    /// Enemies on the minimap and their outlines.
+    pub enemy: Color,
+    pub threat_regular: Color,
+    pub threat_ranged: Color,
+    pub threat_boss: Color,
+    pub pickup: Color,
+    pub spawn_point: Color,
+    pub player: Color,
+    pub panel: Color,
+    pub panel_border: Color,
+    /// Empty part of HUD bars.
+    pub bar_background: Color,
+}
+
+impl Palette {
+    pub fn new(scheme: ColorScheme, high_contrast: bool) -> Self {
+        // Colors of the color blind schemes are from the Okabe-Ito palette
+        let vermillion = Color::srgb_u8(213, 94, 0);
+        let orange = Color::srgb_u8(230, 159, 0);
+        let sky_blue = Color::srgb_u8(86, 180, 233);
+        let blue = Color::srgb_u8(0, 114, 178);
+        let yellow = Color::srgb_u8(240, 228, 66);
+        let reddish_purple = Color::srgb_u8(204, 121, 167);
+        let light_gray = Color::srgb_u8(200, 200, 200);
+
+        let normal = Palette {
+            health: RED.into(),
+            health_trail: ORANGE.into(),
+            ammo: YELLOW.into(),
+            enemy: Color::srgb_u8(80, 220, 80),
+            threat_regular: CRIMSON.into(),
+            threat_ranged: DARK_ORANGE.into(),
+            threat_boss: MEDIUM_PURPLE.into(),
+            pickup: Color::srgb_u8(255, 215, 0),
+            spawn_point: Color::srgb_u8(170, 60, 200),
+            player: WHITE.into(),
+            panel: Color::srgba(0.0, 0.0, 0.0, 0.45),
+            panel_border: Color::NONE,
+            bar_background: Color::srgba(0.1, 0.1, 0.1, 0.8),
+        };
+        let palette = match scheme {
+            ColorScheme::Default => normal,
+            ColorScheme::Deuteranopia => Palette {
+                health: sky_blue,
+                health_trail: yellow,
+                ammo: yellow,
+                enemy: vermillion,
+                threat_regular: vermillion,
+                threat_ranged: yellow,
+                threat_boss: sky_blue,
+                pickup: yellow,
+                spawn_point: reddish_purple,
+                ..normal
+            },
+            // Reds look dark, so enemies are orange instead of vermillion
+            ColorScheme::Protanopia => Palette {
+                health: sky_blue,
+                health_trail: yellow,
+                ammo: yellow,
+                enemy: orange,
+                threat_regular: orange,
+                threat_ranged: sky_blue,
+                threat_boss: reddish_purple,
+                pickup: yellow,
+                spawn_point: blue,
+                ..normal
+            },
+            ColorScheme::Tritanopia => Palette {
+                health: vermillion,
+                health_trail: sky_blue,
+                ammo: light_gray,
+                enemy: vermillion,
+                threat_regular: vermillion,
+                threat_ranged: sky_blue,
+                threat_boss: yellow,
+                pickup: sky_blue,
+                spawn_point: reddish_purple,
+                ..normal
+            },
+        };
+        if !high_contrast {
+            return palette;
+        }
+        Palette {
+            panel: Color::srgba(0.0, 0.0, 0.0, 0.9),
+            panel_border: WHITE.into(),
+            bar_background: Color::BLACK,
+            ..palette
+        }
+    }
+
+    pub fn of(settings: &AccessibilitySettings) -> Self {
+        Palette::new(settings.color_scheme, settings.high_contrast_hud)
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::new(ColorScheme::Default, false)
+    }
+}
+
+/// Font size of a menu text, scaled by [[AccessibilitySettings::text_scale]].
+#[derive(Component)]
+pub struct ScaledText(pub f32);
+
+/// Aim point turned around `origin` towards the nearest target within `max_angle` (in degrees)
+/// of the direction from `origin` to `aim`, unchanged when there is none.
+/// The `strength` from 0 to 1 is the part of the angle to the target it's turned by,
+/// at 1 it points right at the target.
+pub fn assisted_aim(origin: Vec2, aim: Vec2, targets: impl IntoIterator<Item = Vec2>, max_angle: f32, strength: f32) -> Vec2 {
+    let direction = aim - origin;
+    if direction == Vec2::ZERO {
+        return aim;
+    }
+    let max_angle = max_angle.to_radians();
+    let Some(target) = targets.into_iter()
+        .filter(|target| *target != origin && direction.angle_to(*target - origin).abs() <= max_angle)
+        .min_by(|a, b| a.distance_squared(origin).total_cmp(&b.distance_squared(origin)))
+    else {
+        return aim;
+    };
+    let angle = direction.angle_to(target - origin).abs();
+    origin + direction.rotate_towards(target - origin, strength.clamp(0.0, 1.0) * angle)
+}
+
+pub struct AccessibilityPlugin;
+
+impl Plugin for AccessibilityPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Palette>()
+            .add_systems(Update, (
+                update_palette,
+                apply_game_speed,
+                scale_menu_texts,
+            ).run_if(resource_exists::<Settings>))
+            .add_systems(Update, draw_enemy_outlines.run_if(in_state(GameState::InGame)));
+    }
+}
+
+fn update_palette(
+    settings: Res<Settings>,
+    mut palette: ResMut<Palette>,
+) {
+    if settings.is_changed() {
+        palette.set_if_neq(Palette::of(&settings.accessibility));
+    }
+}
+
+/// Replays have their own playback speed, see [[crate::replay::control_playback_speed]].
+fn apply_game_speed(
+    settings: Res<Settings>,
+    input_source: Res<InputSource>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    if !settings.is_changed() || *input_source == InputSource::Replay {
+        return;
+    }
+    time.set_relative_speed(settings.accessibility.game_speed.clamp(MIN_GAME_SPEED, MAX_GAME_SPEED));
+}
+
+fn scale_menu_texts(
+    settings: Res<Settings>,
+    mut text_query: Query<(Ref<ScaledText>, &mut TextFont)>,
+) {
+    let scale = settings.accessibility.text_scale.clamp(MIN_TEXT_SCALE, MAX_TEXT_SCALE);
+    for (text, mut font) in &mut text_query {
+        if settings.is_changed() || text.is_added() {
+            font.font_size = text.0 * scale;
+        }
+    }
+}
+
+fn draw_enemy_outlines(
+    settings: Res<Settings>,
+    palette: Res<Palette>,
+    enemy_query: Query<(&Transform, &EnemyState)>,
+    mut gizmos: Gizmos,
+) {
+    if !settings.accessibility.enemy_outlines {
+        return;
+    }
+    for (transform, state) in &enemy_query {
+        if *state == EnemyState::Alive {
+            gizmos.circle_2d(transform.translation.xy(), OUTLINE_RADIUS, palette.enemy);
+        }
+    }
+}
@@ -1,81 +1,394 @@
 use std::{ops::Range, time::Duration};
 
-use bevy::prelude::*;
-
-use crate::direction::Direction;
-
-/// For an animations specifies what frames intervals correspond to each of directions.
-#[derive(Debug, Default, Clone)]
-pub struct AnimationByDirection {
-    pub bottom: Range<usize>,
-    pub bottm_left: Range<usize>,
-    pub left: Range<usize>,
-    pub top_left: Range<usize>,
-    pub top: Range<usize>,
-    pub top_right: Range<usize>,
-    pub right: Range<usize>,
-    pub bottom_right: Range<usize>,
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::direction::Facing;
+
+pub const DEFAULT_FPS: u8 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayMode {
+    /// Starts over after the last frame.
+    Loop,
+    /// Stops at the last frame and sends [[AnimationFinished]].
+    Once,
+    /// Goes forth and back.
+    PingPong,
 }
 
-impl AnimationByDirection {
-    pub fn for_direction(&self, d: Direction) -> Range<usize> {
-        match d {
-            Direction::Bottom => self.bottom.clone(),
-            Direction::BottomLeft => self.bottm_left.clone(),
-            Direction::Left => self.left.clone(),
-            Direction::TopLeft => self.top_left.clone(),
-            Direction::Top => self.top.clone(),
-            Direction::TopRight => self.top_right.clone(),
-            Direction::Right => self.right.clone(),
-            Direction::BottomRight => self.bottom_right.clone(),
+/// Sequence of texture atlas frames.
+#[derive(Debug, Clone)]
+pub struct SpriteClip {
+    /// Atlas indices of the frames, end exclusive.
+    pub frames: Range<usize>,
+    pub mode: PlayMode,
+    pub fps: u8,
+    /// Show time of each frame. When empty, all frames are shown for the same time, defined by `fps`.
+    pub frame_durations: Vec<Duration>,
+    /// Clip to switch to once a [[PlayMode::Once]] clip is finished.
+    pub next: Option<String>,
+    /// Events sent when given frame (counted from the clip start) is shown.
+    /// Their names are constants of the code, so sending them doesn't allocate.
+    pub events: Vec<(usize, &'static str)>,
+}
+
+impl SpriteClip {
+    pub fn new(frames: Range<usize>, mode: PlayMode) -> Self {
+        SpriteClip { frames, mode, fps: DEFAULT_FPS, frame_durations: Vec::new(), next: None, events: Vec::new() }
+    }
+
+    pub fn with_fps(mut self, fps: u8) -> Self {
+        self.fps = fps;
+        self
+    }
+
+    pub fn then(mut self, next: impl Into<String>) -> Self {
+        self.next = Some(next.into());
+        self
+    }
+
+    pub fn with_event(mut self, frame: usize, name: &'static str) -> Self {
+        self.events.push((frame, name));
+        self
+    }
+
+    pub fn with_frame_durations(mut self, durations: Vec<Duration>) -> Self {
+        self.frame_durations = durations;
+        self
+    }
+
+    /// Show time of given frame (counted from the clip start).
+    pub fn frame_show_time(&self, frame: usize) -> Duration {
+        self.frame_durations.get(frame).copied()
+            .unwrap_or(Duration::from_secs(1) / self.fps.max(1) as u32)
+    }
+
+    /// How long one pass through all frames takes.
+    pub fn duration(&self) -> Duration {
+        (0 .. self.frames.len()).map(|frame| self.frame_show_time(frame)).sum()
+    }
+}
+
+/// Name of the clip of given base animation for given facing, e.g. "walk_left".
+pub fn clip_name(base: &str, facing: &Facing) -> String {
+    format!("{base}_{}", facing.name())
+}
+
+/// Index of a clip in an [[AnimationLibrary]].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ClipId(pub u32);
+
+/// Named clips of a kind of objects, shared by all of them through a handle.
+/// Per object state is kept in [[AnimationPlayer]].
+#[derive(Asset, TypePath, Debug, Default, Clone)]
+pub struct AnimationLibrary {
+    names: Vec<String>,
+    clips: Vec<SpriteClip>,
+    ids: HashMap<String, ClipId>,
+}
+
+impl AnimationLibrary {
+    /// Adds the clip or replaces the clip with the same name.
+    pub fn insert(&mut self, name: impl Into<String>, clip: SpriteClip) -> ClipId {
+        let name = name.into();
+        if let Some(&id) = self.ids.get(&name) {
+            self.clips[id.0 as usize] = clip;
+            return id;
+        }
+        let id = ClipId(self.clips.len() as u32);
+        self.ids.insert(name.clone(), id);
+        self.names.push(name);
+        self.clips.push(clip);
+        id
+    }
+
+    pub fn id(&self, name: &str) -> Option<ClipId> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SpriteClip> {
+        self.id(name).map(|id| &self.clips[id.0 as usize])
+    }
+
+    pub fn clip(&self, id: ClipId) -> Option<&SpriteClip> {
+        self.clips.get(id.0 as usize)
+    }
+
+    pub fn name(&self, id: ClipId) -> &str {
+        self.names.get(id.0 as usize).map(String::as_str).unwrap_or("")
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(String::as_str)
+    }
+
+    /// Takes clips of another library (e.g. of a reloaded sprite sheet).
+    /// Ids of clips stay the same, so players of this library keep working.
+    pub fn update_from(&mut self, other: AnimationLibrary) {
+        for (name, clip) in other.names.into_iter().zip(other.clips) {
+            self.insert(name, clip);
+        }
+    }
+
+    /// Attaches an event to a frame of the clip, if there is such clip.
+    pub fn add_event(&mut self, clip: &str, frame: usize, name: &'static str) {
+        if let Some(id) = self.id(clip) {
+            self.clips[id.0 as usize].events.push((frame, name));
+        }
+    }
+
+    /// Attaches an event to a frame of all directional clips of the base animation, e.g. "walk_left", "walk_right".
+    pub fn add_directional_event(&mut self, base: &str, frame: usize, name: &'static str) {
+        let prefix = format!("{base}_");
+        for (clip_name, clip) in self.names.iter().zip(&mut self.clips) {
+            if clip_name.starts_with(&prefix) {
+                clip.events.push((frame, name));
+            }
         }
     }
 }
 
-/// Generalized animation config for any moving character/enemy.
-/// We assume that each such object should have idel, walk and dying animation.
-#[derive(Component)]
-pub struct AnimationConfig {
-    pub idle: AnimationByDirection,
-    pub walk: AnimationByDirection,
-    pub dying: Range<usize>,
-    pub current_frame_range: Range<usize>,
-    pub fps: u8,
-    pub elapsed_frame_timer: Timer, //move this to struct per instance, everything else can be shared
+/// Plays clips of an [[AnimationLibrary]] on an object.
+/// It only tracks the current frame, so it works without a renderer.
+/// The frame is copied to the sprite by [[sync_animation_sprites]].
+#[derive(Component, Debug, Clone)]
+pub struct AnimationPlayer {
+    pub library: Handle<AnimationLibrary>,
+    clip: ClipId,
+    frame: usize,
+    /// Atlas index of the current frame.
+    index: usize,
+    /// Time the current frame is already shown.
+    elapsed: Duration,
+    /// Playback speed multiplier.
+    pub speed: f32,
+    backwards: bool,
+    finished: bool,
+    started: bool,
+    /// Sprite should be mirrored, because the clip of the opposite direction is played.
+    flip_x: bool,
+}
+
+/// What happened during [[AnimationPlayer::tick]].
+#[derive(Debug, Default)]
+pub struct AnimationTick<'a> {
+    /// Clip the events belong to.
+    pub clip: &'a str,
+    pub frame_events: Vec<&'static str>,
+    pub finished: bool,
 }
 
-impl AnimationConfig {
-    pub fn timer_from_fps(fps: u8) -> Timer {
-        Timer::new(Duration::from_secs_f32(1.0 / (fps as f32)), TimerMode::Once)
+impl AnimationPlayer {
+    pub fn new(library: Handle<AnimationLibrary>, clips: &AnimationLibrary, start: &str) -> Self {
+        let mut player = AnimationPlayer::stopped(library);
+        player.play(clips, start);
+        player
     }
-    pub fn frame_show_time(&self) -> Duration {
-        Duration::from_secs_f32(1.0 / (self.fps as f32))
+
+    /// Starts with the directional clip of the base animation, see [[AnimationPlayer::play_facing]].
+    pub fn facing(library: Handle<AnimationLibrary>, clips: &AnimationLibrary, base: &str, facing: &Facing) -> Self {
+        let mut player = AnimationPlayer::stopped(library);
+        player.play_facing(clips, base, facing);
+        player
+    }
+
+    fn stopped(library: Handle<AnimationLibrary>) -> Self {
+        AnimationPlayer {
+            library,
+            clip: ClipId(u32::MAX),
+            frame: 0,
+            index: 0,
+            elapsed: Duration::ZERO,
+            speed: 1.0,
+            backwards: false,
+            finished: false,
+            started: false,
+            flip_x: false,
+        }
+    }
+
+    /// Switches to the clip, unless it is already playing. Unknown clips are ignored.
+    pub fn play(&mut self, clips: &AnimationLibrary, name: &str) {
+        match clips.id(name) {
+            Some(id) => self.play_id(clips, id),
+            None => warn!("Unknown animation clip {name}"),
+        }
+    }
+
+    pub fn play_id(&mut self, clips: &AnimationLibrary, id: ClipId) {
+        if self.clip == id {
+            return;
+        }
+        let Some(clip) = clips.clip(id) else {
+            return;
+        };
+        self.clip = id;
+        self.frame = 0;
+        self.index = clip.frames.start;
+        self.elapsed = Duration::ZERO;
+        self.backwards = false;
+        self.finished = false;
+        self.started = false;
+    }
+
+    /// Plays the directional clip of the base animation, e.g. "walk_left" for "walk".
+    /// When there is no such clip, the clip of the mirrored direction is played flipped.
+    pub fn play_facing(&mut self, clips: &AnimationLibrary, base: &str, facing: &Facing) {
+        let name = clip_name(base, facing);
+        if let Some(id) = clips.id(&name) {
+            self.flip_x = false;
+            self.play_id(clips, id);
+        } else if let Some(id) = clips.id(&clip_name(base, &facing.mirrored())) {
+            self.flip_x = true;
+            self.play_id(clips, id);
+        } else {
+            self.play(clips, &name);
+        }
+    }
+
+    pub fn clip(&self) -> ClipId {
+        self.clip
+    }
+
+    /// Frame of the current clip, counted from the clip start.
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    pub fn atlas_index(&self) -> usize {
+        self.index
+    }
+
+    pub fn flip_x(&self) -> bool {
+        self.flip_x
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    pub fn tick<'a>(&mut self, clips: &'a AnimationLibrary, delta: Duration) -> AnimationTick<'a> {
+        let Some(clip) = clips.clip(self.clip) else {
+            return AnimationTick::default();
+        };
+        let mut result = AnimationTick { clip: clips.name(self.clip), ..default() };
+        let len = clip.frames.len();
+        // The clip could get shorter after reload
+        if self.frame >= len {
+            self.frame = 0;
+        }
+
+        if !self.started {
+            self.started = true;
+            collect_frame_events(clip, self.frame, &mut result);
+        }
+        if self.finished {
+            return result;
+        }
+
+        self.elapsed += delta.mul_f32(self.speed);
+        loop {
+            let show_time = clip.frame_show_time(self.frame);
+            if show_time.is_zero() || self.elapsed < show_time {
+                break;
+            }
+            self.elapsed -= show_time;
+            match clip.mode {
+                PlayMode::Loop => self.frame = (self.frame + 1) % len.max(1),
+                PlayMode::PingPong if len > 1 => {
+                    if self.backwards && self.frame == 0 {
+                        self.backwards = false;
+                    } else if !self.backwards && self.frame + 1 == len {
+                        self.backwards = true;
+                    }
+                    if self.backwards {
+                        self.frame -= 1;
+                    } else {
+                        self.frame += 1;
+                    }
+                },
+                PlayMode::PingPong => {},
+                PlayMode::Once => {
+                    if self.frame + 1 >= len {
+                        self.finished = true;
+                        result.finished = true;
+                        if let Some(next) = &clip.next {
+                            self.play(clips, next);
+                        }
+                        return result;
+                    }
+                    self.frame += 1;
+                },
+            }
+            self.index = clip.frames.start + self.frame;
+            collect_frame_events(clip, self.frame, &mut result);
+        }
+        result
+    }
+}
+
+fn collect_frame_events(clip: &SpriteClip, frame: usize, result: &mut AnimationTick) {
+    for &(event_frame, name) in &clip.events {
+        if event_frame == frame {
+            result.frame_events.push(name);
+        }
     }
 }
 
-/// Plays sprite anumation for all object that have thier animations defined via [[AnimationConfig]].
-pub fn play_animations(
+/// [[PlayMode::Once]] clip played to the end.
+#[derive(Event, Clone, Debug)]
+pub struct AnimationFinished {
+    pub entity: Entity,
+    pub clip: String,
+}
+
+/// Frame with an event attached was shown (footstep, attack hit frame etc).
+#[derive(Event, Clone, Debug)]
+pub struct AnimationFrameEvent {
+    pub entity: Entity,
+    pub clip: ClipId,
+    pub name: &'static str,
+}
+
+/// Advances all [[AnimationPlayer]]s.
+/// Runs on fixed ticks, so gameplay can rely on animation events.
+pub fn advance_animations(
     time: Res<Time>,
-    mut query: Query<(&mut AnimationConfig, &mut Sprite)>,
+    libraries: Res<Assets<AnimationLibrary>>,
+    mut query: Query<(Entity, &mut AnimationPlayer)>,
+    mut finished_writer: EventWriter<AnimationFinished>,
+    mut frame_writer: EventWriter<AnimationFrameEvent>,
+) {
+    let delta = time.delta();
+    for (entity, mut player) in &mut query {
+        let Some(library) = libraries.get(&player.library) else {
+            continue;
+        };
+        // The player can switch to the next clip during the tick
+        let clip = player.clip();
+        let tick = player.tick(library, delta);
+        for &name in &tick.frame_events {
+            frame_writer.send(AnimationFrameEvent { entity, clip, name });
+        }
+        if tick.finished {
+            finished_writer.send(AnimationFinished { entity, clip: tick.clip.to_string() });
+        }
+    }
+}
+
+/// Shows current frames of [[AnimationPlayer]]s.
+pub fn sync_animation_sprites(
+    mut query: Query<(&AnimationPlayer, &mut Sprite)>,
 ) {
-    for (mut config, mut sprite) in &mut query {
-        // We track how much time passed since previous frame.
-        config.elapsed_frame_timer.tick(time.delta());
-
-        // Then we check if it is time to play next animation frame.
-        // If not, we leave current frame.
-        // Otherwise we switch to the next frame and restart the frame timer.
-        if config.elapsed_frame_timer.just_finished() {
+    for (player, mut sprite) in &mut query {
+        // Sprites are only touched when the frame changes, to not trigger their change detection
+        if sprite.texture_atlas.as_ref().is_some_and(|atlas| atlas.index != player.atlas_index()) {
             if let Some(atlas) = &mut sprite.texture_atlas {
-                if !config.current_frame_range.contains(&atlas.index) {
-                    atlas.index = config.current_frame_range.start;
-                } else if atlas.index == config.current_frame_range.end {
-                    atlas.index = config.current_frame_range.start;
-                } else {
-                    atlas.index += 1;
-                }
-                config.elapsed_frame_timer = AnimationConfig::timer_from_fps(config.fps);
+                atlas.index = player.atlas_index();
             }
         }
+        if sprite.flip_x != player.flip_x() {
+            sprite.flip_x = player.flip_x();
+        }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,183 @@
+//! Import of sprite sheets exported from Aseprite.
+//!
+//! Export with "Array" frames and "Tags" enabled. Each tag becomes a [[SpriteClip]] with the same name,
+//! so tags of directional animations should be named like `walk_left` (see [[crate::animation::clip_name]]).
+//! A tag is played once, when its "repeat" is set to 1.
+
+use std::{error::Error, fmt, path::Path, time::Duration};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    prelude::*,
+};
+use serde::Deserialize;
+
+use crate::{
+    animation::{AnimationLibrary, PlayMode, SpriteClip},
+    json_asset::{parse_json, read_asset, JsonAssetError},
+};
+
+/// Label of the texture atlas layout of a loaded [[SpriteSheet]], e.g. `sprites/player.aseprite.json#layout`.
+pub const LAYOUT_LABEL: &str = "layout";
+
+#[derive(Deserialize, Debug)]
+pub struct AsepriteJson {
+    pub frames: Vec<AsepriteFrame>,
+    pub meta: AsepriteMeta,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct AsepriteFrame {
+    pub frame: AsepriteRect,
+    /// Milliseconds.
+    pub duration: u64,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct AsepriteRect {
+    pub x: u32,
+    pub y: u32,
+    pub w: u32,
+    pub h: u32,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct AsepriteSize {
+    pub w: u32,
+    pub h: u32,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct AsepriteMeta {
+    pub image: String,
+    pub size: AsepriteSize,
+    #[serde(default, rename = "frameTags")]
+    pub frame_tags: Vec<AsepriteTag>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct AsepriteTag {
+    pub name: String,
+    pub from: usize,
+    /// Inclusive.
+    pub to: usize,
+    #[serde(default)]
+    pub direction: String,
+    #[serde(default)]
+    pub repeat: Option<String>,
+}
+
+impl AsepriteJson {
+    pub fn parse(bytes: &[u8]) -> Result<Self, AsepriteError> {
+        let json: AsepriteJson = parse_json(bytes)?;
+        for tag in &json.meta.frame_tags {
+            if tag.from > tag.to || tag.to >= json.frames.len() {
+                return Err(AsepriteError::TagOutOfRange(tag.name.clone()));
+            }
+        }
+        Ok(json)
+    }
+
+    /// Atlas layout where index of a texture is the index of the frame.
+    pub fn layout(&self) -> TextureAtlasLayout {
+        let mut layout = TextureAtlasLayout::new_empty(UVec2::new(self.meta.size.w, self.meta.size.h));
+        for AsepriteFrame { frame, .. } in &self.frames {
+            layout.add_texture(URect::new(frame.x, frame.y, frame.x + frame.w, frame.y + frame.h));
+        }
+        layout
+    }
+
+    pub fn library(&self) -> AnimationLibrary {
+        let mut clips = AnimationLibrary::default();
+        for tag in &self.meta.frame_tags {
+            let mode = match (tag.direction.as_str(), tag.repeat.as_deref()) {
+                ("pingpong" | "pingpong_reverse", _) => PlayMode::PingPong,
+                (_, Some("1")) => PlayMode::Once,
+                _ => PlayMode::Loop,
+            };
+            if tag.direction.contains("reverse") {
+                warn!("Reverse direction of tag {} is not supported, it is played forward", tag.name);
+            }
+            let durations = self.frames[tag.from ..= tag.to].iter()
+                .map(|f| Duration::from_millis(f.duration))
+                .collect();
+            clips.insert(tag.name.clone(), SpriteClip::new(tag.from .. tag.to + 1, mode).with_frame_durations(durations));
+        }
+        clips
+    }
+}
+
+#[derive(Debug)]
+pub enum AsepriteError {
+    Asset(JsonAssetError),
+    TagOutOfRange(String),
+}
+
+impl fmt::Display for AsepriteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AsepriteError::Asset(e @ JsonAssetError::Json(_)) => {
+                write!(f, "can't load sprite sheet: {e} (frames should be exported as Array)")
+            },
+            AsepriteError::Asset(e) => write!(f, "can't load sprite sheet: {e}"),
+            AsepriteError::TagOutOfRange(tag) => write!(f, "tag {tag} refers to missing frames"),
+        }
+    }
+}
+
+impl Error for AsepriteError {}
+
+impl From<JsonAssetError> for AsepriteError {
+    fn from(e: JsonAssetError) -> Self {
+        AsepriteError::Asset(e)
+    }
+}
+
+/// Sprite sheet with its animation clips.
+#[derive(Asset, TypePath, Debug)]
+pub struct SpriteSheet {
+    #[dependency]
+    pub image: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+    pub library: AnimationLibrary,
+}
+
+/// Loads `*.aseprite.json` files as [[SpriteSheet]]s.
+#[derive(Default)]
+pub struct AsepriteLoader;
+
+impl AssetLoader for AsepriteLoader {
+    type Asset = SpriteSheet;
+    type Settings = ();
+    type Error = AsepriteError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<SpriteSheet, AsepriteError> {
+        let bytes = read_asset(reader).await?;
+        let json = AsepriteJson::parse(&bytes)?;
+
+        // Image path is relative to the JSON file
+        let image_path = load_context.path().parent().unwrap_or(Path::new("")).join(&json.meta.image);
+        let image = load_context.load(image_path);
+        let layout = load_context.add_labeled_asset(LAYOUT_LABEL.to_string(), json.layout());
+        Ok(SpriteSheet { image, layout, library: json.library() })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["aseprite.json"]
+    }
+}
+
+pub struct AsepritePlugin;
+
+impl Plugin for AsepritePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_asset::<SpriteSheet>()
+            .init_asset_loader::<AsepriteLoader>();
+    }
+}
@@ -0,0 +1,486 @@
+//! Playback of sounds through buses (music, effects, UI) with per kind voice limits.
+//!
+//! Gameplay only sends semantic events (e.g. [[WeaponFired]], [[Footstep]]), they are turned into
+//! [[PlaySound]] events here. UI sends [[PlaySound]] events directly.
+//! Each [[SoundKind]] has a pool of sounds, one is picked randomly for each event, and its own [[VoiceSettings]]:
+//! bus, number of voices, volume and pitch variation. Both are defined in the [[SoundBank]] file.
+//! When a kind runs out of voices, its oldest sound is stopped.
+//! Sounds with a position are panned and attenuated relative to the player.
+
+use std::{error::Error, fmt, path::Path};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    audio::{PlaybackMode, SpatialScale, Volume},
+    prelude::*,
+    utils::HashMap,
+};
+use rand::{Rng, RngCore};
+use serde::Deserialize;
+
+use crate::{
+    gameplay::player::{BulletHitWall, Footstep, PlayerHurt, PlayerInfo, WeaponFired},
+    json_asset::{parse_json, read_asset, JsonAssetError},
+    rng::GameRng,
+    settings::Settings,
+};
+
+const SOUND_BANK: &str = "sounds/game.sounds.json";
+
+/// Distance between the ears of the [[AudioListener]]. Positional sounds are placed between them.
+const EAR_GAP: f32 = 2.0;
+
+/// Keeps sounds between the ears within the distance, where the spatial sink doesn't attenuate them.
+const LISTENER_SCALE: SpatialScale = SpatialScale::new(1.0 / EAR_GAP);
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioBus {
+    Music,
+    Sfx,
+    Ui,
+}
+
+/// Kind of a sound, sounds of the same kind share voices.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum SoundKind {
+    WeaponFired,
+    BulletHitWall,
+    PlayerHurt,
+    Footstep,
+    SlimeDeath,
+    /// Another menu button got focus.
+    MenuFocus,
+    MenuSelect,
+    MenuBack,
+}
+
+impl SoundKind {
+    pub const ALL: [SoundKind; 8] = [
+        SoundKind::WeaponFired,
+        SoundKind::BulletHitWall,
+        SoundKind::PlayerHurt,
+        SoundKind::Footstep,
+        SoundKind::SlimeDeath,
+        SoundKind::MenuFocus,
+        SoundKind::MenuSelect,
+        SoundKind::MenuBack,
+    ];
+}
+
+/// How sounds of a kind are played.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct VoiceSettings {
+    pub bus: AudioBus,
+    /// Sounds of the kind playing at once, the oldest one is stopped for a new one.
+    pub max_voices: usize,
+    pub volume: f32,
+    /// Random change of volume, e.g. 0.1 is up to 10% quieter or louder.
+    pub volume_variation: f32,
+    /// Random change of playback speed (and so pitch).
+    pub pitch_variation: f32,
+    /// Music is lowered while sounds of the kind are played, for important sounds.
+    pub ducks_music: bool,
+}
+
+impl Default for VoiceSettings {
+    fn default() -> Self {
+        VoiceSettings { bus: AudioBus::Sfx, max_voices: 4, volume: 1.0, volume_variation: 0.0, pitch_variation: 0.0, ducks_music: false }
+    }
+}
+
+impl VoiceSettings {
+    /// Volume and playback speed of a new sound.
+    pub fn sample(&self, rng: &mut dyn RngCore) -> (f32, f32) {
+        let mut vary = |amount: f32| {
+            if amount > 0.0 { 1.0 + rng.gen_range(-amount ..= amount) } else { 1.0 }
+        };
+        let volume = self.volume * vary(self.volume_variation);
+        let speed = vary(self.pitch_variation);
+        (volume, speed)
+    }
+}
+
+/// Distance attenuation and panning of positional sounds.
+#[derive(Debug, Clone)]
+pub struct SpatialRange {
+    /// Sounds closer to the player than this are at full volume.
+    pub full_volume_distance: f32,
+    /// Sounds further than this aren't heard.
+    pub silent_distance: f32,
+    /// Horizontal distance, at which sounds are panned fully left or right.
+    pub pan_distance: f32,
+}
+
+impl Default for SpatialRange {
+    fn default() -> Self {
+        SpatialRange { full_volume_distance: 120.0, silent_distance: 700.0, pan_distance: 300.0 }
+    }
+}
+
+impl SpatialRange {
+    /// Volume multiplier, fading linearly from the full volume distance to the silent distance.
+    pub fn attenuation(&self, distance: f32) -> f32 {
+        let fade = self.silent_distance - self.full_volume_distance;
+        if fade <= 0.0 {
+            return if distance <= self.full_volume_distance { 1.0 } else { 0.0 };
+        }
+        (1.0 - (distance - self.full_volume_distance) / fade).clamp(0.0, 1.0)
+    }
+
+    /// Pan from -1 (left) to 1 (right) of a sound at given offset from the listener.
+    pub fn pan(&self, offset: Vec2) -> f32 {
+        (offset.x / self.pan_distance).clamp(-1.0, 1.0)
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SoundPoolJson {
+    /// Sound files, relative to the JSON file.
+    pub files: Vec<String>,
+    #[serde(flatten)]
+    pub voice: VoiceSettings,
+}
+
+/// Sounds of all kinds from a `*.sounds.json` file:
+///
+/// ```json
+/// {
+///     "weapon_fired": { "files": ["shot_1.wav", "shot_2.wav"], "max_voices": 4, "pitch_variation": 0.06 },
+///     "player_hurt": { "files": ["hurt.wav"], "volume": 0.8, "ducks_music": true }
+/// }
+/// ```
+///
+/// Fields other than `files` are [[VoiceSettings]], with `"bus": "sfx"` by default.
+#[derive(Deserialize, Debug)]
+pub struct SoundBankJson(pub HashMap<SoundKind, SoundPoolJson>);
+
+impl SoundBankJson {
+    pub fn parse(bytes: &[u8]) -> Result<Self, SoundBankError> {
+        let json: SoundBankJson = parse_json(bytes)?;
+        if let Some((kind, _)) = json.0.iter().find(|(_, pool)| pool.files.is_empty()) {
+            return Err(SoundBankError::EmptyPool(*kind));
+        }
+        Ok(json)
+    }
+}
+
+#[derive(Debug)]
+pub enum SoundBankError {
+    Asset(JsonAssetError),
+    EmptyPool(SoundKind),
+}
+
+impl fmt::Display for SoundBankError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SoundBankError::Asset(e) => write!(f, "can't load sound bank: {e}"),
+            SoundBankError::EmptyPool(kind) => write!(f, "{kind:?} has no sound files"),
+        }
+    }
+}
+
+impl Error for SoundBankError {}
+
+impl From<JsonAssetError> for SoundBankError {
+    fn from(e: JsonAssetError) -> Self {
+        SoundBankError::Asset(e)
+    }
+}
+
+/// Sounds of a kind, one is picked randomly for each event.
+#[derive(Debug, Clone)]
+pub struct SoundPool {
+    pub sounds: Vec<Handle<AudioSource>>,
+    pub voice: VoiceSettings,
+}
+
+/// Sound pools of all kinds of sounds.
+#[derive(Asset, TypePath, Debug, Default)]
+pub struct SoundBank {
+    pub pools: HashMap<SoundKind, SoundPool>,
+}
+
+impl SoundBank {
+    pub fn voice(&self, kind: SoundKind) -> VoiceSettings {
+        self.pools.get(&kind).map(|pool| pool.voice.clone()).unwrap_or_default()
+    }
+
+    /// Random sound of the kind, none when the kind has no sounds.
+    pub fn pick(&self, kind: SoundKind, rng: &mut dyn RngCore) -> Option<Handle<AudioSource>> {
+        let sounds = &self.pools.get(&kind)?.sounds;
+        (!sounds.is_empty()).then(|| sounds[rng.gen_range(0 .. sounds.len())].clone())
+    }
+}
+
+/// Loads `*.sounds.json` files as [[SoundBank]]s.
+#[derive(Default)]
+pub struct SoundBankLoader;
+
+impl AssetLoader for SoundBankLoader {
+    type Asset = SoundBank;
+    type Settings = ();
+    type Error = SoundBankError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<SoundBank, SoundBankError> {
+        let bytes = read_asset(reader).await?;
+        let json = SoundBankJson::parse(&bytes)?;
+
+        // Sound paths are relative to the JSON file
+        let directory = load_context.path().parent().unwrap_or(Path::new("")).to_path_buf();
+        let pools = json.0.into_iter().map(|(kind, pool)| {
+            let sounds = pool.files.iter().map(|file| load_context.load(directory.join(file))).collect();
+            (kind, SoundPool { sounds, voice: pool.voice })
+        }).collect();
+        Ok(SoundBank { pools })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["sounds.json"]
+    }
+}
+
+/// Sound bank and how positional sounds are heard.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct AudioMixer {
+    pub bank: Handle<SoundBank>,
+    pub spatial: SpatialRange,
+}
+
+/// Playing sounds of each kind, oldest first.
+#[derive(Resource, Default, Debug)]
+pub struct Voices {
+    playing: HashMap<SoundKind, Vec<Entity>>,
+}
+
+impl Voices {
+    /// Registers a new sound, returns older sounds of the kind which should be stopped
+    /// to stay within the limit of voices.
+    pub fn start(&mut self, kind: SoundKind, entity: Entity, max_voices: usize) -> Vec<Entity> {
+        let playing = self.playing.entry(kind).or_default();
+        playing.push(entity);
+        let excess = playing.len().saturating_sub(max_voices.max(1));
+        playing.drain(.. excess).collect()
+    }
+
+    /// Forgets a sound which has stopped.
+    pub fn finished(&mut self, entity: Entity) {
+        for playing in self.playing.values_mut() {
+            playing.retain(|e| *e != entity);
+        }
+    }
+
+    pub fn count(&self, kind: SoundKind) -> usize {
+        self.playing.get(&kind).map_or(0, Vec::len)
+    }
+}
+
+/// Music volume multiplier, lowered while important sounds play (see [[VoiceSettings::ducks_music]]).
+#[derive(Resource, Debug, Clone)]
+pub struct MusicDucking {
+    /// Current multiplier of music volume.
+    pub level: f32,
+    /// Seconds left until the music starts to come back.
+    pub hold: f32,
+}
+
+impl Default for MusicDucking {
+    fn default() -> Self {
+        MusicDucking { level: 1.0, hold: 0.0 }
+    }
+}
+
+/// Music volume while ducked.
+pub const DUCKED_MUSIC_LEVEL: f32 = 0.35;
+
+/// Seconds the music stays ducked after an important sound starts.
+pub const DUCK_HOLD_SECS: f32 = 0.6;
+
+/// Seconds to duck the music and to bring it back.
+const DUCK_ATTACK_SECS: f32 = 0.08;
+const DUCK_RELEASE_SECS: f32 = 0.5;
+
+impl MusicDucking {
+    pub fn duck(&mut self) {
+        self.hold = DUCK_HOLD_SECS;
+    }
+
+    pub fn tick(&mut self, delta_secs: f32) {
+        let range = 1.0 - DUCKED_MUSIC_LEVEL;
+        if self.hold > 0.0 {
+            self.hold = (self.hold - delta_secs).max(0.0);
+            self.level = (self.level - range * delta_secs / DUCK_ATTACK_SECS).max(DUCKED_MUSIC_LEVEL);
+        } else {
+            self.level = (self.level + range * delta_secs / DUCK_RELEASE_SECS).min(1.0);
+        }
+    }
+}
+
+/// Request to play a random sound of the kind.
+#[derive(Event, Debug, Clone)]
+pub struct PlaySound {
+    pub kind: SoundKind,
+    /// World position for panning and attenuation, none for sounds heard the same everywhere (e.g. UI).
+    pub position: Option<Vec2>,
+}
+
+/// Sound played by the [[GameAudioPlugin]].
+#[derive(Component, Debug)]
+pub struct PlayingSound {
+    pub kind: SoundKind,
+    pub bus: AudioBus,
+    /// Volume without bus volumes, which can be changed while the sound is playing.
+    pub gain: f32,
+}
+
+/// Listener of positional sounds. It stays in place, the sounds are placed around it according to their pan.
+#[derive(Component)]
+pub struct AudioListener;
+
+pub struct GameAudioPlugin;
+
+impl Plugin for GameAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_asset::<SoundBank>()
+            .init_asset_loader::<SoundBankLoader>()
+            .init_resource::<AudioMixer>()
+            .init_resource::<Voices>()
+            .init_resource::<MusicDucking>()
+            .add_event::<PlaySound>()
+            .add_systems(Startup, (setup_audio_listener, load_sound_bank))
+            .add_systems(Update, (
+                send_gameplay_sounds,
+                play_sounds,
+                apply_bus_volumes.run_if(resource_changed::<Settings>),
+            ).chain())
+            .add_observer(forget_finished_sound);
+    }
+}
+
+fn setup_audio_listener(
+    mut commands: Commands,
+) {
+    // Spatial sink of rodio makes a channel louder the further the sound is from that ear,
+    // so the ears are swapped to get sounds on the right side in the right channel
+    let listener = SpatialListener::new(EAR_GAP);
+    commands.spawn((
+        SpatialListener { left_ear_offset: listener.right_ear_offset, right_ear_offset: listener.left_ear_offset },
+        Transform::default(),
+        AudioListener,
+    ));
+}
+
+fn load_sound_bank(
+    asset_server: Res<AssetServer>,
+    mut mixer: ResMut<AudioMixer>,
+) {
+    mixer.bank = asset_server.load(SOUND_BANK);
+}
+
+/// Maps semantic gameplay events to sounds.
+fn send_gameplay_sounds(
+    mut weapon_fired: EventReader<WeaponFired>,
+    mut bullet_hit_wall: EventReader<BulletHitWall>,
+    mut player_hurt: EventReader<PlayerHurt>,
+    mut footsteps: EventReader<Footstep>,
+    mut sound_writer: EventWriter<PlaySound>,
+) {
+    let sound = |kind, position| PlaySound { kind, position: Some(position) };
+    sound_writer.send_batch(weapon_fired.read().map(|e| sound(SoundKind::WeaponFired, e.position)));
+    sound_writer.send_batch(bullet_hit_wall.read().map(|e| sound(SoundKind::BulletHitWall, e.position)));
+    sound_writer.send_batch(player_hurt.read().map(|e| sound(SoundKind::PlayerHurt, e.position)));
+    sound_writer.send_batch(footsteps.read().map(|e| sound(SoundKind::Footstep, e.position)));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn play_sounds(
+    mut commands: Commands,
+    mut events: EventReader<PlaySound>,
+    mixer: Res<AudioMixer>,
+    banks: Res<Assets<SoundBank>>,
+    settings: Res<Settings>,
+    mut voices: ResMut<Voices>,
+    mut ducking: ResMut<MusicDucking>,
+    mut game_rng: Option<ResMut<GameRng>>,
+    player_query: Query<&Transform, With<PlayerInfo>>,
+) {
+    let mut thread_rng = rand::thread_rng();
+    let rng: &mut dyn RngCore = match game_rng.as_deref_mut() {
+        Some(game_rng) => &mut game_rng.audio,
+        None => &mut thread_rng,
+    };
+    let listener = player_query.iter().next().map(|t| t.translation.xy());
+    // Sounds requested before the bank is loaded are skipped
+    let Some(bank) = banks.get(&mixer.bank) else {
+        events.clear();
+        return;
+    };
+
+    for event in events.read() {
+        let Some(sound) = bank.pick(event.kind, rng) else {
+            continue;
+        };
+        let voice = bank.voice(event.kind);
+        let (mut gain, speed) = voice.sample(rng);
+        let mut pan = None;
+        if let (Some(position), Some(listener)) = (event.position, listener) {
+            let offset = position - listener;
+            gain *= mixer.spatial.attenuation(offset.length());
+            pan = Some(mixer.spatial.pan(offset));
+        }
+        if gain <= 0.0 {
+            continue;
+        }
+        if voice.ducks_music {
+            ducking.duck();
+        }
+
+        let entity = commands.spawn((
+            AudioPlayer::new(sound),
+            PlaybackSettings {
+                mode: PlaybackMode::Despawn,
+                volume: Volume::new(gain * settings.audio.bus_volume(voice.bus)),
+                speed,
+                spatial: pan.is_some(),
+                spatial_scale: Some(LISTENER_SCALE),
+                ..default()
+            },
+            Transform::from_xyz(pan.unwrap_or(0.0) * EAR_GAP / 2.0, 0.0, 0.0),
+            PlayingSound { kind: event.kind, bus: voice.bus, gain },
+        )).id();
+        for stolen in voices.start(event.kind, entity, voice.max_voices) {
+            // Dropping the sink stops the sound
+            commands.entity(stolen).despawn();
+        }
+    }
+}
+
+fn apply_bus_volumes(
+    settings: Res<Settings>,
+    sounds: Query<(&PlayingSound, Option<&AudioSink>, Option<&SpatialAudioSink>)>,
+) {
+    for (sound, sink, spatial_sink) in &sounds {
+        let volume = sound.gain * settings.audio.bus_volume(sound.bus);
+        if let Some(sink) = sink {
+            sink.set_volume(volume);
+        }
+        if let Some(sink) = spatial_sink {
+            sink.set_volume(volume);
+        }
+    }
+}
+
+fn forget_finished_sound(
+    trigger: Trigger<OnRemove, PlayingSound>,
+    mut voices: ResMut<Voices>,
+) {
+    voices.finished(trigger.entity());
+}
@@ -0,0 +1,341 @@
+//! Camera following the player, with zoom and screen shake.
+//!
+//! The camera doesn't affect the gameplay, so it runs every frame rather than on fixed ticks.
+
+use std::f32::consts::TAU;
+
+use bevy::{input::mouse::{MouseScrollUnit, MouseWheel}, prelude::*, window::PrimaryWindow};
+
+use crate::{
+    control::PlayerInput,
+    gameplay::{arena::ARENA_BOUNDS, damage::DamageEvent, player::PlayerInfo},
+    settings::Settings,
+    GameState,
+};
+
+/// How the camera follows the player.
+#[derive(Component, Debug, Clone)]
+pub struct CameraRig {
+    /// How quickly the camera catches up, per second. Higher is snappier.
+    pub follow_speed: f32,
+    /// Half size of the area around the camera center, where the player can move without moving the camera.
+    pub dead_zone: Vec2,
+    /// Part of the distance towards the aim point the camera is shifted by.
+    pub look_ahead: f32,
+    pub max_look_ahead: f32,
+    /// Position following the player, without shake.
+    pub position: Vec2,
+    /// Shift of the view towards the point zoomed at, so it stays under the cursor.
+    /// Following the player doesn't reset it, zooming out at the same point does.
+    pub zoom_offset: Vec2,
+    /// Jump to the target instead of moving towards it, e.g. on level start.
+    pub snap: bool,
+}
+
+impl Default for CameraRig {
+    fn default() -> Self {
+        CameraRig {
+            follow_speed: 5.0,
+            dead_zone: Vec2::new(8.0, 6.0),
+            look_ahead: 0.25,
+            max_look_ahead: 40.0,
+            position: Vec2::ZERO,
+            zoom_offset: Vec2::ZERO,
+            snap: true,
+        }
+    }
+}
+
+impl CameraRig {
+    /// Point the camera should look at, shifted from the player towards the aim point.
+    pub fn target(&self, player: Vec2, aim: Option<Vec2>) -> Vec2 {
+        let Some(aim) = aim else {
+            return player;
+        };
+        player + ((aim - player) * self.look_ahead).clamp_length_max(self.max_look_ahead)
+    }
+
+    /// Moves towards the target, when it is out of the dead zone.
+    pub fn follow(&mut self, target: Vec2, delta_secs: f32) {
+        if self.snap {
+            self.snap = false;
+            self.position = target;
+            return;
+        }
+        let offset = target - self.position;
+        let outside_dead_zone = offset.signum() * (offset.abs() - self.dead_zone).max(Vec2::ZERO);
+        // Frame rate independent exponential damping
+        let t = 1.0 - (-self.follow_speed * delta_secs).exp();
+        self.position += outside_dead_zone * t;
+    }
+
+    /// Center of the view without shake.
+    pub fn view_position(&self) -> Vec2 {
+        self.position + self.zoom_offset
+    }
+}
+
+/// Keeps a camera with given visible half size inside the bounds.
+/// When the view is larger than the bounds, it is centered on them.
+pub fn clamp_to_bounds(position: Vec2, half_view: Vec2, bounds: Rect) -> Vec2 {
+    let clamp_axis = |value: f32, half: f32, min: f32, max: f32| {
+        if max - min <= half * 2.0 {
+            (min + max) / 2.0
+        } else {
+            value.clamp(min + half, max - half)
+        }
+    };
+    Vec2::new(
+        clamp_axis(position.x, half_view.x, bounds.min.x, bounds.max.x),
+        clamp_axis(position.y, half_view.y, bounds.min.y, bounds.max.y),
+    )
+}
+
+/// Zoom of the camera, as a number of screen pixels per world unit (i.e. per sprite pixel).
+#[derive(Component, Debug, Clone)]
+pub struct CameraZoom {
+    pub zoom: f32,
+    /// Zoom the camera is moving to.
+    pub target: f32,
+    pub min: f32,
+    pub max: f32,
+    /// How quickly the zoom reaches the target, per second.
+    pub speed: f32,
+}
+
+impl Default for CameraZoom {
+    fn default() -> Self {
+        CameraZoom { zoom: 5.0, target: 5.0, min: 2.0, max: 10.0, speed: 12.0 }
+    }
+}
+
+/// Part of the half view size the view can be shifted from the player by zooming at the cursor.
+pub const MAX_ZOOM_OFFSET: f32 = 0.5;
+
+/// Zoom change of one mouse wheel line (or of one zoom key press).
+pub const ZOOM_STEP: f32 = 1.1;
+
+/// Pixels of trackpad (or browser) scrolling counted as one wheel line.
+pub const PIXELS_PER_SCROLL_LINE: f32 = 40.0;
+
+impl CameraZoom {
+    /// Zooms in (positive) or out (negative) by given number of wheel lines.
+    pub fn scroll(&mut self, lines: f32, pixel_perfect: bool) {
+        if pixel_perfect {
+            // Fractions are kept, so slow trackpad scrolling adds up to whole steps
+            self.target += lines;
+        } else {
+            self.target *= ZOOM_STEP.powf(lines);
+        }
+        self.target = self.target.clamp(self.min, self.max);
+    }
+
+    /// Zoom to move to, whole when pixel perfect.
+    pub fn effective_target(&self, pixel_perfect: bool) -> f32 {
+        if pixel_perfect {
+            self.target.round().clamp(self.min.ceil(), self.max.floor())
+        } else {
+            self.target
+        }
+    }
+
+    /// Moves the zoom towards the target.
+    pub fn tick(&mut self, delta_secs: f32, pixel_perfect: bool) {
+        let target = self.effective_target(pixel_perfect);
+        let t = 1.0 - (-self.speed * delta_secs).exp();
+        self.zoom += (target - self.zoom) * t;
+        if (target - self.zoom).abs() < 0.001 {
+            self.zoom = target;
+        }
+    }
+
+    /// World units per screen pixel, i.e. camera's transform scale.
+    pub fn scale(&self) -> f32 {
+        1.0 / self.zoom
+    }
+}
+
+/// Trauma based screen shake: shake grows with the square of the trauma, which fades over time.
+#[derive(Component, Debug, Clone)]
+pub struct CameraShake {
+    /// From 0 (calm) to 1 (maximum shake).
+    pub trauma: f32,
+    /// Trauma lost per second.
+    pub decay: f32,
+    /// Offset in world units at the maximum shake.
+    pub max_offset: f32,
+    /// Oscillations per second.
+    pub frequency: f32,
+    time: f32,
+}
+
+impl Default for CameraShake {
+    fn default() -> Self {
+        CameraShake { trauma: 0.0, decay: 1.5, max_offset: 6.0, frequency: 8.0, time: 0.0 }
+    }
+}
+
+impl CameraShake {
+    pub fn add_trauma(&mut self, trauma: f32) {
+        self.trauma = (self.trauma + trauma).clamp(0.0, 1.0);
+    }
+
+    pub fn tick(&mut self, delta_secs: f32) {
+        self.time += delta_secs;
+        self.trauma = (self.trauma - self.decay * delta_secs).max(0.0);
+    }
+
+    /// Current offset of the camera.
+    pub fn offset(&self) -> Vec2 {
+        let shake = self.trauma * self.trauma;
+        if shake == 0.0 {
+            return Vec2::ZERO;
+        }
+        // Sums of sines with unrelated frequencies look random enough, while being smooth
+        let wave = |phase: f32| {
+            let t = self.time * self.frequency * TAU;
+            ((t + phase).sin() + (t * 2.3 + phase * 3.1).sin() * 0.5) / 1.5
+        };
+        Vec2::new(wave(0.0), wave(11.0)) * self.max_offset * shake
+    }
+}
+
+/// Shakes the camera, e.g. on explosions. Trauma from 0 to 1 adds up with the current one.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ScreenShake {
+    pub trauma: f32,
+}
+
+/// Trauma added by damage taken by the player, per one point of damage.
+pub const TRAUMA_PER_PLAYER_DAMAGE: f32 = 0.04;
+
+pub struct GameCameraPlugin;
+
+impl Plugin for GameCameraPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<ScreenShake>()
+            .add_systems(Startup, setup_camera)
+            .add_systems(OnEnter(GameState::InGame), reset_camera)
+            .add_systems(Update, (
+                shake_on_player_damage,
+                collect_screen_shakes,
+                zoom_camera,
+                update_camera,
+            ).chain().run_if(in_state(GameState::InGame)));
+    }
+}
+
+fn setup_camera(
+    mut commands: Commands,
+) {
+    let zoom = CameraZoom::default();
+    commands.spawn((
+        Camera2d,
+        Transform::from_scale(Vec3::splat(zoom.scale())),
+        CameraRig::default(),
+        zoom,
+        CameraShake::default(),
+    ));
+}
+
+fn reset_camera(
+    mut camera: Single<(&mut CameraRig, &mut CameraShake)>,
+) {
+    let (rig, shake) = &mut *camera;
+    rig.snap = true;
+    rig.zoom_offset = Vec2::ZERO;
+    shake.trauma = 0.0;
+}
+
+fn shake_on_player_damage(
+    mut damage_events: EventReader<DamageEvent>,
+    mut shake_writer: EventWriter<ScreenShake>,
+    player_query: Query<(), With<PlayerInfo>>,
+) {
+    for event in damage_events.read() {
+        if player_query.contains(event.target) {
+            shake_writer.send(ScreenShake { trauma: event.amount as f32 * TRAUMA_PER_PLAYER_DAMAGE });
+        }
+    }
+}
+
+fn collect_screen_shakes(
+    mut events: EventReader<ScreenShake>,
+    mut shake: Single<&mut CameraShake>,
+    settings: Res<Settings>,
+) {
+    for event in events.read() {
+        shake.add_trauma(event.trauma * settings.accessibility.screen_shake.clamp(0.0, 1.0));
+    }
+}
+
+/// Zooms with the mouse wheel, trackpad or keys, keeping the point under the cursor in place.
+fn zoom_camera(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut wheel_events: EventReader<MouseWheel>,
+    keys: Res<ButtonInput<KeyCode>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    mut camera: Single<(&mut Transform, &mut CameraRig, &mut CameraZoom)>,
+) {
+    let (transform, rig, zoom) = &mut *camera;
+    let pixel_perfect = settings.pixel_perfect_zoom;
+
+    for event in wheel_events.read() {
+        let lines = match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / PIXELS_PER_SCROLL_LINE,
+        };
+        zoom.scroll(lines, pixel_perfect);
+    }
+    if keys.just_pressed(KeyCode::KeyZ) {
+        zoom.scroll(-1.0, pixel_perfect);
+    }
+    if keys.just_pressed(KeyCode::KeyX) {
+        zoom.scroll(1.0, pixel_perfect);
+    }
+
+    let old_scale = zoom.scale();
+    zoom.tick(time.delta_secs(), pixel_perfect);
+    let new_scale = zoom.scale();
+    if new_scale == old_scale {
+        return;
+    }
+    if let Some(cursor) = window.cursor_position() {
+        // Screen y axis points down, world y axis points up
+        let cursor_offset = (cursor - window.size() / 2.0) * Vec2::new(1.0, -1.0);
+        rig.zoom_offset += cursor_offset * (old_scale - new_scale);
+    }
+    transform.scale = Vec3::splat(new_scale);
+}
+
+pub fn update_camera(
+    time: Res<Time>,
+    input: Res<PlayerInput>,
+    settings: Res<Settings>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    player_transform: Single<&Transform, (With<PlayerInfo>, Without<CameraRig>)>,
+    mut camera: Single<(&mut Transform, &mut CameraRig, &mut CameraShake)>,
+) {
+    let (transform, rig, shake) = &mut *camera;
+    let delta_secs = time.delta_secs();
+
+    let target = rig.target(player_transform.translation.xy(), input.aim);
+    rig.follow(target, delta_secs);
+    let half_view = window.size() / 2.0 * transform.scale.xy();
+    rig.position = clamp_to_bounds(rig.position, half_view, ARENA_BOUNDS);
+    // The player stays in view, however far the cursor was when zooming
+    rig.zoom_offset = rig.zoom_offset.clamp(-half_view * MAX_ZOOM_OFFSET, half_view * MAX_ZOOM_OFFSET);
+
+    shake.tick(delta_secs);
+    // Neither zooming nor shaking can show outside of the arena
+    let mut position = clamp_to_bounds(rig.view_position() + shake.offset(), half_view, ARENA_BOUNDS);
+    if settings.pixel_perfect_zoom {
+        // Sprite pixels are aligned with screen pixels only at whole screen pixel positions
+        let scale = transform.scale.x;
+        position = (position / scale).round() * scale;
+    }
+    transform.translation.x = position.x;
+    transform.translation.y = position.y;
+}
@@ -0,0 +1,51 @@
+use std::path::PathBuf;
+
+/// Options that can be passed to the game via command line.
+///
+/// ```sh
+/// bevy-2d-test --seed 42 --record run.replay
+/// bevy-2d-test --replay run.replay
+/// bevy-2d-test --headless --minutes 5 --out stats.json
+/// ```
+#[derive(Debug, Default)]
+pub struct CliArgs {
+    pub seed: Option<u64>,
+    pub record: Option<PathBuf>,
+    pub replay: Option<PathBuf>,
+    pub headless: bool,
+    pub minutes: Option<f64>,
+    pub out: Option<PathBuf>,
+}
+
+impl CliArgs {
+    pub fn parse() -> Self {
+        Self::parse_from(std::env::args().skip(1))
+    }
+
+    pub fn parse_from(args: impl IntoIterator<Item = String>) -> Self {
+        let mut result = CliArgs::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--seed" => {
+                    result.seed = args.next().and_then(|v| v.parse().ok());
+                    if result.seed.is_none() {
+                        eprintln!("--seed expects an unsigned integer");
+                    }
+                },
+                "--record" => result.record = args.next().map(PathBuf::from),
+                "--replay" => result.replay = args.next().map(PathBuf::from),
+                "--headless" => result.headless = true,
+                "--minutes" => {
+                    result.minutes = args.next().and_then(|v| v.parse().ok());
+                    if result.minutes.is_none() {
+                        eprintln!("--minutes expects a number");
+                    }
+                },
+                "--out" => result.out = args.next().map(PathBuf::from),
+                other => eprintln!("Unknown argument: {other}"),
+            }
+        }
+        result
+    }
+}
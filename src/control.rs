@@ -1,4 +1,121 @@
-use bevy::{input::mouse::MouseWheel, prelude::*};
+use std::time::Duration;
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    accessibility::assisted_aim,
+    coords::calc_mouse_world_coord,
+    gameplay::{enemy::EnemyState, player::PlayerInfo},
+    settings::{FireMode, Settings},
+};
+
+/// Time between shots when fire is held or automatic, see [[FireMode]].
+pub const REPEAT_FIRE_INTERVAL: Duration = Duration::from_millis(150);
+
+/// Player commands for a single fixed tick.
+/// Gameplay reads player input only via this resource,
+/// so live input can be replaced with a recorded one (see [[crate::replay]]).
+#[derive(Resource, Default, Clone, Copy, PartialEq, Debug)]
+pub struct PlayerInput {
+    /// Normalized movement direction, or zero if player stands still.
+    pub movement: Vec2,
+    /// World coordinates player aims at.
+    pub aim: Option<Vec2>,
+    /// Whether fire was pressed since previous tick.
+    pub fire: bool,
+}
+
+/// Defines where [[PlayerInput]] comes from.
+#[derive(Resource, Default, PartialEq, Eq, Debug)]
+pub enum InputSource {
+    #[default]
+    Live,
+    Replay,
+    Bot,
+    /// Input is set directly by code, e.g. in tests.
+    Scripted,
+}
+
+/// Keyboard and mouse input collected since the last fixed tick.
+#[derive(Resource, Default)]
+pub struct LiveInput(pub PlayerInput);
+
+/// Builds movement vector from per axis directions (-1, 0 or 1).
+pub fn movement_from_axes(x: i8, y: i8) -> Vec2 {
+    Vec2::new(x as f32, y as f32).normalize_or_zero()
+}
+
+/// Whether a shot is fired this frame. Clicks always shoot, held (or automatic) fire shoots
+/// every [[REPEAT_FIRE_INTERVAL]], with `since_shot` being the time since the last shot.
+pub fn repeat_fire(mode: FireMode, clicked: bool, held: bool, since_shot: &mut Duration, delta: Duration) -> bool {
+    *since_shot = since_shot.saturating_add(delta);
+    let repeating = match mode {
+        FireMode::Click => false,
+        FireMode::Hold => held,
+        FireMode::Auto => true,
+    };
+    let fire = clicked || (repeating && *since_shot >= REPEAT_FIRE_INTERVAL);
+    if fire {
+        *since_shot = Duration::ZERO;
+    }
+    fire
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn collect_live_input(
+    mut live_input: ResMut<LiveInput>,
+    mut since_shot: Local<Duration>,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera: Single<(&Camera, &GlobalTransform)>,
+    player_query: Query<&Transform, With<PlayerInfo>>,
+    enemy_query: Query<(&Transform, &EnemyState)>,
+) {
+    let axis = |negative: KeyCode, positive: KeyCode| keys.pressed(positive) as i8 - keys.pressed(negative) as i8;
+    live_input.0.movement = movement_from_axes(axis(KeyCode::KeyA, KeyCode::KeyD), axis(KeyCode::KeyS, KeyCode::KeyW));
+
+    let (camera, camera_transform) = *camera;
+    if let Some(cursor_position) = window.cursor_position() {
+        if let Some(coord) = calc_mouse_world_coord(cursor_position, camera, camera_transform) {
+            live_input.0.aim = Some(coord.xy());
+
+            let accessibility = &settings.accessibility;
+            if let (true, Ok(player)) = (accessibility.aim_assist, player_query.get_single()) {
+                let enemies = enemy_query.iter()
+                    .filter(|(_, state)| **state == EnemyState::Alive)
+                    .map(|(transform, _)| transform.translation.xy());
+                let aim = assisted_aim(
+                    player.translation.xy(),
+                    coord.xy(),
+                    enemies,
+                    accessibility.aim_assist_angle,
+                    accessibility.aim_assist_strength,
+                );
+                live_input.0.aim = Some(aim);
+            }
+
+            if mouse.just_pressed(MouseButton::Middle) {
+                println!("Clicked coordinates: {coord:?}");
+            }
+        }
+    }
+
+    // Fire presses are accumulated until some fixed tick consumes them
+    let clicked = mouse.just_pressed(MouseButton::Left);
+    let held = mouse.pressed(MouseButton::Left);
+    live_input.0.fire |= repeat_fire(settings.accessibility.fire_mode, clicked, held, &mut since_shot, time.delta());
+}
+
+pub fn take_live_input(
+    mut live_input: ResMut<LiveInput>,
+    mut input: ResMut<PlayerInput>,
+) {
+    *input = live_input.0;
+    live_input.0.fire = false;
+}
 
 pub fn init_cursor(
     mut commands: Commands,
@@ -15,39 +132,3 @@ pub fn init_cursor(
         .insert(cursor_icon);
 
 }
-
-pub fn handle_camera_zoom(
-    mut camera_query: Query<(&Camera, &mut Transform)>,
-    mut evr_scroll: EventReader<MouseWheel>,
-    keys: Res<ButtonInput<KeyCode>>,
-) {
-    let zoom = |camera_transform: &mut Transform, factor: f32| {
-        let current_scale = camera_transform.scale.x;
-        let mut new_scale = ((current_scale + factor) * 100.0).floor() / 100.0;
-        if new_scale < 0.12 {
-            new_scale = 0.12;
-        }
-        camera_transform.scale = Vec3::new(new_scale, new_scale, new_scale);
-    };
-
-    use bevy::input::mouse::MouseScrollUnit;
-    for ev in evr_scroll.read() {
-        match ev.unit {
-            MouseScrollUnit::Line => {
-                let (_, mut camera_transform) = camera_query.single_mut();
-                let factor = ev.y * -0.02;
-                zoom(&mut camera_transform, factor);
-
-            }
-            MouseScrollUnit::Pixel => (),
-        }
-    }
-    if keys.just_pressed(KeyCode::KeyZ) {
-        let (_, mut camera_transform) = camera_query.single_mut();
-        zoom(&mut camera_transform, 0.02);
-    }
-    if keys.just_pressed(KeyCode::KeyX) {
-        let (_, mut camera_transform) = camera_query.single_mut();
-        zoom(&mut camera_transform, -0.02);
-    }
-}
\ No newline at end of file
@@ -15,12 +15,19 @@ pub fn calc_mouse_world_coord(
         return None;
     };
 
-    let Some(distance) = ray.intersect_plane(Vec3::ZERO, plane) else {
-        return None;
-    };
+    let distance = ray.intersect_plane(Vec3::ZERO, plane)?;
 
     let global_cursor = ray.get_point(distance);
 
     Some(global_cursor)
 }
 
+/// Calculates viewport (window) coordinates of the in game world position,
+/// the reverse of [[calc_mouse_world_coord]]. Positions outside of the view give coordinates outside of the viewport.
+pub fn calc_world_viewport_coord(
+    world_position: Vec3,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+) -> Option<Vec2> {
+    camera.world_to_viewport(camera_transform, world_position).ok()
+}
@@ -1,57 +1,117 @@
-use bevy_math::Vec3;
-
-/// Defines possible object orientations (direction is looking at).
-/// \|/
-/// -O-
-/// /|\
-pub enum Direction {
-    Bottom, BottomLeft, Left, TopLeft, Top, TopRight, Right, BottomRight
+use std::f32::consts::{PI, TAU};
+
+use bevy::prelude::*;
+
+/// How many directions an object can face. Each direction covers an equal angle sector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DirectionSet {
+    Four,
+    Eight,
+    Sixteen,
+}
+
+const NAMES_4: [&str; 4] = ["bottom", "left", "top", "right"];
+
+const NAMES_8: [&str; 8] = [
+    "bottom", "bottom_left", "left", "top_left", "top", "top_right", "right", "bottom_right",
+];
+
+const NAMES_16: [&str; 16] = [
+    "bottom", "bottom_bottom_left", "bottom_left", "left_bottom_left",
+    "left", "left_top_left", "top_left", "top_top_left",
+    "top", "top_top_right", "top_right", "right_top_right",
+    "right", "right_bottom_right", "bottom_right", "bottom_bottom_right",
+];
+
+impl DirectionSet {
+    pub fn sectors(self) -> u8 {
+        self.names().len() as u8
+    }
+
+    /// Direction names, clockwise starting from the bottom.
+    pub fn names(self) -> &'static [&'static str] {
+        match self {
+            DirectionSet::Four => &NAMES_4,
+            DirectionSet::Eight => &NAMES_8,
+            DirectionSet::Sixteen => &NAMES_16,
+        }
+    }
+
+    fn sector_width(self) -> f32 {
+        TAU / self.sectors() as f32
+    }
 }
 
-const ANGLE_THRESHOLD: f32 = 0.85;
+/// How far (in radians) past a sector boundary the facing vector should go to switch the direction.
+/// Keeps sprites from flickering when aiming along a boundary.
+pub const DEFAULT_HYSTERESIS: f32 = 0.1;
 
-/// Calculates a direction given vector points to.
-/// 
+/// Direction an object is looking at.
+/// Sectors are counted clockwise starting from the bottom one.
 /// ```ascii
-///                    ^
-///                    |TOP
-///             , - ~ ~|~ - ,
-///          , '-------|-------'-
-///        , |         |0.85    |  ,
-///       ,  |  TOP    | TOP    |   ,
-///      ,   |  LEFT   | RIGHT  |0.85,
-///     -----|---------+--------|------>
-///  LEFT,   | BOTTOM  | BOTTOM |    ,RIGHT
-///       ,  |  LEFT   | RIGHT  |   ,
-///        , |         |        |  ,
-///          ,------------------, '
-///            ' - , _ | _ ,  '
-///                    BOTTOM
+///          top
+///           |
+///   left ---O--- right
+///           |
+///        bottom
 /// ```
-pub fn direction_of_vector(Vec3 {x, y, z: _} : Vec3) -> Direction {
-    if y > 0.0 {
-        if y >= ANGLE_THRESHOLD {
-            Direction::Top   
-        } else if x >= ANGLE_THRESHOLD {
-            Direction::Right
-        } else if x <= -ANGLE_THRESHOLD {
-            Direction::Left
-        } else if x > 0.0 {
-            Direction::TopRight
-        } else {
-            Direction::TopLeft
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Facing {
+    pub set: DirectionSet,
+    pub sector: u8,
+    pub hysteresis: f32,
+}
+
+impl Facing {
+    /// Facing the bottom.
+    pub fn new(set: DirectionSet) -> Self {
+        Facing { set, sector: 0, hysteresis: DEFAULT_HYSTERESIS }
+    }
+
+    pub fn of_vector(set: DirectionSet, v: Vec2) -> Self {
+        let mut facing = Facing::new(set);
+        facing.sector = sector_of_angle(angle_of_vector(v), set);
+        facing
+    }
+
+    /// Turns towards the vector, unless it is still close enough to the current direction.
+    /// Zero vector doesn't change the facing. Returns whether the direction has changed.
+    pub fn turn_towards(&mut self, v: Vec2) -> bool {
+        if v == Vec2::ZERO {
+            return false;
+        }
+        let angle = angle_of_vector(v);
+        let center = self.sector as f32 * self.set.sector_width();
+        let mut diff = (angle - center).abs() % TAU;
+        if diff > PI {
+            diff = TAU - diff;
         }
-    } else {
-        if y <= -ANGLE_THRESHOLD {
-            Direction::Bottom
-        } else if x >= ANGLE_THRESHOLD {
-            Direction::Right
-        } else if x <= -ANGLE_THRESHOLD {
-            Direction::Left
-        } else if x > 0.0 {
-            Direction::BottomRight
-        } else {
-            Direction::BottomLeft
+        if diff > self.set.sector_width() / 2.0 + self.hysteresis {
+            let sector = sector_of_angle(angle, self.set);
+            let changed = sector != self.sector;
+            self.sector = sector;
+            return changed;
         }
+        false
+    }
+
+    /// Name used as a suffix of directional animation clips, e.g. "top_left" in "walk_top_left".
+    pub fn name(&self) -> &'static str {
+        self.set.names()[self.sector as usize]
+    }
+
+    /// Facing mirrored horizontally, e.g. left for right.
+    pub fn mirrored(&self) -> Facing {
+        let sectors = self.set.sectors();
+        Facing { sector: (sectors - self.sector) % sectors, ..*self }
     }
 }
+
+/// Angle clockwise from the bottom direction, in [0, 2π).
+fn angle_of_vector(Vec2 { x, y }: Vec2) -> f32 {
+    (-x).atan2(-y).rem_euclid(TAU)
+}
+
+fn sector_of_angle(angle: f32, set: DirectionSet) -> u8 {
+    ((angle / set.sector_width()).round() as u8) % set.sectors()
+}
@@ -1,9 +1,33 @@
 use bevy::prelude::*;
-use bevy_rapier2d::{plugin::{NoUserData, RapierPhysicsPlugin}, render::RapierDebugRenderPlugin};
+use bevy_rapier2d::render::RapierDebugRenderPlugin;
+use serde::Deserialize;
 
-use crate::{control::init_cursor, gameplay::MyGameplayPlugin, menu::{end_menu::{handle_restart_game, setup_end_screen}, start_menu::{handle_start_game, setup_start_screen}}};
+use crate::{
+    accessibility::AccessibilityPlugin,
+    animation::sync_animation_sprites,
+    audio::GameAudioPlugin,
+    camera::GameCameraPlugin,
+    control::{collect_live_input, init_cursor, InputSource},
+    gameplay::{effects::HitEffectsPlugin, physics_plugin, visuals::GameplayVisualsPlugin, MyGameplayPlugin},
+    high_scores::{record_high_score, HighScoresPlugin},
+    menu::{
+        minimap::MinimapPlugin,
+        threat_indicators::ThreatIndicatorsPlugin,
+        debug_overlay::{setup_debug_overlay, toggle_debug_overlay, update_debug_overlay},
+        end_menu::{handle_end_menu, setup_end_screen, EndScreenElement},
+        in_game_menu::HudPlugin,
+        navigation::{despawn_screen, MenuNavigationPlugin},
+        options_menu::{handle_options, setup_accessibility_screen, setup_options_screen, update_option_labels},
+        start_menu::{handle_main_menu, setup_credits_screen, setup_high_scores_screen, setup_start_screen, MenuPage, StartScreenElement},
+    },
+    localization::LocalizationPlugin,
+    music::MusicPlugin,
+    particles::ParticlePlugin,
+    replay::control_playback_speed,
+    settings::SettingsPlugin,
+};
 
-#[derive(States, PartialEq, Eq, Debug, Clone, Hash, Default)]
+#[derive(States, PartialEq, Eq, Debug, Clone, Hash, Default, Deserialize)]
 pub enum GameState {
     #[default]
     MainMenu,
@@ -11,41 +35,52 @@ pub enum GameState {
     End,
 }
 
+/// The game with window, sprites, sounds, UI and menus.
 pub struct MyGamePlugin;
 
 impl Plugin for MyGamePlugin {
     fn build(&self, app: &mut App) {
         // Adding Rapier physics
-        app.add_plugins(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0).in_fixed_schedule());
-
-        // https://bevy-cheatbook.github.io/fundamentals/fixed-timestep.html
-        app.insert_resource(Time::<Fixed>::from_hz(64.0));
+        app.add_plugins(physics_plugin());
 
         #[cfg(debug_assertions)]
         app.add_plugins(RapierDebugRenderPlugin::default()); // Uncomment to see collider boxes
 
 
-        app.add_plugins(MyGameplayPlugin);
+        app.add_plugins((MyGameplayPlugin, GameplayVisualsPlugin, SettingsPlugin, GameCameraPlugin, MinimapPlugin, ThreatIndicatorsPlugin, HitEffectsPlugin, ParticlePlugin, GameAudioPlugin, MusicPlugin, LocalizationPlugin));
+        app.add_plugins((MenuNavigationPlugin, HighScoresPlugin, HudPlugin, AccessibilityPlugin));
 
         app
-            .init_state::<GameState>()
-    
+            .add_systems(OnEnter(GameState::InGame), setup_debug_overlay)
+            .add_systems(Update, 
+                (
+                    collect_live_input.run_if(resource_equals(InputSource::Live)),
+                    control_playback_speed.run_if(resource_equals(InputSource::Replay)),
+                    sync_animation_sprites,
+                    toggle_debug_overlay,
+                    update_debug_overlay,
+                ).run_if(in_state(GameState::InGame))
+            )
+
             .add_systems(Startup, init_cursor)
-            .add_systems(Startup, setup_camera)
     
-            .add_systems(Startup, setup_start_screen.run_if(in_state(GameState::MainMenu)))
-            .add_systems(Update, handle_start_game.run_if(in_state(GameState::MainMenu)))
+            .add_sub_state::<MenuPage>()
+            .add_systems(OnEnter(MenuPage::Main), setup_start_screen)
+            .add_systems(OnEnter(MenuPage::Options), setup_options_screen)
+            .add_systems(OnEnter(MenuPage::Accessibility), setup_accessibility_screen)
+            .add_systems(OnEnter(MenuPage::HighScores), setup_high_scores_screen)
+            .add_systems(OnEnter(MenuPage::Credits), setup_credits_screen)
+            .add_systems(OnExit(MenuPage::Main), despawn_screen::<StartScreenElement>)
+            .add_systems(OnExit(MenuPage::Options), despawn_screen::<StartScreenElement>)
+            .add_systems(OnExit(MenuPage::Accessibility), despawn_screen::<StartScreenElement>)
+            .add_systems(OnExit(MenuPage::HighScores), despawn_screen::<StartScreenElement>)
+            .add_systems(OnExit(MenuPage::Credits), despawn_screen::<StartScreenElement>)
+            .add_systems(Update, handle_main_menu.run_if(in_state(GameState::MainMenu)))
+            .add_systems(Update, (handle_options, update_option_labels).chain()
+                .run_if(in_state(MenuPage::Options).or(in_state(MenuPage::Accessibility))))
     
-            .add_systems(OnEnter(GameState::End), setup_end_screen)
-            .add_systems(Update, handle_restart_game.run_if(in_state(GameState::End)));
+            .add_systems(OnEnter(GameState::End), (record_high_score, setup_end_screen).chain())
+            .add_systems(OnExit(GameState::End), despawn_screen::<EndScreenElement>)
+            .add_systems(Update, handle_end_menu.run_if(in_state(GameState::End)));
     }
 }
-
-fn setup_camera(
-    mut commands: Commands,
-) {
-    commands.spawn((
-        Camera2d,
-        Transform::from_scale(Vec3::ONE * 0.2),
-    ));
-}
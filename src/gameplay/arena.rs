@@ -1,28 +1,17 @@
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
-use super::{GRP_ENEMY, GRP_ENVIRONMENT, GRP_PLAYER, GRP_PLAYER_BULLET, CollidingObj, LevelComponents};
+use super::{GRP_ENEMY, GRP_ENVIRONMENT, GRP_PLAYER, GRP_PLAYER_BULLET, LevelComponents};
 
-const TEXTURE_ARENA: &str = "sprites/arena.png";
+/// Area covered by the arena background.
+pub const ARENA_BOUNDS: Rect = Rect {
+    min: Vec2::new(-384.0, -384.0),
+    max: Vec2::new(384.0, 384.0),
+};
 
 pub fn setup_arena(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
 ) {
-    let arena_texture: Handle<Image> = asset_server.load(TEXTURE_ARENA);
-
-    // Create arena background
-    {
-        commands.spawn((
-            Sprite {
-                image: arena_texture,
-                .. default()
-            },
-            Transform::from_translation(Vec3::new(0.0, 0.0, -100.0)),
-            LevelComponents,
-        ));
-    }
-
     // Scene colliders
     {
         let collision_group = CollisionGroups::new(
@@ -35,7 +24,6 @@ pub fn setup_arena(
             collision_group,
             Transform::from_translation(Vec3::new(0.0, 330.0, 0.0)),
             LevelComponents,
-            CollidingObj::Environment,
         ));
         commands.spawn(( // bottom border
             RigidBody::Fixed,
@@ -43,7 +31,6 @@ pub fn setup_arena(
             collision_group,
             Transform::from_translation(Vec3::new(0.0, -390.0, 0.0)),
             LevelComponents,
-            CollidingObj::Environment,
         ));
         commands.spawn(( // left border
             RigidBody::Fixed,
@@ -51,7 +38,6 @@ pub fn setup_arena(
             collision_group,
             Transform::from_translation(Vec3::new(-380.0, 0.0, 0.0)),
             LevelComponents,
-            CollidingObj::Environment,
         ));
         commands.spawn(( // right border
             RigidBody::Fixed,
@@ -59,7 +45,6 @@ pub fn setup_arena(
             collision_group,
             Transform::from_translation(Vec3::new(380.0, 0.0, 0.0)),
             LevelComponents,
-            CollidingObj::Environment,
         ));
     }
 }
\ No newline at end of file
@@ -0,0 +1,196 @@
+use bevy::{prelude::*, utils::HashMap};
+use bevy_rapier2d::prelude::*;
+use rand::Rng;
+
+use crate::rng::GameRng;
+
+use super::player::{Bullet, BulletCollided, BulletHitWall};
+
+/// Damage of a critical hit is the regular damage multiplied by this.
+pub const CRIT_MULTIPLIER: u32 = 2;
+
+/// Side an object fights for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Faction {
+    Player,
+    Enemy,
+}
+
+/// Deals damage to [[Hurtbox]]es it collides with.
+#[derive(Component, Debug)]
+pub struct Hitbox {
+    pub damage: u32,
+    pub faction: Faction,
+    /// Probability from 0 to 1 of dealing a critical hit, see [[CRIT_MULTIPLIER]].
+    pub crit_chance: f32,
+}
+
+/// Receives damage from [[Hitbox]]es it collides with.
+#[derive(Component, Debug)]
+pub struct Hurtbox {
+    pub faction: Faction,
+}
+
+#[derive(Component, Debug)]
+pub struct Health {
+    pub current: u32,
+    pub max: u32,
+}
+
+impl Health {
+    pub fn new(max: u32) -> Self {
+        Health { current: max, max }
+    }
+}
+
+/// Object dies once its [[Hitbox]] dealt damage (e.g. slime explodes on contact).
+#[derive(Component, Debug)]
+pub struct ConsumedOnHit;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DamageKind {
+    Projectile,
+    Contact,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct DamageEvent {
+    pub source: Entity,
+    pub target: Entity,
+    pub amount: u32,
+    pub kind: DamageKind,
+    pub critical: bool,
+}
+
+/// Object's health dropped to zero.
+#[derive(Event, Clone, Debug)]
+pub struct DeathEvent {
+    pub entity: Entity,
+    /// Faction of the object that dealt the lethal damage, if any.
+    pub killer_faction: Option<Faction>,
+}
+
+/// Defines which factions can damage which.
+/// By default factions damage each other, but not themselves (no friendly fire).
+#[derive(Resource, Default, Debug)]
+pub struct DamageRules {
+    /// Overrides of the default rule for (attacker, target) faction pairs.
+    overrides: HashMap<(Faction, Faction), bool>,
+}
+
+impl DamageRules {
+    pub fn can_damage(&self, attacker: Faction, target: Faction) -> bool {
+        self.overrides.get(&(attacker, target)).copied().unwrap_or(attacker != target)
+    }
+
+    pub fn set_can_damage(&mut self, attacker: Faction, target: Faction, allowed: bool) {
+        self.overrides.insert((attacker, target), allowed);
+    }
+}
+
+/// Lets objects that both deal and take damage report collisions with each other only when
+/// their faction can damage itself (friendly fire), so touching slimes don't flood [[handle_collision]].
+/// Collisions with the player and bullets are reported by those.
+pub fn enable_friendly_fire_events(
+    mut commands: Commands,
+    rules: Res<DamageRules>,
+    hitbox_query: Query<(Entity, Ref<Hitbox>, Has<ActiveEvents>), With<Hurtbox>>,
+) {
+    for (entity, hitbox, has_events) in &hitbox_query {
+        if !rules.is_changed() && !hitbox.is_added() {
+            continue;
+        }
+        let friendly_fire = rules.can_damage(hitbox.faction, hitbox.faction);
+        if friendly_fire && !has_events {
+            commands.entity(entity).insert(ActiveEvents::COLLISION_EVENTS);
+        } else if !friendly_fire && has_events {
+            commands.entity(entity).remove::<ActiveEvents>();
+        }
+    }
+}
+
+/// Turns physical collisions into damage.
+#[allow(clippy::too_many_arguments)]
+pub fn handle_collision(
+    mut collision_events: EventReader<CollisionEvent>,
+    mut damage_writer: EventWriter<DamageEvent>,
+    mut bullet_collided: EventWriter<BulletCollided>,
+    mut wall_hit_writer: EventWriter<BulletHitWall>,
+    rules: Res<DamageRules>,
+    mut rng: ResMut<GameRng>,
+    hitbox_query: Query<(&Hitbox, Has<Bullet>)>,
+    hurtbox_query: Query<&Hurtbox>,
+    bullet_query: Query<(&Transform, &Velocity), With<Bullet>>,
+) {
+    for collision_event in collision_events.read() {
+        if let &CollisionEvent::Started(c1, c2 , _) = collision_event {
+            for (source, target) in [(c1, c2), (c2, c1)] {
+                let Ok((hitbox, is_bullet)) = hitbox_query.get(source) else {
+                    continue;
+                };
+                // Bullets are destroyed by anything they hit
+                if is_bullet {
+                    bullet_collided.send(BulletCollided(source));
+                }
+                let Ok(hurtbox) = hurtbox_query.get(target) else {
+                    // Anything that can't be damaged is a wall for bullets
+                    if let Ok((transform, velocity)) = bullet_query.get(source) {
+                        wall_hit_writer.send(BulletHitWall {
+                            position: transform.translation.xy(),
+                            direction: velocity.linvel.normalize_or_zero(),
+                        });
+                    }
+                    continue;
+                };
+                if rules.can_damage(hitbox.faction, hurtbox.faction) {
+                    // Hitboxes that can't crit don't take numbers from the stream
+                    let critical = hitbox.crit_chance > 0.0 && rng.combat.gen_bool(hitbox.crit_chance.min(1.0) as f64);
+                    damage_writer.send(DamageEvent {
+                        source,
+                        target,
+                        amount: if critical { hitbox.damage * CRIT_MULTIPLIER } else { hitbox.damage },
+                        kind: if is_bullet { DamageKind::Projectile } else { DamageKind::Contact },
+                        critical,
+                    });
+                }
+            }
+        }
+    }
+}
+
+pub fn apply_damage(
+    mut damage_events: EventReader<DamageEvent>,
+    mut death_writer: EventWriter<DeathEvent>,
+    mut health_query: Query<&mut Health>,
+    hitbox_query: Query<&Hitbox>,
+) {
+    for event in damage_events.read() {
+        if let Ok(mut health) = health_query.get_mut(event.target) {
+            if health.current == 0 {
+                continue;
+            }
+            health.current = health.current.saturating_sub(event.amount);
+            if health.current == 0 {
+                death_writer.send(DeathEvent {
+                    entity: event.target,
+                    killer_faction: hitbox_query.get(event.source).ok().map(|h| h.faction),
+                });
+            }
+        }
+    }
+}
+
+pub fn consume_hitboxes(
+    mut damage_events: EventReader<DamageEvent>,
+    mut death_writer: EventWriter<DeathEvent>,
+    mut consumed_query: Query<&mut Health, With<ConsumedOnHit>>,
+) {
+    for event in damage_events.read() {
+        if let Ok(mut health) = consumed_query.get_mut(event.source) {
+            if health.current > 0 {
+                health.current = 0;
+                death_writer.send(DeathEvent { entity: event.source, killer_faction: None });
+            }
+        }
+    }
+}
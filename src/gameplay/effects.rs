@@ -0,0 +1,490 @@
+//! Hit feedback: damage numbers, hit flash, hit-stop on kills and muzzle flash,
+//! as well as particles of slime splats, bullet impacts and trails, and slime stains on the floor.
+//!
+//! Effects only show what happened in the gameplay, hit feedback can be turned off in [[Settings::effects]].
+//! Short living effect objects are reused through [[EntityPool]]s, like bullets.
+
+use std::{collections::VecDeque, time::Duration};
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    animation::{advance_animations, AnimationFrameEvent},
+    particles::{emit_particles, ParticleEffect, ParticleEmitter},
+    rng::GameRng,
+    settings::Settings,
+    GameState,
+};
+
+use super::{
+    damage::{apply_damage, handle_collision, DamageEvent, DeathEvent, Faction},
+    enemy::{start_enemy_dying, EnemyState, FRAME_EVENT_SPLAT},
+    player::{execute_player_behavior, Bullet, BulletHitWall, WeaponFired},
+    pool::EntityPool,
+    LevelComponents,
+};
+
+const TEXTURE_MUZZLE_FLASH: &str = "sprites/muzzle_flash.png";
+
+const FONT_DAMAGE_NUMBER: &str = "fonts/DejaVuSans-Bold.ttf";
+
+const TEXTURE_SLIME_DECAL: &str = "sprites/slime_decal.png";
+
+const PARTICLES_SLIME_SPLAT: &str = "particles/slime_splat.particle.json";
+
+const PARTICLES_BULLET_IMPACT: &str = "particles/bullet_impact.particle.json";
+
+const PARTICLES_BULLET_TRAIL: &str = "particles/bullet_trail.particle.json";
+
+const DAMAGE_NUMBER_LIFETIME_SECS: f32 = 0.6;
+
+const DAMAGE_NUMBER_RISE_SPEED: f32 = 40.0;
+
+const DAMAGE_NUMBER_FONT_SIZE: f32 = 7.0;
+
+const CRIT_NUMBER_FONT_SIZE: f32 = 10.0;
+
+const COLOR_DAMAGE_NUMBER: Color = Color::srgb(1.0, 1.0, 1.0);
+
+const COLOR_CRIT_NUMBER: Color = Color::srgb(1.0, 0.35, 0.1);
+
+const HIT_FLASH_DURATION: Duration = Duration::from_millis(100);
+
+/// Sprite color multiplier at the start of a flash. It is far above 1, so any non black pixel turns white.
+const HIT_FLASH_BRIGHTNESS: f32 = 20.0;
+
+/// Hit flash brightness with [[crate::settings::AccessibilitySettings::reduced_flashes]].
+const REDUCED_HIT_FLASH_BRIGHTNESS: f32 = 2.0;
+
+/// Muzzle flash opacity with reduced flashes.
+const REDUCED_MUZZLE_FLASH_ALPHA: f32 = 0.35;
+
+/// Real (not game) time the game is frozen for on a kill.
+pub const HIT_STOP_DURATION: Duration = Duration::from_millis(60);
+
+const MUZZLE_FLASH_DURATION: Duration = Duration::from_millis(50);
+
+/// Distance from the player center to the gun barrel.
+const MUZZLE_OFFSET: f32 = 10.0;
+
+const DAMAGE_NUMBERS_CAPACITY: usize = 64;
+
+const MUZZLE_FLASHES_CAPACITY: usize = 16;
+
+const BURST_EMITTERS_CAPACITY: usize = 32;
+
+/// Slime stains on the floor at once, the oldest are removed first.
+pub const MAX_DECALS: usize = 48;
+
+const DECAL_LIFETIME: Duration = Duration::from_secs(20);
+
+/// Decals fade out during this last part of their lifetime.
+const DECAL_FADE_SECS: f32 = 5.0;
+
+/// Above the arena background, below everything else.
+const DECAL_Z: f32 = -50.0;
+
+/// Floating number of damage dealt to an enemy.
+#[derive(Component)]
+pub struct DamageNumber {
+    pub timer: Timer,
+}
+
+#[derive(Component)]
+pub struct MuzzleFlash {
+    pub timer: Timer,
+}
+
+/// Enemy blinking white after being hit.
+#[derive(Component)]
+pub struct HitFlash {
+    pub timer: Timer,
+}
+
+/// Emitter of a single burst of particles (e.g. a splat), released to the [[BurstEmitterPool]] once it's emitted.
+#[derive(Component)]
+pub struct BurstEmitter;
+
+/// Slime stain on the floor.
+#[derive(Component)]
+pub struct Decal {
+    pub timer: Timer,
+}
+
+/// Decals currently on the floor, oldest first.
+#[derive(Resource, Debug)]
+pub struct Decals {
+    entities: VecDeque<Entity>,
+    pub capacity: usize,
+}
+
+impl Decals {
+    pub fn new(capacity: usize) -> Self {
+        Decals { entities: VecDeque::new(), capacity }
+    }
+
+    /// Adds a decal, returns the oldest one when there are too many, to be released.
+    pub fn push(&mut self, entity: Entity) -> Option<Entity> {
+        self.entities.push_back(entity);
+        if self.entities.len() > self.capacity {
+            self.entities.pop_front()
+        } else {
+            None
+        }
+    }
+
+    pub fn remove(&mut self, entity: Entity) {
+        self.entities.retain(|e| *e != entity);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+}
+
+pub type DamageNumberPool = EntityPool<DamageNumber>;
+
+pub type MuzzleFlashPool = EntityPool<MuzzleFlash>;
+
+pub type BurstEmitterPool = EntityPool<(ParticleEmitter, BurstEmitter)>;
+
+pub type DecalPool = EntityPool<Decal>;
+
+/// Freeze of the game on kills, the virtual time is paused while it lasts.
+#[derive(Resource, Default)]
+pub struct HitStop {
+    /// Real time left, none when the game isn't frozen by a hit-stop.
+    pub remaining: Option<Duration>,
+}
+
+#[derive(Resource)]
+pub struct EffectAssets {
+    pub muzzle_flash: Handle<Image>,
+    pub font: Handle<Font>,
+    pub slime_decal: Handle<Image>,
+    pub slime_splat: Handle<ParticleEffect>,
+    pub bullet_impact: Handle<ParticleEffect>,
+    pub bullet_trail: Handle<ParticleEffect>,
+}
+
+pub struct HitEffectsPlugin;
+
+impl Plugin for HitEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<HitStop>()
+            .add_systems(Startup, load_effect_assets)
+            .add_systems(OnEnter(GameState::InGame), setup_effect_pools)
+            .add_systems(OnExit(GameState::InGame), end_hit_stop)
+            .add_systems(FixedUpdate, (
+                spawn_muzzle_flashes.after(execute_player_behavior),
+                (spawn_damage_numbers, start_hit_flashes).after(apply_damage),
+                start_hit_stop.after(start_enemy_dying),
+                spawn_wall_impacts.after(handle_collision),
+                spawn_slime_splats.after(advance_animations),
+            ).run_if(in_state(GameState::InGame)))
+            .add_systems(Update, (
+                update_hit_stop,
+                animate_damage_numbers,
+                animate_hit_flashes,
+                animate_muzzle_flashes,
+                fade_decals,
+                release_burst_emitters.after(emit_particles),
+            ).run_if(in_state(GameState::InGame)))
+            .add_observer(add_bullet_trail);
+    }
+}
+
+fn load_effect_assets(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    commands.insert_resource(EffectAssets {
+        muzzle_flash: asset_server.load(TEXTURE_MUZZLE_FLASH),
+        font: asset_server.load(FONT_DAMAGE_NUMBER),
+        slime_decal: asset_server.load(TEXTURE_SLIME_DECAL),
+        slime_splat: asset_server.load(PARTICLES_SLIME_SPLAT),
+        bullet_impact: asset_server.load(PARTICLES_BULLET_IMPACT),
+        bullet_trail: asset_server.load(PARTICLES_BULLET_TRAIL),
+    });
+}
+
+fn setup_effect_pools(
+    mut commands: Commands,
+) {
+    commands.insert_resource(DamageNumberPool::new(DAMAGE_NUMBERS_CAPACITY));
+    commands.insert_resource(MuzzleFlashPool::new(MUZZLE_FLASHES_CAPACITY));
+    commands.insert_resource(BurstEmitterPool::new(BURST_EMITTERS_CAPACITY));
+    commands.insert_resource(DecalPool::new(MAX_DECALS));
+    commands.insert_resource(Decals::new(MAX_DECALS));
+}
+
+fn spawn_damage_numbers(
+    mut commands: Commands,
+    mut events: EventReader<DamageEvent>,
+    settings: Res<Settings>,
+    assets: Res<EffectAssets>,
+    mut pool: ResMut<DamageNumberPool>,
+    enemy_query: Query<&Transform, With<EnemyState>>,
+) {
+    for event in events.read() {
+        if !settings.effects.damage_numbers {
+            continue;
+        }
+        let Ok(transform) = enemy_query.get(event.target) else {
+            continue;
+        };
+        let (text, font_size, color) = if event.critical {
+            (format!("{}!", event.amount), CRIT_NUMBER_FONT_SIZE, COLOR_CRIT_NUMBER)
+        } else {
+            (event.amount.to_string(), DAMAGE_NUMBER_FONT_SIZE, COLOR_DAMAGE_NUMBER)
+        };
+        pool.acquire(&mut commands, (
+            Text2d::new(text),
+            TextFont { font: assets.font.clone(), font_size, ..default() },
+            TextColor(color),
+            // Above the enemy and its score popup
+            Transform::from_translation((transform.translation.xy() + Vec2::Y * 8.0).extend(11.0)),
+            DamageNumber { timer: Timer::from_seconds(DAMAGE_NUMBER_LIFETIME_SECS, TimerMode::Once) },
+            LevelComponents,
+        ));
+    }
+}
+
+/// Numbers float up and fade out.
+fn animate_damage_numbers(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pool: ResMut<DamageNumberPool>,
+    mut numbers: Query<(Entity, &mut DamageNumber, &mut Transform, &mut TextColor)>,
+) {
+    for (entity, mut number, mut transform, mut color) in &mut numbers {
+        number.timer.tick(time.delta());
+        if number.timer.just_finished() {
+            pool.release(&mut commands, entity);
+            continue;
+        }
+        transform.translation.y += DAMAGE_NUMBER_RISE_SPEED * time.delta_secs();
+        color.0.set_alpha(1.0 - number.timer.fraction());
+    }
+}
+
+fn start_hit_flashes(
+    mut commands: Commands,
+    mut events: EventReader<DamageEvent>,
+    settings: Res<Settings>,
+    enemy_query: Query<(), With<EnemyState>>,
+) {
+    for event in events.read() {
+        if settings.effects.hit_flash && enemy_query.contains(event.target) {
+            commands.entity(event.target).insert(HitFlash { timer: Timer::new(HIT_FLASH_DURATION, TimerMode::Once) });
+        }
+    }
+}
+
+fn animate_hit_flashes(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut flashes: Query<(Entity, &mut HitFlash, &mut Sprite)>,
+) {
+    let max_brightness = if settings.accessibility.reduced_flashes { REDUCED_HIT_FLASH_BRIGHTNESS } else { HIT_FLASH_BRIGHTNESS };
+    for (entity, mut flash, mut sprite) in &mut flashes {
+        flash.timer.tick(time.delta());
+        if flash.timer.finished() {
+            sprite.color = Color::WHITE;
+            commands.entity(entity).remove::<HitFlash>();
+            continue;
+        }
+        let brightness = 1.0 + (max_brightness - 1.0) * (1.0 - flash.timer.fraction());
+        sprite.color = Color::linear_rgb(brightness, brightness, brightness);
+    }
+}
+
+fn start_hit_stop(
+    mut events: EventReader<DeathEvent>,
+    settings: Res<Settings>,
+    mut hit_stop: ResMut<HitStop>,
+    mut time: ResMut<Time<Virtual>>,
+    enemy_query: Query<(), With<EnemyState>>,
+) {
+    let kills = events.read()
+        .any(|e| e.killer_faction == Some(Faction::Player) && enemy_query.contains(e.entity));
+    if !kills || !settings.effects.hit_stop {
+        return;
+    }
+    // Already paused by something else (e.g. a pause menu), it shouldn't be resumed by the hit-stop
+    if time.is_paused() && hit_stop.remaining.is_none() {
+        return;
+    }
+    time.pause();
+    hit_stop.remaining = Some(HIT_STOP_DURATION);
+}
+
+fn update_hit_stop(
+    real_time: Res<Time<Real>>,
+    mut hit_stop: ResMut<HitStop>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    let Some(remaining) = hit_stop.remaining else {
+        return;
+    };
+    let remaining = remaining.saturating_sub(real_time.delta());
+    if remaining.is_zero() {
+        hit_stop.remaining = None;
+        time.unpause();
+    } else {
+        hit_stop.remaining = Some(remaining);
+    }
+}
+
+fn end_hit_stop(
+    mut hit_stop: ResMut<HitStop>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    if hit_stop.remaining.take().is_some() {
+        time.unpause();
+    }
+}
+
+fn spawn_muzzle_flashes(
+    mut commands: Commands,
+    mut events: EventReader<WeaponFired>,
+    settings: Res<Settings>,
+    assets: Res<EffectAssets>,
+    mut pool: ResMut<MuzzleFlashPool>,
+) {
+    for event in events.read() {
+        if !settings.effects.muzzle_flash {
+            continue;
+        }
+        let position = event.position + event.direction * MUZZLE_OFFSET;
+        let alpha = if settings.accessibility.reduced_flashes { REDUCED_MUZZLE_FLASH_ALPHA } else { 1.0 };
+        pool.acquire(&mut commands, (
+            Sprite { color: Color::WHITE.with_alpha(alpha), ..Sprite::from_image(assets.muzzle_flash.clone()) },
+            Transform::from_translation(position.extend(5.0))
+                .with_rotation(Quat::from_rotation_z(event.direction.to_angle())),
+            MuzzleFlash { timer: Timer::new(MUZZLE_FLASH_DURATION, TimerMode::Once) },
+            LevelComponents,
+        ));
+    }
+}
+
+fn animate_muzzle_flashes(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pool: ResMut<MuzzleFlashPool>,
+    mut flashes: Query<(Entity, &mut MuzzleFlash, &mut Transform)>,
+) {
+    for (entity, mut flash, mut transform) in &mut flashes {
+        flash.timer.tick(time.delta());
+        if flash.timer.just_finished() {
+            pool.release(&mut commands, entity);
+            continue;
+        }
+        transform.scale = Vec3::splat(1.0 - flash.timer.fraction() * 0.5);
+    }
+}
+
+/// Bullets leave a trail while they fly. Bullets are pooled, so it's added each time one is fired.
+fn add_bullet_trail(
+    trigger: Trigger<OnAdd, Bullet>,
+    mut commands: Commands,
+    assets: Res<EffectAssets>,
+) {
+    commands.entity(trigger.entity()).insert(ParticleEmitter::new(assets.bullet_trail.clone()));
+}
+
+fn spawn_wall_impacts(
+    mut commands: Commands,
+    mut events: EventReader<BulletHitWall>,
+    assets: Res<EffectAssets>,
+    mut pool: ResMut<BurstEmitterPool>,
+) {
+    for event in events.read() {
+        // Sparks fly back from the wall
+        pool.acquire(&mut commands, (
+            ParticleEmitter::new(assets.bullet_impact.clone()),
+            BurstEmitter,
+            Transform::from_translation(event.position.extend(5.0))
+                .with_rotation(Quat::from_rotation_z((-event.direction).to_angle())),
+            LevelComponents,
+        ));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_slime_splats(
+    mut commands: Commands,
+    mut events: EventReader<AnimationFrameEvent>,
+    assets: Res<EffectAssets>,
+    mut emitter_pool: ResMut<BurstEmitterPool>,
+    mut decal_pool: ResMut<DecalPool>,
+    mut decals: ResMut<Decals>,
+    mut rng: ResMut<GameRng>,
+    enemy_query: Query<&Transform, With<EnemyState>>,
+) {
+    for event in events.read() {
+        if event.name != FRAME_EVENT_SPLAT {
+            continue;
+        }
+        let Ok(transform) = enemy_query.get(event.entity) else {
+            continue;
+        };
+        let position = transform.translation.xy();
+        emitter_pool.acquire(&mut commands, (
+            ParticleEmitter::new(assets.slime_splat.clone()),
+            BurstEmitter,
+            Transform::from_translation(position.extend(transform.translation.z + 1.0)),
+            LevelComponents,
+        ));
+
+        let decal = decal_pool.acquire(&mut commands, (
+            Sprite::from_image(assets.slime_decal.clone()),
+            Transform::from_translation(position.extend(DECAL_Z))
+                .with_rotation(Quat::from_rotation_z(rng.effects.gen_range(0.0 .. std::f32::consts::TAU)))
+                .with_scale(Vec3::splat(rng.effects.gen_range(0.8 ..= 1.3))),
+            Decal { timer: Timer::new(DECAL_LIFETIME, TimerMode::Once) },
+            LevelComponents,
+        ));
+        if let Some(oldest) = decals.push(decal) {
+            decal_pool.release(&mut commands, oldest);
+        }
+    }
+}
+
+fn release_burst_emitters(
+    mut commands: Commands,
+    mut pool: ResMut<BurstEmitterPool>,
+    emitter_query: Query<(Entity, &ParticleEmitter), With<BurstEmitter>>,
+) {
+    for (entity, emitter) in &emitter_query {
+        if emitter.burst_emitted() {
+            pool.release(&mut commands, entity);
+        }
+    }
+}
+
+fn fade_decals(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pool: ResMut<DecalPool>,
+    mut decals: ResMut<Decals>,
+    mut decal_query: Query<(Entity, &mut Decal, &mut Sprite)>,
+) {
+    for (entity, mut decal, mut sprite) in &mut decal_query {
+        decal.timer.tick(time.delta());
+        if decal.timer.finished() {
+            decals.remove(entity);
+            pool.release(&mut commands, entity);
+            continue;
+        }
+        let alpha = (decal.timer.remaining_secs() / DECAL_FADE_SECS).min(1.0);
+        sprite.color.set_alpha(alpha);
+    }
+}
@@ -1,32 +1,48 @@
-use std::{ops::Range, time::Duration};
+use std::time::Duration;
 
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
-use crate::animation::{AnimationByDirection, AnimationConfig};
+use rand::Rng;
 
-use super::{player::PlayerInfo, GRP_ENEMY, GRP_ENVIRONMENT, GRP_PLAYER, GRP_PLAYER_BULLET, CollidingObj, LevelComponents};
+use crate::{animation::{AnimationFinished, AnimationLibrary, AnimationPlayer}, direction::{DirectionSet, Facing}, rng::GameRng};
 
-const TEXTURE_SLIME: &str = "sprites/slime.png";
+use super::{damage::{ConsumedOnHit, DeathEvent, Faction, Health, Hitbox, Hurtbox}, player::PlayerInfo, pool::EnemyPool, scoring::ScoreValue, GameplayAnimations, GRP_ENEMY, GRP_ENVIRONMENT, GRP_PLAYER, GRP_PLAYER_BULLET, LevelComponents};
 
-const SPEED_SLIME: f32 = 50.0;
+pub const SPEED_SLIME: f32 = 50.0;
 
-const DAMAGE_SLIME: u32 = 10;
+pub const DAMAGE_SLIME: u32 = 10;
 
-const ENEMY_SPAWN_INTERVAL: Duration = Duration::from_millis(1000);
+pub const HEALTH_SLIME: u32 = 1;
 
-#[derive(Component, PartialEq, Eq)]
+pub const POINTS_SLIME: u32 = 10;
+
+pub const ENEMY_SPAWN_INTERVAL: Duration = Duration::from_millis(1000);
+
+pub const CLIP_WALK: &str = "walk";
+
+pub const CLIP_DYING: &str = "dying";
+
+/// Frame event of the dying animation when the slime bursts.
+pub const FRAME_EVENT_SPLAT: &str = "splat";
+
+/// Type of an enemy, for everything that shows enemies differently (minimap, indicators).
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnemyKind {
+    Slime,
+}
+
+#[derive(Component, Debug, PartialEq, Eq)]
 pub enum EnemyState {
-    Alive, Dying(Timer)
+    Alive,
+    /// Playing the dying animation, returned to the [[EnemyPool]] when it is finished.
+    Dying,
 }
 
-#[derive(Default, Clone, Resource)]
-pub struct EnemySpriteSpawnConfig {
-    pub texture: Handle<Image>,
-    pub atlas: Handle<TextureAtlasLayout>,
-    pub walk_frames: AnimationByDirection,
-    pub idle_frames: AnimationByDirection,
-    pub dying_frames: Range<usize>,
+#[derive(Clone, Resource)]
+pub struct EnemySpawnConfig {
+    /// Animation state of a newly spawned slime, cloned to every slime.
+    pub animation: AnimationPlayer,
 }
 
 #[derive(Default, Clone, Resource)]
@@ -35,19 +51,16 @@ pub struct EnemySpawner {
     pub timer: Timer,
 }
 
-#[derive(Resource)]
-pub struct SlimeDeathSound(pub Vec<Handle<AudioSource>>);
-
-#[derive(Component)]
-pub struct ActiveSlimeDeathSound;
+/// Attaches gameplay events to the clips imported from the slime sprite sheet.
+pub fn add_slime_frame_events(library: &mut AnimationLibrary) {
+    library.add_event(CLIP_DYING, 0, FRAME_EVENT_SPLAT);
+}
 
 pub fn setup_enemies(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    animations: Res<GameplayAnimations>,
+    libraries: Res<Assets<AnimationLibrary>>,
 ) {
-    let slime_texture: Handle<Image> = asset_server.load(TEXTURE_SLIME);
-
     // List of locations where enemies can be spawned and spawning timer
     commands.insert_resource(EnemySpawner {
         locations: vec![
@@ -57,150 +70,116 @@ pub fn setup_enemies(
             Vec3::new(-345.0, -360.0, 0.0),
             Vec3::new(345.0, -360.0, 0.0),
         ],
-        timer: Timer::new(ENEMY_SPAWN_INTERVAL, TimerMode::Repeating)
+        timer: Timer::new(ENEMY_SPAWN_INTERVAL, TimerMode::Repeating),
     });
 
-    let slime_sprite_spawn_config = {
-        let slime_texture: Handle<Image> = slime_texture;
-        let slime_layout = TextureAtlasLayout::from_grid(UVec2::splat(64), 8, 5, None, None);
-        let slime_atlas_layout = texture_atlas_layouts.add(slime_layout);
-        let walk_frames = AnimationByDirection {
-            bottom: 0 .. 7,
-            bottm_left: 0 .. 7,
-            left: 16 .. 23,
-            top_left: 8 .. 15,
-            top: 8 .. 15,
-            top_right: 8 .. 15,
-            right: 24 .. 31,
-            bottom_right: 0 .. 7,
-        };
-        let idle_frames = walk_frames.clone();
-        EnemySpriteSpawnConfig {
-            texture: slime_texture,
-            atlas: slime_atlas_layout,
-            walk_frames,
-            idle_frames,
-            dying_frames: 35 .. 39,
-        }
-    };
-
-    commands.insert_resource(slime_sprite_spawn_config);
-
-    commands.insert_resource(SlimeDeathSound(vec![
-        asset_server.load("sounds/slime_1.ogg"),
-        asset_server.load("sounds/slime_2.ogg"),
-        asset_server.load("sounds/slime_3.ogg"),
-        asset_server.load("sounds/slime_4.ogg"),
-        asset_server.load("sounds/slime_5.ogg"),
-    ]));
+    let library = libraries.get(&animations.slime).expect("slime animations are added on startup");
+    let animation = AnimationPlayer::facing(animations.slime.clone(), library, CLIP_WALK, &Facing::new(DirectionSet::Four));
+    commands.insert_resource(EnemySpawnConfig { animation });
 }
 
 pub fn spawn_enemies(
     mut commands: Commands,
     time: Res<Time>,
-    spawn_configs: Res<EnemySpriteSpawnConfig>,
+    spawn_configs: Res<EnemySpawnConfig>,
     mut spawner: ResMut<EnemySpawner>,
+    mut enemy_pool: ResMut<EnemyPool>,
+    mut rng: ResMut<GameRng>,
 ) {
     spawner.timer.tick(time.delta());
 
     if spawner.timer.just_finished() {
-        let location_index: usize = (rand::random::<usize>()) % spawner.locations.len();
-
-        let start_animation = spawn_configs.walk_frames.bottom.clone();
-        let start_frame = start_animation.start;
-        let slime_animation_config = AnimationConfig {
-            idle: spawn_configs.idle_frames.clone(),
-            walk: spawn_configs.walk_frames.clone(),
-            dying: spawn_configs.dying_frames.clone(),
-            current_frame_range: start_animation,
-            fps: 10,
-            elapsed_frame_timer: AnimationConfig::timer_from_fps(10),
-        };
-
-        commands.spawn((
-            RigidBody::Dynamic,
-            GravityScale(0.0),
-            Collider::ball(10.0),
-            CollisionGroups::new(
-                GRP_ENEMY,
-                GRP_ENVIRONMENT | GRP_PLAYER | GRP_ENEMY | GRP_PLAYER_BULLET,
-            ),
-            Velocity::zero(),
-            LockedAxes::ROTATION_LOCKED,
-            Sprite {
-                image: spawn_configs.texture.clone(),
-                texture_atlas: Some(TextureAtlas {
-                    layout: spawn_configs.atlas.clone(),
-                    index: start_frame,
-                }),
-                ..default()
-            },
-            Transform::from_translation(spawner.locations[location_index]),
-            EnemyState::Alive,
-            slime_animation_config,
-            LevelComponents,
-            CollidingObj::Enemy { dmg: DAMAGE_SLIME },
-        ));
+        let location_index = rng.spawning.gen_range(0..spawner.locations.len());
+        enemy_pool.acquire(&mut commands, enemy_bundle(&spawn_configs, spawner.locations[location_index]));
     }
 }
 
+/// All components of a newly spawned slime.
+pub fn enemy_bundle(spawn_config: &EnemySpawnConfig, location: Vec3) -> impl Bundle {
+    let facing = Facing::new(DirectionSet::Four);
+    let slime_animator = spawn_config.animation.clone();
+
+    (
+        RigidBody::Dynamic,
+        GravityScale(0.0),
+        Collider::ball(10.0),
+        CollisionGroups::new(
+            GRP_ENEMY,
+            GRP_ENVIRONMENT | GRP_PLAYER | GRP_ENEMY | GRP_PLAYER_BULLET,
+        ),
+        Velocity::zero(),
+        LockedAxes::ROTATION_LOCKED,
+        Transform::from_translation(location),
+        EnemyState::Alive,
+        EnemyKind::Slime,
+        slime_animator,
+        facing,
+        LevelComponents,
+        (
+            Health::new(HEALTH_SLIME),
+            Hitbox { damage: DAMAGE_SLIME, faction: Faction::Enemy, crit_chance: 0.0 },
+            Hurtbox { faction: Faction::Enemy },
+            ConsumedOnHit,
+            ScoreValue(POINTS_SLIME),
+        ),
+    )
+}
+
 pub fn execute_enemy_behavior(
-    mut commands: Commands,
-    time: Res<Time>,
     player_transform: Single<&Transform, With<PlayerInfo>>,
-    mut enemy_query: Query<(Entity, &mut Transform, &mut Velocity, &mut EnemyState), Without<PlayerInfo>>,
+    libraries: Res<Assets<AnimationLibrary>>,
+    mut enemy_query: Query<(&mut Transform, &mut Velocity, &mut Facing, &mut AnimationPlayer, &EnemyState), Without<PlayerInfo>>,
 ) {
     // Iterate through enemies
-    for (enemy_entity, mut enemy_transfrom, mut enemy_velocity, mut enemy_state) in &mut enemy_query {
-        match enemy_state.as_mut() {
+    for (mut enemy_transfrom, mut enemy_velocity, mut facing, mut animator, enemy_state) in &mut enemy_query {
+        match enemy_state {
             EnemyState::Alive => {
                 let move_vector = (player_transform.translation - enemy_transfrom.translation).normalize();
 
                 enemy_velocity.linvel = move_vector.xy() * SPEED_SLIME;
+                // Clip names are only looked up when the direction changes, there can be lots of slimes
+                if facing.turn_towards(move_vector.xy()) {
+                    if let Some(library) = libraries.get(&animator.library) {
+                        animator.play_facing(library, CLIP_WALK, &facing);
+                    }
+                }
 
                 // We update Z axis to implement correct sparites overlapping order
                 enemy_transfrom.translation.z = -(enemy_transfrom.translation.y * 0.01);
             },
-            EnemyState::Dying(timer) => {
-                timer.tick(time.delta());
-                if timer.just_finished() {
-                    commands.entity(enemy_entity).despawn();
-                }
-            },
+            EnemyState::Dying => {},
         };
     }
 }
 
-#[derive(Event)]
-pub struct EnemyReceiveHitEvent(pub Entity);
-
 pub fn start_enemy_dying(
     mut commands: Commands,
-    mut events: EventReader<EnemyReceiveHitEvent>,
-    time: Res<Time>,
-    sounds: Res<SlimeDeathSound>,
-    mut enemy_query: Query<(Entity, &mut Velocity, &mut AnimationConfig, &mut EnemyState), Without<PlayerInfo>>,
-    mut music_controller: Query<&mut AudioSink, With<ActiveSlimeDeathSound>>,
+    mut events: EventReader<DeathEvent>,
+    libraries: Res<Assets<AnimationLibrary>>,
+    mut enemy_query: Query<(Entity, &mut Velocity, &mut AnimationPlayer, &mut EnemyState), Without<PlayerInfo>>,
 ) {
-    if events.is_empty() {
-        return;
-    }
-
     for event in events.read() {
-        if let Ok((enemy_entity, mut enemy_velocity, mut enemy_anim_config, mut enemy_state)) = enemy_query.get_mut(event.0) {
-            let dying_duration = enemy_anim_config.frame_show_time() * enemy_anim_config.dying.len() as u32;
-            *enemy_state = EnemyState::Dying(Timer::new(dying_duration, TimerMode::Once));
-            enemy_anim_config.current_frame_range = enemy_anim_config.dying.clone();
-        
-            commands.entity(enemy_entity).remove::<Collider>();
+        if let Ok((enemy_entity, mut enemy_velocity, mut enemy_animator, mut enemy_state)) = enemy_query.get_mut(event.entity) {
+            *enemy_state = EnemyState::Dying;
+            if let Some(library) = libraries.get(&enemy_animator.library) {
+                enemy_animator.play(library, CLIP_DYING);
+            }
+
+            commands.entity(enemy_entity).insert(ColliderDisabled);
             enemy_velocity.linvel = Vec2::ZERO;
         }
     }
+}
 
-    for active_sound in &mut music_controller {
-        active_sound.stop();
+pub fn release_dead_enemies(
+    mut commands: Commands,
+    mut events: EventReader<AnimationFinished>,
+    mut enemy_pool: ResMut<EnemyPool>,
+    enemy_query: Query<&EnemyState>,
+) {
+    for event in events.read() {
+        if event.clip == CLIP_DYING && enemy_query.get(event.entity) == Ok(&EnemyState::Dying) {
+            enemy_pool.release(&mut commands, event.entity);
+        }
     }
-
-    let sound_to_play = sounds.0[time.elapsed_secs() as usize % sounds.0.len()].clone();
-    commands.spawn((AudioPlayer::new(sound_to_play), PlaybackSettings::DESPAWN));
 }
@@ -0,0 +1,91 @@
+use bevy::prelude::*;
+
+use super::{damage::{DamageEvent, Health}, enemy::EnemyState, player::PlayerInfo, pool::Pooled};
+
+/// Enemies alive for the full intensity from their count.
+pub const INTENSITY_FULL_ENEMIES: usize = 20;
+
+/// Enemies closer to the player than this give the full intensity from proximity, in world units.
+pub const INTENSITY_NEAR_DISTANCE: f32 = 60.0;
+/// Enemies further than this don't add any intensity from proximity.
+pub const INTENSITY_FAR_DISTANCE: f32 = 400.0;
+
+/// Part of player's max health taken recently for the full intensity from damage.
+pub const INTENSITY_FULL_DAMAGE: f32 = 0.3;
+/// Seconds, in which the remembered damage decays to about a third.
+const DAMAGE_MEMORY_SECS: f32 = 4.0;
+
+const ENEMY_COUNT_WEIGHT: f32 = 0.4;
+const DAMAGE_WEIGHT: f32 = 0.3;
+const PROXIMITY_WEIGHT: f32 = 0.3;
+
+/// Intensity change per second, it rises quickly and calms down slowly.
+const INTENSITY_RISE_SPEED: f32 = 0.5;
+const INTENSITY_FALL_SPEED: f32 = 0.1;
+
+/// How intense the fight is, from 0 (calm) to 1, e.g. for the music (see [[crate::music]]).
+#[derive(Resource, Default, Debug, Clone, PartialEq)]
+pub struct CombatIntensity {
+    pub value: f32,
+    /// Recently taken damage as a part of player's max health, decaying over time.
+    pub recent_damage: f32,
+}
+
+impl CombatIntensity {
+    /// Intensity the value moves to, from the enemies alive, the recent damage
+    /// and the distance of the nearest enemy.
+    pub fn target(enemies: usize, recent_damage: f32, nearest_enemy: Option<f32>) -> f32 {
+        let count = (enemies as f32 / INTENSITY_FULL_ENEMIES as f32).min(1.0);
+        let damage = (recent_damage / INTENSITY_FULL_DAMAGE).min(1.0);
+        let proximity = nearest_enemy.map_or(0.0, |distance| {
+            1.0 - ((distance - INTENSITY_NEAR_DISTANCE) / (INTENSITY_FAR_DISTANCE - INTENSITY_NEAR_DISTANCE)).clamp(0.0, 1.0)
+        });
+        (ENEMY_COUNT_WEIGHT * count + DAMAGE_WEIGHT * damage + PROXIMITY_WEIGHT * proximity).clamp(0.0, 1.0)
+    }
+
+    /// Moves the value towards the target.
+    pub fn approach(&mut self, target: f32, delta_secs: f32) {
+        self.value = if target > self.value {
+            (self.value + INTENSITY_RISE_SPEED * delta_secs).min(target)
+        } else {
+            (self.value - INTENSITY_FALL_SPEED * delta_secs).max(target)
+        };
+    }
+}
+
+pub fn reset_combat_intensity(
+    mut intensity: ResMut<CombatIntensity>,
+) {
+    *intensity = CombatIntensity::default();
+}
+
+pub fn update_combat_intensity(
+    time: Res<Time>,
+    mut intensity: ResMut<CombatIntensity>,
+    mut damage_events: EventReader<DamageEvent>,
+    player_query: Query<(&Transform, &Health), With<PlayerInfo>>,
+    enemy_query: Query<(&Transform, &EnemyState), Without<Pooled>>,
+) {
+    let delta_secs = time.delta_secs();
+    let Ok((player_transform, health)) = player_query.get_single() else {
+        return;
+    };
+
+    intensity.recent_damage *= (-delta_secs / DAMAGE_MEMORY_SECS).exp();
+    for event in damage_events.read() {
+        if player_query.contains(event.target) {
+            intensity.recent_damage += event.amount as f32 / health.max.max(1) as f32;
+        }
+    }
+
+    let player_position = player_transform.translation.xy();
+    let distances = enemy_query.iter()
+        .filter(|(_, state)| **state == EnemyState::Alive)
+        .map(|(transform, _)| transform.translation.xy().distance(player_position));
+    let (enemies, nearest) = distances.fold((0, None::<f32>), |(count, nearest), distance| {
+        (count + 1, Some(nearest.map_or(distance, |nearest| nearest.min(distance))))
+    });
+
+    let target = CombatIntensity::target(enemies, intensity.recent_damage, nearest);
+    intensity.approach(target, delta_secs);
+}
@@ -1,113 +1,182 @@
 pub mod arena;
 pub mod player;
 pub mod enemy;
+pub mod damage;
+pub mod effects;
+pub mod intensity;
+pub mod pool;
+pub mod scoring;
+pub mod stats;
+pub mod visuals;
 
 use arena::setup_arena;
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
-use enemy::{execute_enemy_behavior, setup_enemies, spawn_enemies, start_enemy_dying, EnemyReceiveHitEvent};
-use player::{execute_bullets_lifetime, execute_player_behavior, on_bullet_collided, on_player_damaged, setup_player, BulletCollided, PlayerDamage, PlayerInfo};
+use damage::{apply_damage, consume_hitboxes, enable_friendly_fire_events, handle_collision, DamageEvent, DamageRules, DeathEvent, Health};
+use enemy::{add_slime_frame_events, release_dead_enemies, execute_enemy_behavior, setup_enemies, spawn_enemies, start_enemy_dying};
+use intensity::{reset_combat_intensity, update_combat_intensity, CombatIntensity};
+use player::{add_player_frame_events, execute_bullets_lifetime, execute_player_behavior, on_bullet_collided, report_footsteps, report_player_hurt, setup_player, BulletCollided, BulletHitWall, Footstep, PlayerHurt, PlayerInfo, WeaponFired};
+use pool::{setup_pools, PoolConfig};
+use scoring::{award_score, break_kill_streak, decay_combo, reset_score, Combo, GameScore, KillStreak, ScoreBreakdown, ScoreEvent};
+use stats::{reset_run_stats, track_run_stats, RunStats};
 
-use crate::{animation::play_animations, control::handle_camera_zoom, menu::in_game_menu::{setup_game_ui, update_game_ui}, GameState};
+use crate::{
+    animation::{advance_animations, AnimationFinished, AnimationFrameEvent, AnimationLibrary},
+    aseprite::AsepriteJson,
+    control::{take_live_input, InputSource, LiveInput, PlayerInput},
+    replay::{record_player_input, save_replay_on_exit, save_replay_recording, start_replay_recording, take_replay_input, ReplayRecorder},
+    rng::{reseed_game_rng, SeedOverride},
+    GameState,
+};
 
 const GRP_PLAYER: Group = Group::GROUP_1;
 const GRP_ENEMY: Group = Group::GROUP_2;
 const GRP_ENVIRONMENT: Group = Group::GROUP_3;
 const GRP_PLAYER_BULLET: Group = Group::GROUP_4;
 
+/// Frequency of fixed ticks, on which all gameplay logic is executed.
+pub const FIXED_TICKS_PER_SECOND: f64 = 64.0;
+
+/// Sprite sheets compiled into the game, so the gameplay can run without an asset server.
+const PLAYER_SHEET: &[u8] = include_bytes!("../../assets/sprites/player.aseprite.json");
+const SLIME_SHEET: &[u8] = include_bytes!("../../assets/sprites/slime.aseprite.json");
+
 #[derive(Component)]
 pub struct LevelComponents;
 
-#[derive(Resource)]
-pub struct GameScore(pub u32);
+/// Animation libraries of gameplay objects, with gameplay frame events attached.
+/// Initially they are taken from the compiled in sprite sheets,
+/// [[visuals::GameplayVisualsPlugin]] updates them when the sheets are (re)loaded from assets.
+#[derive(Resource, Clone)]
+pub struct GameplayAnimations {
+    pub player: Handle<AnimationLibrary>,
+    pub slime: Handle<AnimationLibrary>,
+}
+
+impl GameplayAnimations {
+    pub fn player_library(mut library: AnimationLibrary) -> AnimationLibrary {
+        add_player_frame_events(&mut library);
+        library
+    }
 
-#[derive(Component,Debug)]
-pub enum CollidingObj {
-    Player,
-    Bullet,
-    Enemy { dmg: u32 },
-    Environment,
+    pub fn slime_library(mut library: AnimationLibrary) -> AnimationLibrary {
+        add_slime_frame_events(&mut library);
+        library
+    }
 }
 
+impl FromWorld for GameplayAnimations {
+    fn from_world(world: &mut World) -> Self {
+        let sheet_library = |bytes| AsepriteJson::parse(bytes).expect("compiled in sprite sheet is valid").library();
+        let mut libraries = world.resource_mut::<Assets<AnimationLibrary>>();
+        GameplayAnimations {
+            player: libraries.add(GameplayAnimations::player_library(sheet_library(PLAYER_SHEET))),
+            slime: libraries.add(GameplayAnimations::slime_library(sheet_library(SLIME_SHEET))),
+        }
+    }
+}
+
+/// Physics used by the gameplay.
+pub fn physics_plugin() -> RapierPhysicsPlugin<NoUserData> {
+    RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0).in_fixed_schedule()
+}
+
+/// Gameplay logic only.
+/// It doesn't need window, renderer or audio, so it can be run headless (see [[crate::headless]]).
+/// Sprites and sounds are attached by [[visuals::GameplayVisualsPlugin]].
+/// Physics should be added separately with [[physics_plugin]].
 pub struct MyGameplayPlugin;
 
 impl Plugin for MyGameplayPlugin {
     fn build(&self, app: &mut App) {
+        // https://bevy-cheatbook.github.io/fundamentals/fixed-timestep.html
+        app.insert_resource(Time::<Fixed>::from_hz(FIXED_TICKS_PER_SECOND));
+
+        app
+        .init_state::<GameState>()
+        .init_resource::<SeedOverride>()
+        .init_resource::<InputSource>()
+        .init_resource::<LiveInput>()
+        .init_resource::<PlayerInput>()
+        .init_resource::<ReplayRecorder>()
+        .init_resource::<RunStats>()
+        .init_resource::<GameScore>()
+        .init_resource::<Combo>()
+        .init_resource::<KillStreak>()
+        .init_resource::<ScoreBreakdown>()
+        .init_resource::<CombatIntensity>()
+        .init_resource::<DamageRules>()
+        .init_resource::<PoolConfig>()
+        .init_asset::<AnimationLibrary>()
+        .init_resource::<GameplayAnimations>();
+
         app.add_systems(OnEnter(GameState::InGame), 
             (
+                reseed_game_rng,
                 setup_arena,
                 setup_player,
                 setup_enemies,
-                setup_game_ui,
+                setup_pools,
+                reset_score,
+                reset_run_stats,
+                reset_combat_intensity,
+                start_replay_recording,
             ).chain()
         );
+        app.add_systems(OnEnter(GameState::End), save_replay_recording);
+        app.add_systems(Last, save_replay_on_exit);
 
         app
-        .add_event::<PlayerDamage>()
-        .add_event::<EnemyReceiveHitEvent>()
-        .add_event::<BulletCollided>();
-        app.add_systems(Update, 
+        .add_event::<DamageEvent>()
+        .add_event::<DeathEvent>()
+        .add_event::<BulletCollided>()
+        .add_event::<BulletHitWall>()
+        .add_event::<WeaponFired>()
+        .add_event::<PlayerHurt>()
+        .add_event::<Footstep>()
+        .add_event::<ScoreEvent>()
+        .add_event::<AnimationFinished>()
+        .add_event::<AnimationFrameEvent>();
+
+        // Everything that affects the outcome of a run is executed on fixed ticks in a fixed order,
+        // so the same seed and the same inputs always produce the same run.
+        app.add_systems(FixedUpdate, 
             (
+                take_live_input.run_if(resource_equals(InputSource::Live)),
+                take_replay_input.run_if(resource_equals(InputSource::Replay)),
+                record_player_input,
                 execute_player_behavior,
-                execute_enemy_behavior,
-                execute_bullets_lifetime,
-                spawn_enemies,
-                play_animations,
-                handle_camera_zoom,
-                update_game_ui,
-            ).run_if(in_state(GameState::InGame))
-        )
-        .add_systems(FixedUpdate, 
-            (
+                handle_collision,
+                apply_damage,
+                consume_hitboxes,
                 on_bullet_collided,
                 start_enemy_dying,
-                on_player_damaged,
+                decay_combo,
+                break_kill_streak,
+                award_score,
                 handle_game_over,
-            ).run_if(in_state(GameState::InGame))
-        )
-        .add_systems(FixedUpdate, handle_collision.run_if(in_state(GameState::InGame)));
-    }
-}
-
-pub fn handle_collision(
-    mut collision_events: EventReader<CollisionEvent>,
-    mut player_damage_writer: EventWriter<PlayerDamage>,
-    mut enemy_gamage_writer: EventWriter<EnemyReceiveHitEvent>,
-    mut bullet_collided: EventWriter<BulletCollided>,
-    query: Query<&CollidingObj>,
-    mut game_score: ResMut<GameScore>,
-) {
-    for collision_event in collision_events.read() {
-        if let &CollisionEvent::Started(c1, c2 , _) = collision_event {
-            let obj1 = query.get(c1).ok();
-            let obj2 = query.get(c2).ok();
-            use CollidingObj::*;
-            match ((c1,obj1), (c2,obj2)) {
-                ((c2,Some(Enemy {dmg})), (_,Some(Player))) | ((_,Some(Player)), (c2,Some(Enemy {dmg}))) => {
-                    player_damage_writer.send(PlayerDamage(*dmg));
-                    enemy_gamage_writer.send(EnemyReceiveHitEvent(c2));
-                },
-                ((c2,Some(Enemy {dmg:_})), (c1,Some(Bullet))) | ((c1,Some(Bullet)), (c2,Some(Enemy {dmg:_}))) => {
-                    bullet_collided.send(BulletCollided(c1));
-                    enemy_gamage_writer.send(EnemyReceiveHitEvent(c2));
-
-                    // TODO: move from here
-                    game_score.0 += 1;
-                },
-                ((_,Some(Environment)), (c1,Some(Bullet))) | ((c1,Some(Bullet)), (_,Some(Environment))) => {
-                    bullet_collided.send(BulletCollided(c1));
-                },
-                _ => (),
-            }
-        }
+                spawn_enemies,
+                execute_enemy_behavior,
+                execute_bullets_lifetime,
+                advance_animations,
+                release_dead_enemies,
+                track_run_stats,
+            ).chain().run_if(in_state(GameState::InGame))
+        );
+        app.add_systems(FixedUpdate, (
+            update_combat_intensity.after(track_run_stats),
+            report_player_hurt.after(apply_damage),
+            enable_friendly_fire_events.after(spawn_enemies),
+            report_footsteps.after(advance_animations),
+        ).run_if(in_state(GameState::InGame)));
     }
 }
 
 pub fn handle_game_over(
     mut commands: Commands,
-    player_info: Single<&PlayerInfo>,
+    player_health: Single<&Health, With<PlayerInfo>>,
 ) {
-    if player_info.health <= 0 {
+    if player_health.current == 0 {
         commands.set_state(GameState::End);
     }
 }
\ No newline at end of file
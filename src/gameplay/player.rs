@@ -1,15 +1,24 @@
 use std::time::Duration;
 
-use bevy::{prelude::*, window::PrimaryWindow};
+use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
-use crate::{animation::{AnimationByDirection, AnimationConfig}, coords::calc_mouse_world_coord, direction::direction_of_vector};
+use crate::{animation::{AnimationFrameEvent, AnimationLibrary, AnimationPlayer}, control::PlayerInput, direction::{DirectionSet, Facing}};
 
-use super::{GRP_ENEMY, GRP_ENVIRONMENT, GRP_PLAYER, GRP_PLAYER_BULLET, CollidingObj, LevelComponents};
+use super::{damage::{DamageEvent, Faction, Health, Hitbox, Hurtbox}, pool::BulletPool, GameplayAnimations, GRP_ENEMY, GRP_ENVIRONMENT, GRP_PLAYER, GRP_PLAYER_BULLET, LevelComponents};
 
-const TEXTURE_PLAYER: &str = "sprites/player.png";
+pub const PLAYER_HEALTH: u32 = 100;
 
-const TEXTURE_BULLET: &str = "sprites/ball.png";
+pub const BULLET_DAMAGE: u32 = 1;
+
+pub const BULLET_CRIT_CHANCE: f32 = 0.1;
+
+pub const CLIP_IDLE: &str = "idle";
+
+pub const CLIP_WALK: &str = "walk";
+
+/// Frame event of the walk animation when a foot touches the ground.
+pub const FRAME_EVENT_FOOTSTEP: &str = "footstep";
 
 const SPEED_PLAYER: f32 = 100.0;
 
@@ -18,65 +27,60 @@ const SPEED_BULLET: f32 = 200.0;
 const BULLET_LIFE_TIME: Duration = Duration::from_secs(1);
 
 #[derive(Component)]
-pub struct PlayerInfo {
-    pub health: u32,
-}
+pub struct PlayerInfo;
 
 #[derive(Component)]
 pub struct Bullet {
     elapsed: Timer,
 }
 
-#[derive(Resource)]
-pub struct BulletSprite(pub Handle<Image>);
+#[derive(Event, Clone, Debug)]
+pub struct BulletCollided(pub Entity);
 
-#[derive(Event)]
-pub struct PlayerDamage(pub u32);
+/// Bullet hit something that can't be damaged (e.g. a wall).
+#[derive(Event, Clone, Debug)]
+pub struct BulletHitWall {
+    pub position: Vec2,
+    /// Direction the bullet was flying in.
+    pub direction: Vec2,
+}
 
-#[derive(Event)]
-pub struct BulletCollided(pub Entity);
+/// Player shot a bullet from the position in the direction.
+#[derive(Event, Clone, Debug)]
+pub struct WeaponFired {
+    pub position: Vec2,
+    pub direction: Vec2,
+}
+
+/// Player took damage.
+#[derive(Event, Clone, Debug)]
+pub struct PlayerHurt {
+    pub position: Vec2,
+    pub amount: u32,
+}
+
+/// Player's foot touched the ground, in sync with the walk animation.
+#[derive(Event, Clone, Debug)]
+pub struct Footstep {
+    pub position: Vec2,
+}
+
+/// Attaches gameplay events to the clips imported from the player sprite sheet.
+pub fn add_player_frame_events(library: &mut AnimationLibrary) {
+    library.add_directional_event(CLIP_WALK, 1, FRAME_EVENT_FOOTSTEP);
+    library.add_directional_event(CLIP_WALK, 5, FRAME_EVENT_FOOTSTEP);
+}
 
 pub fn setup_player(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    animations: Res<GameplayAnimations>,
+    libraries: Res<Assets<AnimationLibrary>>,
 ) {
-    let player_texture: Handle<Image> = asset_server.load(TEXTURE_PLAYER);
-
     // Create player
     {
-        let player_texture: Handle<Image> = player_texture;
-        let player_layout = TextureAtlasLayout::from_grid(UVec2::splat(48), 8, 7, None, None);
-        let player_atlas_layout = texture_atlas_layouts.add(player_layout);
-        let idle_frames = AnimationByDirection {
-            bottom: 0 .. 0,
-            bottm_left: 1 .. 1,
-            left: 1 .. 1,
-            top_left: 2 .. 2,
-            top: 5 .. 5,
-            top_right: 4 .. 4,
-            right: 3 .. 3,
-            bottom_right: 3 .. 3,
-        };
-        let walk_frames = AnimationByDirection {
-            bottom: 8 .. 15,
-            bottm_left: 16 .. 23,
-            left: 16 .. 23,
-            top_left: 24 .. 31,
-            top: 48 .. 55,
-            top_right: 40 .. 47,
-            right: 32 .. 39,
-            bottom_right: 8 .. 15,
-        };
-        let start_animation = idle_frames.bottom.clone();
-        let player_animation_config = AnimationConfig {
-            idle: idle_frames,
-            walk: walk_frames,
-            dying: 0..0,
-            current_frame_range: start_animation,
-            fps: 10,
-            elapsed_frame_timer: AnimationConfig::timer_from_fps(10),
-        };
+        let facing = Facing::new(DirectionSet::Eight);
+        let library = libraries.get(&animations.player).expect("player animations are added on startup");
+        let player_animator = AnimationPlayer::facing(animations.player.clone(), library, CLIP_IDLE, &facing);
 
         commands.spawn((
             RigidBody::Dynamic,
@@ -90,140 +94,117 @@ pub fn setup_player(
             ),
             GravityScale(0.0),
             Dominance::group(100),
-            Sprite {
-                image: player_texture.clone(),
-                texture_atlas: Some(TextureAtlas {
-                    layout: player_atlas_layout.clone(),
-                    index: 0,
-                }),
-                ..default()
-            },
             Transform::from_translation(Vec3::new(-100.0, 0.0, 0.0)),
-            PlayerInfo { health: 100 },
-            player_animation_config,
+            PlayerInfo,
+            (Health::new(PLAYER_HEALTH), Hurtbox { faction: Faction::Player }),
+            player_animator,
+            facing,
             Velocity::zero(),
             LevelComponents,
-            CollidingObj::Player,
         ));
     }
-
-    let bullet_texture: Handle<Image> = asset_server.load(TEXTURE_BULLET);
-    commands.insert_resource(BulletSprite(bullet_texture));
 }
 
 pub fn execute_player_behavior(
     mut commands: Commands,
-    mouse: Res<ButtonInput<MouseButton>>,
-    keys: Res<ButtonInput<KeyCode>>,
-    textures: Res<BulletSprite>,
-    window_query: Query<&Window, With<PrimaryWindow>>,
-    mut camera_query: Query<(&Camera, &mut Transform, &GlobalTransform), Without<AnimationConfig>>,
-    mut player_query: Query<(&mut AnimationConfig, &mut Velocity, &mut Transform), With<PlayerInfo>>,
+    input: Res<PlayerInput>,
+    libraries: Res<Assets<AnimationLibrary>>,
+    mut bullet_pool: ResMut<BulletPool>,
+    mut fired_writer: EventWriter<WeaponFired>,
+    mut player_query: Query<(&mut AnimationPlayer, &mut Facing, &mut Velocity, &mut Transform), With<PlayerInfo>>,
 ) {
-    let (mut animation, mut velocity, mut transform) = player_query.single_mut();
-
-    let move_direction =
-        if keys.pressed(KeyCode::KeyA) && keys.pressed(KeyCode::KeyS) {
-            Vec3::new(-1.0, -1.0, 0.0).normalize()
-        } else if keys.pressed(KeyCode::KeyA) && keys.pressed(KeyCode::KeyW) {
-            Vec3::new(-1.0, 1.0, 0.0).normalize()
-        } else if keys.pressed(KeyCode::KeyD) && keys.pressed(KeyCode::KeyS) {
-            Vec3::new(1.0, -1.0, 0.0).normalize()
-        } else if keys.pressed(KeyCode::KeyD) && keys.pressed(KeyCode::KeyW) {
-            Vec3::new(1.0, 1.0, 0.0).normalize()
-        } else if keys.pressed(KeyCode::KeyA) {
-            Vec3::new(-1.0, 0.0, 0.0)
-        } else if keys.pressed(KeyCode::KeyW) {
-            Vec3::new(0.0, 1.0, 0.0)
-        } else if keys.pressed(KeyCode::KeyD) {
-            Vec3::new(1.0, 0.0, 0.0)
-        } else if keys.pressed(KeyCode::KeyS) {
-            Vec3::new(0.0, -1.0, 0.0)
-        } else {
-            Vec3::ZERO
-        };
-
-    let (camera, mut camera_transform, camera_global_transform) = camera_query.single_mut();
-    if let Some(cursor_position) = window_query.single().cursor_position() {
-        if let Some(coord) = calc_mouse_world_coord(cursor_position, &camera, &camera_global_transform) {
-            let player_orientation = (coord - transform.translation).normalize();
-            let look_direction = direction_of_vector(player_orientation);
-            let frames_range = if move_direction != Vec3::ZERO {
-                animation.walk.for_direction(look_direction)
-            } else {
-                animation.idle.for_direction(look_direction)
-            };
-
-            if animation.current_frame_range != frames_range {
-                animation.current_frame_range = frames_range;
-            }
-
-            if mouse.just_pressed(MouseButton::Middle) {
-                println!("Clicked coordinates: {coord:?}");
-            }
-
-            if mouse.just_pressed(MouseButton::Left) {
-                commands.spawn((
-                    Sprite {
-                        image: textures.0.clone(),
-                        ..default()
-                    },
-                    transform.clone(),
-                    Bullet {
-                        elapsed: Timer::new(BULLET_LIFE_TIME, TimerMode::Once),
-                    },
-                    RigidBody::Dynamic,
-                    GravityScale(0.0),
-                    LockedAxes::ROTATION_LOCKED,
-                    Collider::ball(2.0),
-                    CollisionGroups::new(
-                        GRP_PLAYER_BULLET,
-                        GRP_ENVIRONMENT | GRP_ENEMY,
-                    ),
-                    ActiveEvents::COLLISION_EVENTS,
-                    Velocity::linear(player_orientation.xy() * SPEED_BULLET),
-                    LevelComponents,
-                    CollidingObj::Bullet,
-                ));
-            }
-        };
+    let (mut animation, mut facing, mut velocity, mut transform) = player_query.single_mut();
+
+    let move_direction = input.movement.extend(0.0);
+
+    if let Some(aim) = input.aim {
+        let player_orientation = (aim.extend(0.0) - transform.translation).normalize();
+        facing.turn_towards(player_orientation.xy());
+        let base_clip = if move_direction != Vec3::ZERO { CLIP_WALK } else { CLIP_IDLE };
+        if let Some(library) = libraries.get(&animation.library) {
+            animation.play_facing(library, base_clip, &facing);
+        }
+
+        if input.fire {
+            bullet_pool.acquire(&mut commands, bullet_bundle(transform.translation, player_orientation.xy()));
+            fired_writer.send(WeaponFired { position: transform.translation.xy(), direction: player_orientation.xy() });
+        }
     }
 
     velocity.linvel = (move_direction * SPEED_PLAYER).xy();
 
     transform.translation.z = -(transform.translation.y * 0.01);
+}
 
-    camera_transform.translation.x = transform.translation.x;
-    camera_transform.translation.y = transform.translation.y;
+/// All components of a bullet shot from given position in given direction.
+pub fn bullet_bundle(position: Vec3, direction: Vec2) -> impl Bundle {
+    (
+        Transform::from_translation(position),
+        Bullet {
+            elapsed: Timer::new(BULLET_LIFE_TIME, TimerMode::Once),
+        },
+        RigidBody::Dynamic,
+        GravityScale(0.0),
+        LockedAxes::ROTATION_LOCKED,
+        Collider::ball(2.0),
+        CollisionGroups::new(
+            GRP_PLAYER_BULLET,
+            GRP_ENVIRONMENT | GRP_ENEMY,
+        ),
+        ActiveEvents::COLLISION_EVENTS,
+        Velocity::linear(direction * SPEED_BULLET),
+        LevelComponents,
+        Hitbox { damage: BULLET_DAMAGE, faction: Faction::Player, crit_chance: BULLET_CRIT_CHANCE },
+    )
 }
 
 pub fn execute_bullets_lifetime(
     mut commands: Commands,
     time: Res<Time>,
+    mut bullet_pool: ResMut<BulletPool>,
     mut bullet_query: Query<(Entity, &mut Bullet)>,
 ) {
     for (bullet_entity, mut bullet_info) in &mut bullet_query {
         bullet_info.elapsed.tick(time.delta());
         if bullet_info.elapsed.just_finished() {
-            commands.entity(bullet_entity).despawn();
+            bullet_pool.release(&mut commands, bullet_entity);
         }
     }
 }
 
-pub fn on_player_damaged(
-    mut events: EventReader<PlayerDamage>,
-    mut player_query: Single<&mut PlayerInfo>,
-) {
-    for PlayerDamage(dmg) in events.read() {
-        player_query.health = player_query.health.saturating_sub(*dmg);
-    }
-}
-
 pub fn on_bullet_collided(
     mut commands: Commands,
     mut events: EventReader<BulletCollided>,
+    mut bullet_pool: ResMut<BulletPool>,
 ) {
     for BulletCollided(bullet_entity) in events.read() {
-        commands.entity(*bullet_entity).despawn();
+        bullet_pool.release(&mut commands, *bullet_entity);
     }
-}
\ No newline at end of file
+}
+
+pub fn report_player_hurt(
+    mut damage_events: EventReader<DamageEvent>,
+    mut hurt_writer: EventWriter<PlayerHurt>,
+    player_query: Query<&Transform, With<PlayerInfo>>,
+) {
+    for event in damage_events.read() {
+        if let Ok(transform) = player_query.get(event.target) {
+            hurt_writer.send(PlayerHurt { position: transform.translation.xy(), amount: event.amount });
+        }
+    }
+}
+
+pub fn report_footsteps(
+    mut frame_events: EventReader<AnimationFrameEvent>,
+    mut footstep_writer: EventWriter<Footstep>,
+    player_query: Query<&Transform, With<PlayerInfo>>,
+) {
+    for event in frame_events.read() {
+        if event.name != FRAME_EVENT_FOOTSTEP {
+            continue;
+        }
+        if let Ok(transform) = player_query.get(event.entity) {
+            footstep_writer.send(Footstep { position: transform.translation.xy() });
+        }
+    }
+}
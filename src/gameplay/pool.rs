@@ -0,0 +1,174 @@
+//! Reuse of short living objects (bullets, enemies, visual effects).
+//!
+//! Spawning and despawning a physics body for every shot is expensive,
+//! so released objects are deactivated instead: hidden, with disabled body and collider (if they have them).
+//! Gameplay components are removed from them, so gameplay systems don't see inactive objects.
+//! The next acquired object of the same kind reuses such entity.
+
+use std::marker::PhantomData;
+
+use bevy::{ecs::entity::EntityHashSet, prelude::*};
+use bevy_rapier2d::prelude::*;
+
+use super::{
+    damage::{ConsumedOnHit, Health, Hitbox, Hurtbox},
+    enemy::{enemy_bundle, EnemySpawnConfig, EnemyState},
+    player::{bullet_bundle, Bullet},
+    scoring::ScoreValue,
+};
+use crate::{animation::AnimationPlayer, direction::Facing};
+
+/// Marks an inactive entity kept in an [[EntityPool]].
+#[derive(Component, Debug)]
+pub struct Pooled;
+
+/// Numbers of objects of a pool, shown in the debug overlay.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PoolStats {
+    /// Objects currently in the game.
+    pub active: usize,
+    pub peak_active: usize,
+    /// Inactive objects waiting to be reused.
+    pub free: usize,
+    /// Entities created by the pool.
+    pub spawned: u32,
+    /// Objects that reused an inactive entity instead of spawning a new one.
+    pub reused: u32,
+}
+
+/// Inactive entities of one kind of objects.
+/// `B` is the bundle of gameplay components removed from released entities.
+#[derive(Resource)]
+pub struct EntityPool<B: Bundle> {
+    free: Vec<Entity>,
+    /// Entities of `free`, to tell quickly whether an entity is pooled.
+    pooled: EntityHashSet,
+    /// Entities released over the capacity, until their despawn is applied.
+    despawning: EntityHashSet,
+    /// How many inactive entities are kept, the rest is despawned.
+    pub capacity: usize,
+    pub stats: PoolStats,
+    components: PhantomData<fn() -> B>,
+}
+
+/// Gameplay components of a bullet.
+pub type BulletPool = EntityPool<(Bullet, Hitbox)>;
+
+/// Gameplay components of an enemy.
+pub type EnemyPool = EntityPool<(EnemyState, AnimationPlayer, Facing, (Health, Hitbox, Hurtbox, ConsumedOnHit, ScoreValue))>;
+
+impl<B: Bundle> EntityPool<B> {
+    pub fn new(capacity: usize) -> Self {
+        EntityPool {
+            free: Vec::new(),
+            pooled: EntityHashSet::default(),
+            despawning: EntityHashSet::default(),
+            capacity,
+            stats: PoolStats::default(),
+            components: PhantomData,
+        }
+    }
+
+    /// Activates an inactive entity with the bundle, or spawns a new entity when there are none.
+    pub fn acquire(&mut self, commands: &mut Commands, bundle: impl Bundle) -> Entity {
+        let entity = match self.free.pop() {
+            Some(entity) => {
+                self.pooled.remove(&entity);
+                self.stats.reused += 1;
+                commands.entity(entity)
+                    .remove::<(Pooled, RigidBodyDisabled, ColliderDisabled)>()
+                    .insert((bundle, Visibility::Inherited))
+                    .queue(move_body_to_transform);
+                entity
+            },
+            None => {
+                self.stats.spawned += 1;
+                commands.spawn(bundle).id()
+            },
+        };
+        self.stats.active += 1;
+        self.stats.peak_active = self.stats.peak_active.max(self.stats.active);
+        self.stats.free = self.free.len();
+        entity
+    }
+
+    /// Deactivates the entity, so it can be reused.
+    /// Releasing it twice, or releasing a despawned entity, does nothing.
+    pub fn release(&mut self, commands: &mut Commands, entity: Entity) {
+        // Despawned entities stay in the world until the commands are applied
+        self.despawning.retain(|despawning| commands.get_entity(*despawning).is_some());
+        if self.pooled.contains(&entity) || self.despawning.contains(&entity) || commands.get_entity(entity).is_none() {
+            return;
+        }
+        // Objects spawned outside of the pool (e.g. in tests) are released too
+        self.stats.active = self.stats.active.saturating_sub(1);
+        if self.free.len() < self.capacity {
+            self.deactivate(commands, entity);
+        } else {
+            commands.entity(entity).despawn_recursive();
+            self.despawning.insert(entity);
+        }
+    }
+
+    /// Spawns inactive entities up to the capacity, so the first objects don't have to.
+    pub fn prewarm<T: Bundle>(&mut self, commands: &mut Commands, bundle: impl Fn() -> T) {
+        while self.free.len() < self.capacity {
+            self.stats.spawned += 1;
+            let entity = commands.spawn(bundle()).id();
+            self.deactivate(commands, entity);
+        }
+    }
+
+    fn deactivate(&mut self, commands: &mut Commands, entity: Entity) {
+        commands.entity(entity)
+            .remove::<B>()
+            .insert((Pooled, Visibility::Hidden))
+            .queue(disable_body);
+        self.free.push(entity);
+        self.pooled.insert(entity);
+        self.stats.free = self.free.len();
+    }
+}
+
+/// Stops physics of a deactivated entity, objects without a body (e.g. effects) are left as they are.
+fn disable_body(mut entity: EntityWorldMut) {
+    if entity.contains::<RigidBody>() {
+        entity.insert((RigidBodyDisabled, ColliderDisabled, Velocity::zero()));
+    }
+}
+
+/// Physics moves bodies according to their global transforms, which are only propagated at the end of a frame,
+/// so a reused body would stay where it was released.
+/// Pooled objects have no parents, so their global transforms are the same as their transforms.
+fn move_body_to_transform(mut entity: EntityWorldMut) {
+    if let Some(transform) = entity.get::<Transform>().copied() {
+        entity.insert(GlobalTransform::from(transform));
+    }
+}
+
+/// Sizes of the pools, set before entering the game to change them.
+#[derive(Resource, Debug, Clone)]
+pub struct PoolConfig {
+    pub bullets: usize,
+    pub enemies: usize,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        PoolConfig { bullets: 128, enemies: 64 }
+    }
+}
+
+pub fn setup_pools(
+    mut commands: Commands,
+    config: Res<PoolConfig>,
+    spawn_config: Res<EnemySpawnConfig>,
+) {
+    let mut bullets = BulletPool::new(config.bullets);
+    bullets.prewarm(&mut commands, || bullet_bundle(Vec3::ZERO, Vec2::ZERO));
+    commands.insert_resource(bullets);
+
+    let mut enemies = EnemyPool::new(config.enemies);
+    enemies.prewarm(&mut commands, || enemy_bundle(&spawn_config, Vec3::ZERO));
+    commands.insert_resource(enemies);
+}
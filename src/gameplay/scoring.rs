@@ -0,0 +1,159 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use super::{damage::{DamageEvent, DeathEvent, Faction}, player::PlayerInfo};
+
+/// Time after a kill within which the next kill keeps the combo going.
+pub const COMBO_WINDOW: Duration = Duration::from_millis(2000);
+
+pub const MAX_MULTIPLIER: u32 = 5;
+
+/// Number of combo kills needed to raise the multiplier by one.
+pub const KILLS_PER_MULTIPLIER: u32 = 3;
+
+/// Every this many kills without taking damage give a bonus.
+pub const STREAK_BONUS_EVERY: u32 = 10;
+
+/// Streak bonus is the streak length multiplied by this.
+pub const STREAK_BONUS_PER_KILL: u32 = 5;
+
+#[derive(Resource, Default)]
+pub struct GameScore(pub u32);
+
+/// Points given for killing an object.
+#[derive(Component, Debug)]
+pub struct ScoreValue(pub u32);
+
+/// Multiplier that grows with quick successive kills.
+/// When no kills happen within [[COMBO_WINDOW]], the multiplier drops by one step at a time.
+#[derive(Resource, Debug)]
+pub struct Combo {
+    pub kills: u32,
+    pub multiplier: u32,
+    pub timer: Timer,
+}
+
+impl Default for Combo {
+    fn default() -> Self {
+        Combo {
+            kills: 0,
+            multiplier: 1,
+            timer: Timer::new(COMBO_WINDOW, TimerMode::Once),
+        }
+    }
+}
+
+impl Combo {
+    /// Returns the multiplier to apply to the kill.
+    pub fn register_kill(&mut self) -> u32 {
+        self.kills += 1;
+        self.multiplier = (1 + self.kills / KILLS_PER_MULTIPLIER).min(MAX_MULTIPLIER);
+        self.timer.reset();
+        self.multiplier
+    }
+
+    pub fn decay(&mut self, delta: Duration) {
+        if self.kills == 0 || !self.timer.tick(delta).just_finished() {
+            return;
+        }
+        self.multiplier = self.multiplier.saturating_sub(1).max(1);
+        if self.multiplier > 1 {
+            self.kills = (self.multiplier - 1) * KILLS_PER_MULTIPLIER;
+            self.timer.reset();
+        } else {
+            self.kills = 0;
+        }
+    }
+}
+
+/// Kills in a row without the player taking damage.
+#[derive(Resource, Default, Debug)]
+pub struct KillStreak(pub u32);
+
+/// How the score of the current run was earned.
+#[derive(Resource, Default, Debug, Clone)]
+pub struct ScoreBreakdown {
+    pub kills: u32,
+    pub kill_points: u32,
+    pub combo_bonus: u32,
+    pub streak_bonus: u32,
+    pub best_multiplier: u32,
+    pub best_streak: u32,
+}
+
+/// Points were earned at the given location.
+#[derive(Event, Clone, Debug)]
+pub struct ScoreEvent {
+    pub position: Vec2,
+    pub points: u32,
+    pub multiplier: u32,
+}
+
+pub fn reset_score(
+    mut commands: Commands,
+) {
+    commands.insert_resource(GameScore(0));
+    commands.insert_resource(Combo::default());
+    commands.insert_resource(KillStreak::default());
+    commands.insert_resource(ScoreBreakdown { best_multiplier: 1, ..default() });
+}
+
+pub fn decay_combo(
+    time: Res<Time>,
+    mut combo: ResMut<Combo>,
+) {
+    combo.decay(time.delta());
+}
+
+pub fn break_kill_streak(
+    mut damage_events: EventReader<DamageEvent>,
+    player_query: Query<(), With<PlayerInfo>>,
+    mut streak: ResMut<KillStreak>,
+) {
+    if damage_events.read().any(|e| player_query.contains(e.target)) {
+        streak.0 = 0;
+    }
+}
+
+/// Scores objects with [[ScoreValue]] killed by the player.
+pub fn award_score(
+    mut death_events: EventReader<DeathEvent>,
+    mut score_writer: EventWriter<ScoreEvent>,
+    scored_query: Query<(&ScoreValue, &Transform)>,
+    mut game_score: ResMut<GameScore>,
+    mut combo: ResMut<Combo>,
+    mut streak: ResMut<KillStreak>,
+    mut breakdown: ResMut<ScoreBreakdown>,
+) {
+    for event in death_events.read() {
+        if event.killer_faction != Some(Faction::Player) {
+            continue;
+        }
+        let Ok((value, transform)) = scored_query.get(event.entity) else {
+            continue;
+        };
+
+        let multiplier = combo.register_kill();
+        let mut points = value.0 * multiplier;
+        breakdown.kills += 1;
+        breakdown.kill_points += value.0;
+        breakdown.combo_bonus += points - value.0;
+        breakdown.best_multiplier = breakdown.best_multiplier.max(multiplier);
+
+        streak.0 += 1;
+        breakdown.best_streak = breakdown.best_streak.max(streak.0);
+        if streak.0.is_multiple_of(STREAK_BONUS_EVERY) {
+            let bonus = streak.0 * STREAK_BONUS_PER_KILL;
+            breakdown.streak_bonus += bonus;
+            points += bonus;
+        }
+
+        game_score.0 += points;
+        score_writer.send(ScoreEvent {
+            position: transform.translation.xy(),
+            points,
+            multiplier,
+        });
+    }
+}
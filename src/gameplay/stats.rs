@@ -0,0 +1,39 @@
+use bevy::prelude::*;
+
+use super::{damage::{DamageEvent, DeathEvent}, enemy::EnemyState, player::{Bullet, PlayerInfo}};
+
+/// Statistics of the current run.
+#[derive(Resource, Default, Debug, Clone)]
+pub struct RunStats {
+    pub ticks: u32,
+    pub shots_fired: u32,
+    pub enemies_spawned: u32,
+    pub enemies_killed: u32,
+    pub damage_taken: u32,
+}
+
+pub fn reset_run_stats(
+    mut stats: ResMut<RunStats>,
+) {
+    *stats = RunStats::default();
+}
+
+pub fn track_run_stats(
+    mut stats: ResMut<RunStats>,
+    mut damage_events: EventReader<DamageEvent>,
+    mut death_events: EventReader<DeathEvent>,
+    player_query: Query<(), With<PlayerInfo>>,
+    enemy_query: Query<(), With<EnemyState>>,
+    new_bullets: Query<(), Added<Bullet>>,
+    new_enemies: Query<(), Added<EnemyState>>,
+) {
+    stats.ticks += 1;
+    stats.shots_fired += new_bullets.iter().count() as u32;
+    stats.enemies_spawned += new_enemies.iter().count() as u32;
+    stats.enemies_killed += death_events.read().filter(|e| enemy_query.contains(e.entity)).count() as u32;
+    for event in damage_events.read() {
+        if player_query.contains(event.target) {
+            stats.damage_taken += event.amount;
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use bevy::prelude::*;
+
+use crate::{animation::{advance_animations, AnimationFrameEvent, AnimationLibrary, AnimationPlayer}, aseprite::{AsepritePlugin, SpriteSheet, LAYOUT_LABEL}, audio::{PlaySound, SoundKind}, GameState};
+
+use super::{enemy::{EnemyState, FRAME_EVENT_SPLAT}, player::{Bullet, PlayerInfo}, GameplayAnimations, scoring::{award_score, ScoreEvent}, LevelComponents};
+
+const TEXTURE_ARENA: &str = "sprites/arena.png";
+
+const SHEET_PLAYER: &str = "sprites/player.aseprite.json";
+
+const TEXTURE_BULLET: &str = "sprites/ball.png";
+
+const SHEET_SLIME: &str = "sprites/slime.aseprite.json";
+
+const FONT_POPUP: &str = "fonts/DejaVuSans-Bold.ttf";
+
+const SCORE_POPUP_LIFETIME_SECS: f32 = 0.8;
+
+const SCORE_POPUP_RISE_SPEED: f32 = 30.0;
+
+/// Textures and fonts of all gameplay objects.
+/// Images of animated objects come with their [[SpriteSheet]].
+#[derive(Resource)]
+pub struct GameplaySprites {
+    pub arena: Handle<Image>,
+    pub player_sheet: Handle<SpriteSheet>,
+    pub player_atlas: Handle<TextureAtlasLayout>,
+    pub bullet: Handle<Image>,
+    pub slime_sheet: Handle<SpriteSheet>,
+    pub slime_atlas: Handle<TextureAtlasLayout>,
+    pub popup_font: Handle<Font>,
+}
+
+/// Floating text with points earned for a kill.
+#[derive(Component)]
+pub struct ScorePopup {
+    pub timer: Timer,
+}
+
+/// Attaches sprites to gameplay objects spawned by [[super::MyGameplayPlugin]] and plays their sounds.
+pub struct GameplayVisualsPlugin;
+
+impl Plugin for GameplayVisualsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_plugins(AsepritePlugin)
+            .add_systems(Startup, load_gameplay_assets)
+            .add_systems(PreUpdate, reload_gameplay_clips)
+            .add_systems(OnEnter(GameState::InGame), setup_arena_background)
+            .add_systems(FixedUpdate, (
+                play_enemy_death_sound.after(advance_animations),
+                spawn_score_popups.after(award_score),
+            ).run_if(in_state(GameState::InGame)))
+            .add_systems(Update, animate_score_popups.run_if(in_state(GameState::InGame)))
+            .add_observer(add_player_sprite)
+            .add_observer(add_enemy_sprite)
+            .add_observer(add_bullet_sprite);
+    }
+}
+
+fn load_gameplay_assets(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    commands.insert_resource(GameplaySprites {
+        arena: asset_server.load(TEXTURE_ARENA),
+        player_sheet: asset_server.load(SHEET_PLAYER),
+        player_atlas: asset_server.load(format!("{SHEET_PLAYER}#{LAYOUT_LABEL}")),
+        bullet: asset_server.load(TEXTURE_BULLET),
+        slime_sheet: asset_server.load(SHEET_SLIME),
+        slime_atlas: asset_server.load(format!("{SHEET_SLIME}#{LAYOUT_LABEL}")),
+        popup_font: asset_server.load(FONT_POPUP),
+    });
+}
+
+/// Applies clips and images of sprite sheets loaded from assets, including re-exports while the game is running.
+/// Libraries are updated in place, so all objects pick up the new clips.
+fn reload_gameplay_clips(
+    mut events: EventReader<AssetEvent<SpriteSheet>>,
+    sprites: Res<GameplaySprites>,
+    sheets: Res<Assets<SpriteSheet>>,
+    animations: Res<GameplayAnimations>,
+    mut libraries: ResMut<Assets<AnimationLibrary>>,
+    mut player_sprites: Query<&mut Sprite, With<PlayerInfo>>,
+    mut enemy_sprites: Query<&mut Sprite, (With<EnemyState>, Without<PlayerInfo>)>,
+) {
+    for event in events.read() {
+        let (AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id }) = event else {
+            continue;
+        };
+        let Some(sheet) = sheets.get(*id) else {
+            continue;
+        };
+        let is_player = *id == sprites.player_sheet.id();
+        let (handle, library) = if is_player {
+            (&animations.player, GameplayAnimations::player_library(sheet.library.clone()))
+        } else if *id == sprites.slime_sheet.id() {
+            (&animations.slime, GameplayAnimations::slime_library(sheet.library.clone()))
+        } else {
+            continue;
+        };
+        if let Some(current) = libraries.get_mut(handle) {
+            current.update_from(library);
+        }
+        // Objects spawned before the sheet was loaded have no image yet
+        if is_player {
+            player_sprites.iter_mut().for_each(|mut sprite| sprite.image = sheet.image.clone());
+        } else {
+            enemy_sprites.iter_mut().for_each(|mut sprite| sprite.image = sheet.image.clone());
+        }
+    }
+}
+
+fn setup_arena_background(
+    mut commands: Commands,
+    sprites: Res<GameplaySprites>,
+) {
+    commands.spawn((
+        Sprite {
+            image: sprites.arena.clone(),
+            .. default()
+        },
+        Transform::from_translation(Vec3::new(0.0, 0.0, -100.0)),
+        LevelComponents,
+    ));
+}
+
+fn add_player_sprite(
+    trigger: Trigger<OnAdd, PlayerInfo>,
+    mut commands: Commands,
+    sprites: Res<GameplaySprites>,
+    sheets: Res<Assets<SpriteSheet>>,
+    animators: Query<&AnimationPlayer>,
+) {
+    let start_frame = animators.get(trigger.entity()).map(|a| a.atlas_index()).unwrap_or(0);
+    commands.entity(trigger.entity()).insert(Sprite {
+        image: sheet_image(&sheets, &sprites.player_sheet),
+        texture_atlas: Some(TextureAtlas {
+            layout: sprites.player_atlas.clone(),
+            index: start_frame,
+        }),
+        ..default()
+    });
+}
+
+fn add_enemy_sprite(
+    trigger: Trigger<OnAdd, EnemyState>,
+    mut commands: Commands,
+    sprites: Res<GameplaySprites>,
+    sheets: Res<Assets<SpriteSheet>>,
+    animators: Query<&AnimationPlayer>,
+) {
+    let start_frame = animators.get(trigger.entity()).map(|a| a.atlas_index()).unwrap_or(0);
+    commands.entity(trigger.entity()).insert(Sprite {
+        image: sheet_image(&sheets, &sprites.slime_sheet),
+        texture_atlas: Some(TextureAtlas {
+            layout: sprites.slime_atlas.clone(),
+            index: start_frame,
+        }),
+        ..default()
+    });
+}
+
+fn add_bullet_sprite(
+    trigger: Trigger<OnAdd, Bullet>,
+    mut commands: Commands,
+    sprites: Res<GameplaySprites>,
+) {
+    commands.entity(trigger.entity()).insert(Sprite {
+        image: sprites.bullet.clone(),
+        ..default()
+    });
+}
+
+fn play_enemy_death_sound(
+    mut events: EventReader<AnimationFrameEvent>,
+    mut sound_writer: EventWriter<PlaySound>,
+    enemy_query: Query<&Transform, With<EnemyState>>,
+) {
+    for event in events.read() {
+        if event.name != FRAME_EVENT_SPLAT {
+            continue;
+        }
+        sound_writer.send(PlaySound {
+            kind: SoundKind::SlimeDeath,
+            position: enemy_query.get(event.entity).ok().map(|t| t.translation.xy()),
+        });
+    }
+}
+
+fn spawn_score_popups(
+    mut commands: Commands,
+    mut events: EventReader<ScoreEvent>,
+    sprites: Res<GameplaySprites>,
+) {
+    for event in events.read() {
+        let text = if event.multiplier > 1 {
+            format!("+{} x{}", event.points, event.multiplier)
+        } else {
+            format!("+{}", event.points)
+        };
+        commands.spawn((
+            Text2d::new(text),
+            TextFont {
+                font: sprites.popup_font.clone(),
+                font_size: 8.0,
+                ..default()
+            },
+            TextColor(Color::srgb(1.0, 0.9, 0.2)),
+            Transform::from_translation(event.position.extend(10.0)),
+            ScorePopup { timer: Timer::from_seconds(SCORE_POPUP_LIFETIME_SECS, TimerMode::Once) },
+            LevelComponents,
+        ));
+    }
+}
+
+/// Popups float up and fade out.
+fn animate_score_popups(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut popups: Query<(Entity, &mut ScorePopup, &mut Transform, &mut TextColor)>,
+) {
+    for (entity, mut popup, mut transform, mut color) in &mut popups {
+        popup.timer.tick(time.delta());
+        if popup.timer.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        transform.translation.y += SCORE_POPUP_RISE_SPEED * time.delta_secs();
+        color.0.set_alpha(1.0 - popup.timer.fraction());
+    }
+}
+
+/// Image of the sprite sheet, none until it's loaded (see [[reload_gameplay_clips]]).
+fn sheet_image(sheets: &Assets<SpriteSheet>, sheet: &Handle<SpriteSheet>) -> Handle<Image> {
+    sheets.get(sheet).map(|sheet| sheet.image.clone()).unwrap_or_default()
+}
@@ -0,0 +1,218 @@
+use std::{path::PathBuf, time::Duration};
+
+use bevy::{
+    app::PluginsState,
+    ecs::system::RunSystemOnce,
+    prelude::*,
+    state::app::StatesPlugin,
+    time::TimeUpdateStrategy,
+};
+use rand::Rng;
+use serde::Serialize;
+
+use crate::{
+    cli::CliArgs,
+    control::{movement_from_axes, InputSource, PlayerInput},
+    gameplay::{
+        damage::Health,
+        enemy::{EnemyState, DAMAGE_SLIME, ENEMY_SPAWN_INTERVAL, SPEED_SLIME},
+        physics_plugin,
+        player::{PlayerInfo, PLAYER_HEALTH},
+        scoring::GameScore,
+        stats::RunStats,
+        MyGameplayPlugin, FIXED_TICKS_PER_SECOND,
+    },
+    replay::{record_player_input, save_replay_recording, ReplayRecorder},
+    rng::{GameRng, SeedOverride},
+    GameState,
+};
+
+const DEFAULT_SIMULATED_MINUTES: f64 = 10.0;
+
+// Bot shoots 4 times per second
+const BOT_FIRE_INTERVAL_TICKS: u32 = 16;
+// Enemies closer than this are pushing the bot away
+const BOT_DANGER_RADIUS: f32 = 120.0;
+// Beyond this distance from the arena center the bot heads back to the center
+const BOT_SAFE_AREA_RADIUS: f32 = 250.0;
+const BOT_AIM_JITTER: f32 = 4.0;
+
+/// Statistics of a simulated run, written as JSON.
+#[derive(Serialize, Debug)]
+pub struct SimulationReport {
+    pub seed: u64,
+    pub died: bool,
+    pub survived_secs: f64,
+    pub score: u32,
+    pub health_left: u32,
+    pub shots_fired: u32,
+    pub enemies_spawned: u32,
+    pub enemies_killed: u32,
+    pub enemies_alive: u32,
+    pub damage_taken: u32,
+    pub tuning: Tuning,
+}
+
+/// Gameplay constants the run was simulated with.
+#[derive(Serialize, Debug)]
+pub struct Tuning {
+    pub speed_slime: f32,
+    pub damage_slime: u32,
+    pub enemy_spawn_interval_secs: f32,
+    pub player_health: u32,
+}
+
+/// Runs gameplay without window, renderer and audio, with a bot instead of the player.
+/// The simulation isn't bound to real time, it runs fixed ticks as fast as possible
+/// until the player dies or the given number of minutes is simulated.
+///
+/// ```sh
+/// bevy-2d-test --headless --minutes 5 --seed 42 --out stats.json
+/// ```
+pub fn run(cli_args: CliArgs) {
+    let minutes = cli_args.minutes.unwrap_or(DEFAULT_SIMULATED_MINUTES);
+    let max_ticks = (minutes * 60.0 * FIXED_TICKS_PER_SECOND) as u32;
+
+    let mut app = simulation_app(cli_args.seed);
+    app
+        .insert_resource(InputSource::Bot)
+        .insert_resource(ReplayRecorder {
+            path: cli_args.record,
+            ..default()
+        })
+        .add_systems(FixedUpdate,
+            bot_player_input
+                .before(record_player_input)
+                .run_if(resource_equals(InputSource::Bot))
+                .run_if(in_state(GameState::InGame))
+        );
+
+    loop {
+        app.update();
+        if *app.world().resource::<State<GameState>>() == GameState::End {
+            break;
+        }
+        if app.world().resource::<RunStats>().ticks >= max_ticks {
+            // Run is over, but player is still alive, so replay hasn't been saved on game over
+            if let Err(e) = app.world_mut().run_system_once(save_replay_recording) {
+                error!("Failed to save replay: {e}");
+            }
+            break;
+        }
+    }
+
+    let report = make_report(app.world_mut());
+    let json = serde_json::to_string_pretty(&report).expect("Report is always serializable");
+    write_report(&json, cli_args.out);
+}
+
+/// Builds an app with gameplay logic and physics only, where each [[App::update]] advances the game
+/// by exactly one fixed tick. The run starts on the first update.
+pub fn simulation_app(seed: Option<u64>) -> App {
+    let mut app = App::new();
+    app
+        .add_plugins((MinimalPlugins, StatesPlugin, TransformPlugin, HierarchyPlugin))
+        // Animation libraries are assets, nothing is loaded from files though
+        .add_plugins(AssetPlugin { watch_for_changes_override: Some(false), ..default() })
+        .add_plugins(physics_plugin())
+        .add_plugins(MyGameplayPlugin)
+        .insert_resource(SeedOverride(seed))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(1.0 / FIXED_TICKS_PER_SECOND)));
+
+    app.world_mut().resource_mut::<NextState<GameState>>().set(GameState::InGame);
+
+    while app.plugins_state() == PluginsState::Adding {
+        bevy::tasks::tick_global_task_pools_on_main_thread();
+    }
+    app.finish();
+    app.cleanup();
+
+    app
+}
+
+fn make_report(world: &mut World) -> SimulationReport {
+    let died = *world.resource::<State<GameState>>() == GameState::End;
+    let enemies_alive = world.query::<&EnemyState>().iter(world).filter(|s| **s == EnemyState::Alive).count() as u32;
+    let health_left = world.query_filtered::<&Health, With<PlayerInfo>>().iter(world).next().map(|h| h.current).unwrap_or(0);
+    let stats = world.resource::<RunStats>();
+    SimulationReport {
+        seed: world.resource::<GameRng>().seed,
+        died,
+        survived_secs: stats.ticks as f64 / FIXED_TICKS_PER_SECOND,
+        score: world.resource::<GameScore>().0,
+        health_left,
+        shots_fired: stats.shots_fired,
+        enemies_spawned: stats.enemies_spawned,
+        enemies_killed: stats.enemies_killed,
+        enemies_alive,
+        damage_taken: stats.damage_taken,
+        tuning: Tuning {
+            speed_slime: SPEED_SLIME,
+            damage_slime: DAMAGE_SLIME,
+            enemy_spawn_interval_secs: ENEMY_SPAWN_INTERVAL.as_secs_f32(),
+            player_health: PLAYER_HEALTH,
+        },
+    }
+}
+
+fn write_report(json: &str, out: Option<PathBuf>) {
+    match out {
+        Some(path) => {
+            if let Err(e) = std::fs::write(&path, json) {
+                eprintln!("Failed to write report to {}: {e}", path.display());
+            }
+        },
+        None => println!("{json}"),
+    }
+}
+
+/// Simple bot: keeps away from slimes and the arena walls, and shoots the nearest slime.
+pub fn bot_player_input(
+    mut input: ResMut<PlayerInput>,
+    mut rng: ResMut<GameRng>,
+    stats: Res<RunStats>,
+    player_transform: Single<&Transform, With<PlayerInfo>>,
+    enemy_query: Query<(&Transform, &EnemyState), Without<PlayerInfo>>,
+) {
+    let player_pos = player_transform.translation.xy();
+
+    let mut nearest_enemy: Option<(Vec2, f32)> = None;
+    let mut escape = Vec2::ZERO;
+    for (enemy_transform, enemy_state) in &enemy_query {
+        if *enemy_state != EnemyState::Alive {
+            continue;
+        }
+        let enemy_pos = enemy_transform.translation.xy();
+        let distance = player_pos.distance(enemy_pos);
+        if nearest_enemy.is_none_or(|(_, d)| distance < d) {
+            nearest_enemy = Some((enemy_pos, distance));
+        }
+        if distance < BOT_DANGER_RADIUS && distance > 0.0 {
+            escape += (player_pos - enemy_pos) / (distance * distance);
+        }
+    }
+    if player_pos.length() > BOT_SAFE_AREA_RADIUS {
+        escape -= player_pos.normalize() / BOT_SAFE_AREA_RADIUS;
+    }
+
+    let axis = |v: f32| if v.abs() < escape.length() * 0.4 { 0 } else { v.signum() as i8 };
+    input.movement = if escape == Vec2::ZERO {
+        Vec2::ZERO
+    } else {
+        movement_from_axes(axis(escape.x), axis(escape.y))
+    };
+
+    match nearest_enemy {
+        Some((enemy_pos, _)) => {
+            let jitter = Vec2::new(
+                rng.bot.gen_range(-BOT_AIM_JITTER ..= BOT_AIM_JITTER),
+                rng.bot.gen_range(-BOT_AIM_JITTER ..= BOT_AIM_JITTER),
+            );
+            input.aim = Some(enemy_pos + jitter);
+            input.fire = stats.ticks.is_multiple_of(BOT_FIRE_INTERVAL_TICKS);
+        },
+        None => {
+            input.fire = false;
+        },
+    }
+}
@@ -0,0 +1,115 @@
+//! Best runs, kept between runs in [[HIGH_SCORES_FILE]] (in the local storage on the web, like the settings).
+
+use std::{io, path::{Path, PathBuf}};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    gameplay::scoring::{GameScore, ScoreBreakdown},
+    rng::GameRng,
+    settings::{read_stored_file, write_stored_file},
+};
+
+/// High scores file, in the working directory (the local storage key on the web).
+pub const HIGH_SCORES_FILE: &str = "high_scores.json";
+
+/// Number of runs in the table.
+pub const MAX_HIGH_SCORES: usize = 10;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HighScore {
+    pub score: u32,
+    pub kills: u32,
+    /// Seed of the run, to replay it.
+    pub seed: u64,
+}
+
+/// Best runs, the best one first.
+#[derive(Resource, Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct HighScores(pub Vec<HighScore>);
+
+impl HighScores {
+    /// Adds the run if it's among the best ones, returns its place (0 for the best).
+    /// Earlier runs stay ahead of later ones with the same score.
+    pub fn record(&mut self, run: HighScore) -> Option<usize> {
+        let place = self.0.partition_point(|high_score| high_score.score >= run.score);
+        if place >= MAX_HIGH_SCORES {
+            return None;
+        }
+        self.0.insert(place, run);
+        self.0.truncate(MAX_HIGH_SCORES);
+        Some(place)
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let bytes = read_stored_file(path)?;
+        serde_json::from_slice(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self).expect("High scores are always serializable");
+        write_stored_file(path, &json)
+    }
+}
+
+/// Place of the last run in the [[HighScores]], none when it didn't get there.
+#[derive(Resource, Default, Debug)]
+pub struct LastRunPlace(pub Option<usize>);
+
+/// Where [[HighScores]] are saved to when changed. Not set (e.g. in tests) means they aren't saved.
+#[derive(Resource, Default)]
+pub struct HighScoresPath(pub Option<PathBuf>);
+
+/// Loads high scores from [[HIGH_SCORES_FILE]] and saves them back whenever a run gets there.
+pub struct HighScoresPlugin;
+
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut App) {
+        let path = PathBuf::from(HIGH_SCORES_FILE);
+        let high_scores = match HighScores::load(&path) {
+            Ok(high_scores) => high_scores,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => HighScores::default(),
+            Err(e) => {
+                warn!("Failed to load {}: {e}", path.display());
+                HighScores::default()
+            },
+        };
+        app
+            .insert_resource(high_scores)
+            .insert_resource(HighScoresPath(Some(path)))
+            .init_resource::<LastRunPlace>()
+            .add_systems(PostUpdate, save_high_scores.run_if(resource_changed::<HighScores>));
+    }
+}
+
+/// Adds the finished run to the [[HighScores]].
+pub fn record_high_score(
+    score: Res<GameScore>,
+    breakdown: Res<ScoreBreakdown>,
+    rng: Res<GameRng>,
+    mut high_scores: ResMut<HighScores>,
+    mut last_run_place: ResMut<LastRunPlace>,
+) {
+    // Changed only when the run gets there, so the file isn't rewritten after every run
+    last_run_place.0 = high_scores.bypass_change_detection()
+        .record(HighScore { score: score.0, kills: breakdown.kills, seed: rng.seed });
+    if last_run_place.0.is_some() {
+        high_scores.set_changed();
+    }
+}
+
+fn save_high_scores(
+    high_scores: Res<HighScores>,
+    path: Res<HighScoresPath>,
+) {
+    // Insertion of the loaded high scores is a change too
+    if high_scores.is_added() {
+        return;
+    }
+    if let Some(path) = &path.0 {
+        if let Err(e) = high_scores.save(path) {
+            warn!("Failed to save high scores to {}: {e}", path.display());
+        }
+    }
+}
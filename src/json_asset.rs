@@ -0,0 +1,50 @@
+//! Reading of JSON asset files, shared by their loaders.
+//!
+//! Loaders read the file with [[read_asset]] and parse it with [[parse_json]], which fail with a [[JsonAssetError]].
+//! Errors of a particular asset (e.g. a playlist with an unknown track) are variants of its loader's own error,
+//! next to a variant wrapping [[JsonAssetError]].
+
+use std::{error::Error, fmt, io};
+
+use bevy::asset::io::Reader;
+use serde::de::DeserializeOwned;
+
+#[derive(Debug)]
+pub enum JsonAssetError {
+    Io(io::Error),
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for JsonAssetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonAssetError::Io(e) => write!(f, "can't read file: {e}"),
+            JsonAssetError::Json(e) => write!(f, "invalid JSON: {e}"),
+        }
+    }
+}
+
+impl Error for JsonAssetError {}
+
+impl From<io::Error> for JsonAssetError {
+    fn from(e: io::Error) -> Self {
+        JsonAssetError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for JsonAssetError {
+    fn from(e: serde_json::Error) -> Self {
+        JsonAssetError::Json(e)
+    }
+}
+
+/// Whole content of the asset file.
+pub async fn read_asset(reader: &mut dyn Reader) -> Result<Vec<u8>, JsonAssetError> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes).await?;
+    Ok(bytes)
+}
+
+pub fn parse_json<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, JsonAssetError> {
+    Ok(serde_json::from_slice(bytes)?)
+}
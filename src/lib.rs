@@ -0,0 +1,24 @@
+pub mod game;
+pub mod audio;
+pub mod music;
+pub mod localization;
+pub mod camera;
+pub mod settings;
+pub mod accessibility;
+pub mod direction;
+pub mod coords;
+pub mod animation;
+pub mod json_asset;
+pub mod particles;
+pub mod aseprite;
+pub mod menu;
+pub mod control;
+pub mod gameplay;
+pub mod rng;
+pub mod cli;
+pub mod replay;
+pub mod headless;
+pub mod high_scores;
+pub mod testing;
+
+pub use game::GameState;
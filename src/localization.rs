@@ -0,0 +1,354 @@
+//! Translations of UI text with [Fluent](https://projectfluent.org).
+//!
+//! Languages are listed in [[LANGUAGES_FILE]], with their messages in `assets/locales/<id>/main.ftl`.
+//! The language is chosen in the settings, or taken from the system locale when not set there.
+//! Messages missing in a language are taken from the [[FALLBACK_LANGUAGE]], which is also compiled in,
+//! so text can be shown before the assets are loaded.
+//!
+//! The default UI font doesn't cover all scripts, so a language can name its own font.
+//! That font replaces the default one for all UI text, there is no fallback for single missing glyphs,
+//! so it has to cover the Latin digits and symbols used by the HUD too.
+//! UI text should use [[Localization::font]], and [[LocalizedText]] for text which doesn't change.
+
+use std::{error::Error, fmt, path::Path};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext, ReadAssetBytesError},
+    prelude::*,
+    utils::HashMap,
+};
+use fluent_bundle::{concurrent::FluentBundle, FluentResource};
+use fluent_syntax::ast::Entry;
+use serde::Deserialize;
+use unic_langid::LanguageIdentifier;
+
+use crate::{
+    json_asset::{parse_json, read_asset, JsonAssetError},
+    settings::Settings,
+};
+
+pub use fluent_bundle::FluentArgs;
+
+pub const LANGUAGES_FILE: &str = "locales/game.languages.json";
+
+/// Language used for messages missing in other languages.
+pub const FALLBACK_LANGUAGE: &str = "en-US";
+
+const FALLBACK_MESSAGES: &str = include_str!("../assets/locales/en-US/main.ftl");
+
+/// Font of UI text, unless the language has its own.
+pub const DEFAULT_UI_FONT: &str = "fonts/DejaVuSans-Bold.ttf";
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct LanguageInfo {
+    /// Language identifier, e.g. `de` or `pt-BR`, also the directory of its messages.
+    pub id: String,
+    /// Name of the language in that language, shown in the language selector.
+    pub name: String,
+    /// Font for scripts the default font doesn't cover, used instead of it (not as a fallback).
+    #[serde(default)]
+    pub font: Option<String>,
+}
+
+/// Messages file of the language.
+pub fn messages_path(language: &str) -> String {
+    format!("{language}/main.ftl")
+}
+
+/// Identifiers of the messages in Fluent source.
+pub fn message_ids(source: &str) -> Result<Vec<String>, LocalizationError> {
+    let resource = parse_resource(source)?;
+    Ok(resource.entries().filter_map(|entry| match entry {
+        Entry::Message(message) => Some(message.id.name.to_string()),
+        _ => None,
+    }).collect())
+}
+
+/// Messages of the reference source missing in the other source.
+pub fn missing_keys(reference: &str, other: &str) -> Result<Vec<String>, LocalizationError> {
+    let present = message_ids(other)?;
+    Ok(message_ids(reference)?.into_iter().filter(|id| !present.contains(id)).collect())
+}
+
+/// The first of the available languages matching one of the requested locales (e.g. `de-AT` matches `de`).
+pub fn negotiate_language<'a>(requested: impl IntoIterator<Item = &'a str>, available: &[String]) -> Option<String> {
+    let available: Vec<(LanguageIdentifier, &String)> = available.iter()
+        .filter_map(|id| Some((id.parse().ok()?, id)))
+        .collect();
+    for requested in requested {
+        // System locales can look like `de_AT.UTF-8`
+        let requested = requested.split('.').next().unwrap_or_default().replace('_', "-");
+        let Ok(requested) = requested.parse::<LanguageIdentifier>() else {
+            continue;
+        };
+        let exact = available.iter().find(|(id, _)| *id == requested);
+        let same_language = || available.iter().find(|(id, _)| id.language == requested.language);
+        if let Some((_, id)) = exact.or_else(same_language) {
+            return Some((*id).clone());
+        }
+    }
+    None
+}
+
+fn parse_resource(source: &str) -> Result<FluentResource, LocalizationError> {
+    FluentResource::try_new(source.to_string())
+        .map_err(|(_, errors)| LocalizationError::Syntax(errors.iter().map(|e| format!("{e:?}")).collect()))
+}
+
+#[derive(Debug)]
+pub enum LocalizationError {
+    Asset(JsonAssetError),
+    Messages(String, ReadAssetBytesError),
+    Syntax(Vec<String>),
+    InvalidLanguage(String),
+}
+
+impl fmt::Display for LocalizationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LocalizationError::Asset(e) => write!(f, "can't load languages: {e}"),
+            LocalizationError::Messages(language, e) => write!(f, "can't read messages of {language}: {e}"),
+            LocalizationError::Syntax(errors) => write!(f, "invalid Fluent messages: {}", errors.join(", ")),
+            LocalizationError::InvalidLanguage(language) => write!(f, "invalid language identifier {language}"),
+        }
+    }
+}
+
+impl Error for LocalizationError {}
+
+impl From<JsonAssetError> for LocalizationError {
+    fn from(e: JsonAssetError) -> Self {
+        LocalizationError::Asset(e)
+    }
+}
+
+/// Messages of a language.
+pub struct Messages {
+    bundle: FluentBundle<FluentResource>,
+}
+
+impl Messages {
+    pub fn new(language: &str, source: &str) -> Result<Self, LocalizationError> {
+        let id: LanguageIdentifier = language.parse()
+            .map_err(|_| LocalizationError::InvalidLanguage(language.to_string()))?;
+        let mut bundle = FluentBundle::new_concurrent(vec![id]);
+        // Unicode isolation marks around arguments aren't in the UI font
+        bundle.set_use_isolating(false);
+        bundle.add_resource_overriding(parse_resource(source)?);
+        Ok(Messages { bundle })
+    }
+
+    /// Formatted message, none when the language doesn't have it.
+    pub fn format(&self, key: &str, args: Option<&FluentArgs>) -> Option<String> {
+        let pattern = self.bundle.get_message(key)?.value()?;
+        let mut errors = Vec::new();
+        let text = self.bundle.format_pattern(pattern, args, &mut errors).into_owned();
+        if !errors.is_empty() {
+            warn!("Errors in message {key}: {errors:?}");
+        }
+        Some(text)
+    }
+}
+
+impl fmt::Debug for Messages {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Messages").field("locales", &self.bundle.locales).finish()
+    }
+}
+
+/// Languages with their messages, loaded from [[LANGUAGES_FILE]].
+#[derive(Asset, TypePath, Debug)]
+pub struct Languages {
+    pub languages: Vec<LanguageInfo>,
+    /// Fluent source of each language.
+    pub sources: HashMap<String, String>,
+}
+
+impl Languages {
+    pub fn ids(&self) -> Vec<String> {
+        self.languages.iter().map(|language| language.id.clone()).collect()
+    }
+
+    pub fn info(&self, id: &str) -> Option<&LanguageInfo> {
+        self.languages.iter().find(|language| language.id == id)
+    }
+}
+
+/// List of languages in a `*.languages.json` file:
+///
+/// ```json
+/// {
+///     "languages": [
+///         { "id": "en-US", "name": "English" },
+///         { "id": "ja", "name": "日本語", "font": "fonts/NotoSansJP-Bold.ttf" }
+///     ]
+/// }
+/// ```
+#[derive(Deserialize, Debug)]
+pub struct LanguagesJson {
+    pub languages: Vec<LanguageInfo>,
+}
+
+impl LanguagesJson {
+    pub fn parse(bytes: &[u8]) -> Result<Self, LocalizationError> {
+        Ok(parse_json(bytes)?)
+    }
+}
+
+/// Loads `*.languages.json` files as [[Languages]].
+#[derive(Default)]
+pub struct LanguagesLoader;
+
+impl AssetLoader for LanguagesLoader {
+    type Asset = Languages;
+    type Settings = ();
+    type Error = LocalizationError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Languages, LocalizationError> {
+        let bytes = read_asset(reader).await?;
+        let json = LanguagesJson::parse(&bytes)?;
+
+        // Messages are in directories next to the JSON file
+        let directory = load_context.path().parent().unwrap_or(Path::new("")).to_path_buf();
+        let mut sources = HashMap::new();
+        for language in &json.languages {
+            let source = load_context.read_asset_bytes(directory.join(messages_path(&language.id))).await
+                .map_err(|e| LocalizationError::Messages(language.id.clone(), e))?;
+            sources.insert(language.id.clone(), String::from_utf8_lossy(&source).into_owned());
+        }
+        Ok(Languages { languages: json.languages, sources })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["languages.json"]
+    }
+}
+
+/// Messages of the current language.
+#[derive(Resource, Debug)]
+pub struct Localization {
+    /// Identifier of the current language.
+    pub language: String,
+    pub font: Handle<Font>,
+    messages: Option<Messages>,
+    fallback: Messages,
+}
+
+impl Localization {
+    /// Only the compiled in messages of the [[FALLBACK_LANGUAGE]].
+    pub fn fallback() -> Self {
+        Localization {
+            language: FALLBACK_LANGUAGE.to_string(),
+            font: Handle::default(),
+            messages: None,
+            fallback: Messages::new(FALLBACK_LANGUAGE, FALLBACK_MESSAGES).expect("compiled in messages are valid"),
+        }
+    }
+
+    /// Messages of the language with the messages of the fallback source for missing ones.
+    pub fn new(language: &str, source: &str, fallback_source: &str) -> Result<Self, LocalizationError> {
+        Ok(Localization {
+            language: language.to_string(),
+            font: Handle::default(),
+            messages: Some(Messages::new(language, source)?),
+            fallback: Messages::new(FALLBACK_LANGUAGE, fallback_source)?,
+        })
+    }
+
+    /// Message without arguments.
+    pub fn text(&self, key: &str) -> String {
+        self.format(key, None)
+    }
+
+    /// Message with the arguments, the key itself when no language has the message.
+    pub fn format(&self, key: &str, args: Option<&FluentArgs>) -> String {
+        self.messages.as_ref().and_then(|messages| messages.format(key, args))
+            .or_else(|| self.fallback.format(key, args))
+            .unwrap_or_else(|| key.to_string())
+    }
+}
+
+/// Text of the message, updated when the language changes.
+#[derive(Component, Debug, Clone)]
+pub struct LocalizedText(pub &'static str);
+
+/// Languages asset, the current language is kept in [[Settings::language]].
+#[derive(Resource, Default)]
+pub struct LanguageLibrary(pub Handle<Languages>);
+
+pub struct LocalizationPlugin;
+
+impl Plugin for LocalizationPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_asset::<Languages>()
+            .init_asset_loader::<LanguagesLoader>()
+            .init_resource::<LanguageLibrary>()
+            .insert_resource(Localization::fallback())
+            .add_systems(Startup, load_languages)
+            .add_systems(PreUpdate, switch_language)
+            .add_systems(Update, update_localized_texts.run_if(resource_changed::<Localization>));
+    }
+}
+
+fn load_languages(
+    asset_server: Res<AssetServer>,
+    mut library: ResMut<LanguageLibrary>,
+    mut localization: ResMut<Localization>,
+) {
+    library.0 = asset_server.load(LANGUAGES_FILE);
+    localization.font = asset_server.load(DEFAULT_UI_FONT);
+}
+
+/// Applies the language from the settings (or the system locale) once languages are loaded,
+/// and again whenever the setting or the messages change.
+fn switch_language(
+    mut asset_events: EventReader<AssetEvent<Languages>>,
+    asset_server: Res<AssetServer>,
+    settings: Res<Settings>,
+    library: Res<LanguageLibrary>,
+    languages: Res<Assets<Languages>>,
+    mut localization: ResMut<Localization>,
+) {
+    let reloaded = asset_events.read().any(|event| event.is_loaded_with_dependencies(&library.0) || event.is_modified(&library.0));
+    if !reloaded && !settings.is_changed() {
+        return;
+    }
+    let Some(languages) = languages.get(&library.0) else {
+        return;
+    };
+
+    let ids = languages.ids();
+    let system_locale = sys_locale::get_locale();
+    let requested = settings.language.as_deref().into_iter().chain(system_locale.as_deref());
+    let language = negotiate_language(requested, &ids).unwrap_or_else(|| FALLBACK_LANGUAGE.to_string());
+    if !reloaded && language == localization.language {
+        return;
+    }
+
+    let source = languages.sources.get(&language).map_or("", String::as_str);
+    let fallback_source = languages.sources.get(FALLBACK_LANGUAGE).map_or(FALLBACK_MESSAGES, String::as_str);
+    match Localization::new(&language, source, fallback_source) {
+        Ok(mut new_localization) => {
+            let font = languages.info(&language).and_then(|info| info.font.clone());
+            new_localization.font = asset_server.load(font.unwrap_or_else(|| DEFAULT_UI_FONT.to_string()));
+            *localization = new_localization;
+        },
+        Err(e) => warn!("Failed to switch language to {language}: {e}"),
+    }
+}
+
+fn update_localized_texts(
+    localization: Res<Localization>,
+    mut texts: Query<(&LocalizedText, &mut Text, &mut TextFont)>,
+) {
+    for (localized, mut text, mut font) in &mut texts {
+        text.0 = localization.text(localized.0);
+        font.font = localization.font.clone();
+    }
+}
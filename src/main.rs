@@ -1,17 +1,23 @@
-mod game;
-mod direction;
-mod coords;
-mod animation;
-mod menu;
-mod control;
-mod gameplay;
-
-use game::{GameState, MyGamePlugin};
+use bevy_2d_test::{
+    cli::CliArgs,
+    control::InputSource,
+    game::MyGamePlugin,
+    headless,
+    replay::{Replay, ReplayPlayback, ReplayRecorder},
+    rng::SeedOverride,
+};
 use bevy::prelude::*;
 use bevy::window::WindowResolution;
 use bevy_framepace::{FramepaceSettings, Limiter};
 
 fn main() {
+    let cli_args = CliArgs::parse();
+
+    if cli_args.headless {
+        headless::run(cli_args);
+        return;
+    }
+
     let mut app = App::new();
 
     app.add_plugins(
@@ -42,6 +48,23 @@ fn main() {
     //     .add_plugins(LogDiagnosticsPlugin::default())
     // }
 
+    app.insert_resource(SeedOverride(cli_args.seed));
+
+    app.insert_resource(ReplayRecorder {
+        path: cli_args.record,
+        ..default()
+    });
+    if let Some(replay_path) = cli_args.replay {
+        match Replay::load(&replay_path) {
+            Ok(replay) => {
+                app.insert_resource(SeedOverride(Some(replay.seed)));
+                app.insert_resource(ReplayPlayback { replay, tick: 0 });
+                app.insert_resource(InputSource::Replay);
+            },
+            Err(e) => eprintln!("Failed to load replay {}: {e}", replay_path.display()),
+        }
+    }
+
     app.add_plugins(MyGamePlugin);
 
     app.run();
@@ -0,0 +1,65 @@
+use bevy::{color::palettes::css::LIME, prelude::*};
+
+use crate::gameplay::{pool::{BulletPool, EnemyPool, PoolStats}, LevelComponents};
+
+/// Key showing and hiding the [[DebugOverlay]].
+pub const KEY_DEBUG_OVERLAY: KeyCode = KeyCode::F3;
+
+/// Text with internals of the running game, hidden by default.
+#[derive(Component)]
+pub struct DebugOverlay;
+
+pub fn setup_debug_overlay(
+    mut commands: Commands,
+) {
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(LIME.into()),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(12.0),
+            right: Val::Px(12.0),
+            ..default()
+        },
+        Visibility::Hidden,
+        DebugOverlay,
+        LevelComponents,
+    ));
+}
+
+pub fn toggle_debug_overlay(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut overlay_query: Query<&mut Visibility, With<DebugOverlay>>,
+) {
+    if keyboard.just_pressed(KEY_DEBUG_OVERLAY) {
+        for mut visibility in &mut overlay_query {
+            visibility.toggle_visible_hidden();
+        }
+    }
+}
+
+pub fn update_debug_overlay(
+    bullet_pool: Option<Res<BulletPool>>,
+    enemy_pool: Option<Res<EnemyPool>>,
+    mut overlay_query: Query<(&mut Text, &Visibility), With<DebugOverlay>>,
+) {
+    let (Some(bullet_pool), Some(enemy_pool)) = (bullet_pool, enemy_pool) else {
+        return;
+    };
+    for (mut text, visibility) in &mut overlay_query {
+        if visibility != Visibility::Hidden {
+            text.0 = format!("{}\n{}", pool_text("Bullets", &bullet_pool.stats), pool_text("Enemies", &enemy_pool.stats));
+        }
+    }
+}
+
+fn pool_text(name: &str, stats: &PoolStats) -> String {
+    format!(
+        "{name}: {} active (peak {}), {} free, {} spawned, {} reused",
+        stats.active, stats.peak_active, stats.free, stats.spawned, stats.reused,
+    )
+}
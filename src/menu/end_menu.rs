@@ -1,42 +1,71 @@
 use bevy::prelude::*;
 
-use crate::{gameplay::{GameScore, LevelComponents}, GameState};
+use crate::{
+    gameplay::{scoring::{GameScore, ScoreBreakdown}, LevelComponents},
+    high_scores::LastRunPlace,
+    localization::{FluentArgs, Localization, LocalizedText},
+    menu::navigation::{menu_screen, menu_text, spawn_button, MenuAction, MenuActivated, MENU_TEXT_SIZE},
+    rng::GameRng,
+    GameState,
+};
 
+/// Root of the game over screen, despawned when leaving it.
 #[derive(Component)]
 pub struct EndScreenElement;
 
 pub fn setup_end_screen(
     mut commands: Commands,
     score: Res<GameScore>,
+    breakdown: Res<ScoreBreakdown>,
+    rng: Res<GameRng>,
+    last_run_place: Res<LastRunPlace>,
+    localization: Res<Localization>,
     level_entities: Query<Entity, With<LevelComponents>>,
 ) {
     for level_entity in &level_entities {
         commands.entity(level_entity).despawn_recursive();
     }
 
-    commands.spawn((
-        Text::new(format!("Game over\nScore: {}\nPress Enter to restart.", score.0)),
-        TextLayout::new_with_justify(JustifyText::Center),
-        Node {
-            position_type: PositionType::Absolute,
-            top: Val::Percent(50.0),
-            width: Val::Vw(100.0),
-            justify_content: JustifyContent::Center,
-            ..default()
-        },
-        EndScreenElement
-    ));
+    let font = &localization.font;
+    commands.spawn((menu_screen(), EndScreenElement)).with_children(|builder| {
+        builder.spawn((
+            menu_text(end_text(&localization, score.0, &breakdown, last_run_place.0, rng.seed), font, MENU_TEXT_SIZE),
+            TextLayout::new_with_justify(JustifyText::Center),
+        ));
+        spawn_button(builder, MenuAction::Retry, font, localization.text("menu-retry"), LocalizedText("menu-retry"));
+        spawn_button(builder, MenuAction::MainMenu, font, localization.text("menu-main-menu"), LocalizedText("menu-main-menu"));
+    });
 }
 
-pub fn handle_restart_game(
+fn end_text(localization: &Localization, score: u32, breakdown: &ScoreBreakdown, place: Option<usize>, seed: u64) -> String {
+    let message = |key, args: &[(&'static str, u32)]| {
+        localization.format(key, Some(&args.iter().copied().collect()))
+    };
+    // Seeds don't fit into Fluent numbers
+    let seed_args = FluentArgs::from_iter([("seed", seed.to_string())]);
+    let high_score = place.map(|place| message("end-high-score", &[("place", place as u32 + 1)]));
+    [
+        localization.text("end-title"),
+        message("end-score", &[("score", score)]),
+        message("end-kills", &[("kills", breakdown.kills), ("points", breakdown.kill_points)]),
+        message("end-combo", &[("bonus", breakdown.combo_bonus), ("multiplier", breakdown.best_multiplier)]),
+        message("end-streak", &[("bonus", breakdown.streak_bonus), ("streak", breakdown.best_streak)]),
+    ].into_iter()
+        .chain(high_score)
+        .chain([String::new(), localization.format("end-seed", Some(&seed_args))])
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn handle_end_menu(
     mut commands: Commands,
-    keys: Res<ButtonInput<KeyCode>>,
-    end_entities: Query<Entity, With<EndScreenElement>>,
+    mut activated: EventReader<MenuActivated>,
 ) {
-    if keys.just_pressed(KeyCode::Enter) {
-        for end_entity in &end_entities {
-            commands.entity(end_entity).despawn();
+    for event in activated.read() {
+        match event.action {
+            MenuAction::Retry => commands.set_state(GameState::InGame),
+            MenuAction::MainMenu => commands.set_state(GameState::MainMenu),
+            _ => {},
         }
-        commands.set_state(GameState::InGame);
     }
-}
\ No newline at end of file
+}
@@ -1,102 +1,505 @@
-use bevy::{color::palettes::css::{RED, YELLOW}, prelude::*};
+//! HUD of the running game: segmented health bar, score, weapon panel with ammo and shots fired,
+//! wave counter, survival timer and optional FPS readout ([[Settings::show_fps]]).
+//!
+//! Each part is updated only when the state it shows changes. Sizes are relative to the window
+//! and texts are scaled with its height, see [[HudText]]. Colors come from the [[Palette]].
 
-use crate::gameplay::{player::PlayerInfo, GameScore, LevelComponents};
+use bevy::{
+    color::palettes::css::{ORANGE, WHITE, YELLOW},
+    diagnostic::{DiagnosticsStore, EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin},
+    prelude::*,
+    window::{PrimaryWindow, WindowResized},
+};
 
-#[derive(Component)]
-pub struct HealthBar(u32);
+use crate::{
+    accessibility::{Palette, MAX_TEXT_SCALE, MIN_TEXT_SCALE},
+    gameplay::{
+        damage::Health,
+        player::{PlayerInfo, PLAYER_HEALTH},
+        scoring::{Combo, GameScore},
+        stats::RunStats,
+        LevelComponents, FIXED_TICKS_PER_SECOND,
+    },
+    localization::{FluentArgs, Localization},
+    settings::Settings,
+    GameState,
+};
+
+/// Key showing and hiding the FPS readout.
+pub const KEY_FPS: KeyCode = KeyCode::F2;
+
+/// Window height at which HUD texts have their own size.
+const HUD_REFERENCE_HEIGHT: f32 = 1024.0;
+
+pub const HEALTH_PER_SEGMENT: u32 = 10;
+
+/// Health lost recently stays shown for a while before the trail shrinks.
+const HEALTH_TRAIL_DELAY_SECS: f32 = 0.4;
+
+/// Speed the health trail shrinks with, in health points per second.
+const HEALTH_TRAIL_SPEED: f32 = 40.0;
+
+const HEALTH_FLASH_SECS: f32 = 0.25;
+
+const FPS_UPDATE_INTERVAL_SECS: f32 = 0.5;
+
+/// Waves only number parts of the run, enemies spawn at the same pace in all of them.
+pub const WAVE_DURATION_SECS: u32 = 30;
 
+/// Part of the health bar flash left with [[crate::settings::AccessibilitySettings::reduced_flashes]].
+const REDUCED_HEALTH_FLASH: f32 = 0.3;
+
+/// Font size of a HUD text when the window is [[HUD_REFERENCE_HEIGHT]] high and texts aren't scaled.
 #[derive(Component)]
-pub struct ScoreBar(u32);
+pub struct HudText(pub f32);
 
-pub fn update_game_ui(
-    player_query: Query<&PlayerInfo>,
-    score: Res<GameScore>,
-    mut health_query: Query<(&mut Text, &mut HealthBar), Without<ScoreBar>>,
-    mut score_query: Query<(&mut Text, &mut ScoreBar), Without<HealthBar>>,
-) {
-    for (mut text, mut health_bar) in &mut health_query {
-        for player_info in &player_query {
-            if health_bar.0 != player_info.health {
-                health_bar.0 = player_info.health;
-                text.0 = health_text(health_bar.0);
-            }
+/// Color of a HUD part, from the [[Palette]]. Background of nodes, color of texts.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+#[require(BackgroundColor)]
+pub enum HudColor {
+    /// Also sets the border color.
+    Panel,
+    BarBackground,
+    Health,
+    HealthTrail,
+    Ammo,
+}
+
+impl HudColor {
+    pub fn of(self, palette: &Palette) -> Color {
+        match self {
+            HudColor::Panel => palette.panel,
+            HudColor::BarBackground => palette.bar_background,
+            HudColor::Health => palette.health,
+            HudColor::HealthTrail => palette.health_trail,
+            HudColor::Ammo => palette.ammo,
+        }
+    }
+}
+
+/// Player's health, animated when it changes: the lost part stays as a trail for a moment
+/// and shrinks after that, while the bar flashes.
+#[derive(Component, Debug, Clone)]
+pub struct HealthBar {
+    pub health: f32,
+    pub trail: f32,
+    trail_delay: f32,
+    flash: f32,
+}
+
+impl HealthBar {
+    pub fn new(health: u32) -> Self {
+        HealthBar { health: health as f32, trail: health as f32, trail_delay: 0.0, flash: 0.0 }
+    }
+
+    pub fn set_health(&mut self, health: u32) {
+        let health = health as f32;
+        if health < self.health {
+            self.trail_delay = HEALTH_TRAIL_DELAY_SECS;
+            self.flash = HEALTH_FLASH_SECS;
         }
+        self.health = health;
+        // Healing isn't animated
+        self.trail = self.trail.max(health);
     }
 
-    for (mut text, mut score_bar) in &mut score_query {
-        if score_bar.0 != score.0 {
-            score_bar.0 = score.0;
-            text.0 = score_text(score_bar.0);
+    pub fn tick(&mut self, delta_secs: f32) {
+        self.flash = (self.flash - delta_secs).max(0.0);
+        if self.trail_delay > 0.0 {
+            self.trail_delay -= delta_secs;
+        } else {
+            self.trail = (self.trail - HEALTH_TRAIL_SPEED * delta_secs).max(self.health);
         }
     }
+
+    pub fn is_animating(&self) -> bool {
+        self.flash > 0.0 || self.trail > self.health
+    }
+
+    /// How much white is mixed into the bar, from 0 to 1.
+    pub fn flash(&self) -> f32 {
+        self.flash / HEALTH_FLASH_SECS
+    }
+}
+
+/// Part of the segment filled by the value, from 0 to 1.
+pub fn segment_fill(value: f32, segment: u32, per_segment: u32) -> f32 {
+    ((value - (segment * per_segment) as f32) / per_segment as f32).clamp(0.0, 1.0)
+}
+
+/// Filled part of a [[HealthBar]] segment, either of the current health or of the trail.
+#[derive(Component)]
+pub struct HealthSegmentFill {
+    pub segment: u32,
+    pub trail: bool,
+}
+
+#[derive(Component)]
+pub struct HealthLabel;
+
+#[derive(Component)]
+pub struct ScoreBar;
+
+/// Current combo multiplier, shown next to the [[ScoreBar]].
+#[derive(Component)]
+pub struct MultiplierBar;
+
+/// Shots fired in the run, with the last shown number.
+#[derive(Component)]
+pub struct ShotsLabel(pub u32);
+
+/// Wave of the run, with the last shown number.
+#[derive(Component)]
+pub struct WaveLabel(pub u32);
+
+/// Wave at the given time of the run, starting with 1.
+pub fn wave_at(seconds: u32) -> u32 {
+    seconds / WAVE_DURATION_SECS + 1
+}
+
+/// Time survived, with the last shown number of seconds.
+#[derive(Component)]
+pub struct RunTimer(pub u32);
+
+#[derive(Component)]
+pub struct FpsReadout {
+    timer: Timer,
+}
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugins(FrameTimeDiagnosticsPlugin);
+        }
+        if !app.is_plugin_added::<EntityCountDiagnosticsPlugin>() {
+            app.add_plugins(EntityCountDiagnosticsPlugin);
+        }
+        app
+            .add_systems(OnEnter(GameState::InGame), setup_game_ui)
+            .add_systems(Update, (
+                update_health_bar,
+                update_score_ui,
+                update_shots_label,
+                update_run_timer,
+                toggle_fps_readout,
+                update_fps_readout,
+                scale_hud_texts,
+                apply_hud_palette,
+            ).run_if(in_state(GameState::InGame)));
+    }
+}
+
+fn hud_text(text: String, font: &Handle<Font>, size: f32, color: impl Into<Color>) -> impl Bundle {
+    (
+        Text::new(text),
+        TextFont { font: font.clone(), font_size: size, ..default() },
+        TextColor(color.into()),
+        HudText(size),
+    )
+}
+
+fn panel(node: Node) -> impl Bundle {
+    (
+        Node { padding: UiRect::axes(Val::Vh(1.0), Val::Vh(0.5)), border: UiRect::all(Val::Vh(0.2)), ..node },
+        BorderRadius::all(Val::Vh(0.8)),
+        BorderColor::default(),
+        HudColor::Panel,
+    )
 }
 
 pub fn setup_game_ui(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
+    localization: Res<Localization>,
+    settings: Res<Settings>,
 ) {
-    commands.insert_resource(GameScore(0));
+    let font = &localization.font;
 
+    // Health and score in the top left corner
     commands.spawn((
         Node {
             position_type: PositionType::Absolute,
-            top: Val::Px(12.0),
-            left: Val::Px(12.0),
+            top: Val::Vh(1.5),
+            left: Val::Vh(1.5),
             flex_direction: FlexDirection::Column,
-            row_gap: Val::Px(12.),
+            row_gap: Val::Vh(1.0),
             ..default()
         },
         LevelComponents,
     )).with_children(|builder| {
-        builder
-        .spawn((
-            Node {
-                padding: UiRect::axes(Val::Px(5.), Val::Px(1.)),
-                ..default()
-            },
-        ))
+        builder.spawn(panel(Node { align_items: AlignItems::Center, column_gap: Val::Vh(1.0), ..default() }))
         .with_children(|builder| {
+            builder.spawn((hud_text(health_text(&localization, PLAYER_HEALTH), font, 28.0, WHITE), HudColor::Health, HealthLabel));
             builder.spawn((
-                Text::new(health_text(100)),
-                TextFont {
-                font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
-                font_size: 48.0,
-                    ..default()
-                },
-                TextColor(RED.into()),
-                HealthBar(100),
-                LevelComponents,
-            ));
+                Node { width: Val::Vw(24.0), height: Val::Vh(2.4), column_gap: Val::Vh(0.3), ..default() },
+                HealthBar::new(PLAYER_HEALTH),
+            )).with_children(|builder| {
+                for segment in 0 .. PLAYER_HEALTH.div_ceil(HEALTH_PER_SEGMENT) {
+                    builder.spawn((
+                        Node { flex_grow: 1.0, height: Val::Percent(100.0), ..default() },
+                        HudColor::BarBackground,
+                    )).with_children(|builder| {
+                        for trail in [true, false] {
+                            builder.spawn((
+                                Node {
+                                    position_type: PositionType::Absolute,
+                                    width: Val::Percent(100.0),
+                                    height: Val::Percent(100.0),
+                                    ..default()
+                                },
+                                if trail { HudColor::HealthTrail } else { HudColor::Health },
+                                HealthSegmentFill { segment, trail },
+                            ));
+                        }
+                    });
+                }
+            });
         });
 
+        builder.spawn(panel(Node { align_items: AlignItems::Center, column_gap: Val::Vh(2.0), ..default() }))
+        .with_children(|builder| {
+            builder.spawn((hud_text(score_text(&localization, 0), font, 40.0, YELLOW), ScoreBar));
+            builder.spawn((hud_text(multiplier_text(&localization, 1), font, 28.0, ORANGE), MultiplierBar));
+        });
+    });
 
-        builder
-        .spawn((
-            Node {
-                padding: UiRect::axes(Val::Px(5.), Val::Px(1.)),
-                ..default()
-            },
-        ))
+    // Wave and survival time at the top
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Vh(1.5),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        LevelComponents,
+    )).with_children(|builder| {
+        builder.spawn(panel(Node { flex_direction: FlexDirection::Column, align_items: AlignItems::Center, ..default() }))
         .with_children(|builder| {
+            builder.spawn((hud_text(wave_text(&localization, 1), font, 32.0, WHITE), WaveLabel(1)));
+            builder.spawn((hud_text(time_text(&localization, 0), font, 24.0, WHITE), RunTimer(0)));
             builder.spawn((
-                Text::new(score_text(0)),
-                TextFont {
-                font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
-                font_size: 48.0,
-                    ..default()
-                },
-                TextColor(YELLOW.into()),
-                ScoreBar(0),
-                LevelComponents,
+                hud_text(String::new(), font, 16.0, WHITE),
+                FpsReadout { timer: Timer::from_seconds(FPS_UPDATE_INTERVAL_SECS, TimerMode::Repeating) },
+                if settings.show_fps { Visibility::Inherited } else { Visibility::Hidden },
             ));
         });
     });
+
+    // Weapon in the bottom left corner
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Vh(1.5),
+            left: Val::Vh(1.5),
+            ..default()
+        },
+        LevelComponents,
+    )).with_children(|builder| {
+        builder.spawn(panel(Node { align_items: AlignItems::Center, column_gap: Val::Vh(1.5), ..default() }))
+        .with_children(|builder| {
+            builder.spawn((hud_text(localization.text("hud-ammo"), font, 32.0, WHITE), HudColor::Ammo));
+            builder.spawn((hud_text(shots_text(&localization, 0), font, 24.0, WHITE), ShotsLabel(0)));
+        });
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_health_bar(
+    time: Res<Time>,
+    localization: Res<Localization>,
+    settings: Res<Settings>,
+    palette: Res<Palette>,
+    player_query: Query<Ref<Health>, With<PlayerInfo>>,
+    mut bar_query: Query<&mut HealthBar>,
+    mut label_query: Query<&mut Text, With<HealthLabel>>,
+    mut fill_query: Query<(&HealthSegmentFill, &mut Node, &mut BackgroundColor)>,
+) {
+    let Ok(health) = player_query.get_single() else {
+        return;
+    };
+    let Ok(mut bar) = bar_query.get_single_mut() else {
+        return;
+    };
+    if health.is_changed() {
+        bar.set_health(health.current);
+        for mut text in &mut label_query {
+            text.0 = health_text(&localization, health.current);
+        }
+    } else if !bar.is_animating() {
+        return;
+    }
+    bar.tick(time.delta_secs());
+
+    let flash = if settings.accessibility.reduced_flashes { bar.flash() * REDUCED_HEALTH_FLASH } else { bar.flash() };
+    let color = palette.health.mix(&WHITE.into(), flash);
+    for (fill, mut node, mut background) in &mut fill_query {
+        let value = if fill.trail { bar.trail } else { bar.health };
+        node.width = Val::Percent(100.0 * segment_fill(value, fill.segment, HEALTH_PER_SEGMENT));
+        if !fill.trail {
+            background.0 = color;
+        }
+    }
+}
+
+pub fn update_score_ui(
+    score: Res<GameScore>,
+    combo: Res<Combo>,
+    localization: Res<Localization>,
+    mut score_query: Query<&mut Text, (With<ScoreBar>, Without<MultiplierBar>)>,
+    mut multiplier_query: Query<&mut Text, (With<MultiplierBar>, Without<ScoreBar>)>,
+) {
+    if score.is_changed() {
+        for mut text in &mut score_query {
+            text.0 = score_text(&localization, score.0);
+        }
+    }
+    if combo.is_changed() {
+        for mut text in &mut multiplier_query {
+            text.0 = multiplier_text(&localization, combo.multiplier);
+        }
+    }
+}
+
+pub fn update_shots_label(
+    stats: Res<RunStats>,
+    localization: Res<Localization>,
+    mut label_query: Query<(&mut Text, &mut ShotsLabel)>,
+) {
+    for (mut text, mut label) in &mut label_query {
+        if label.0 != stats.shots_fired {
+            label.0 = stats.shots_fired;
+            text.0 = shots_text(&localization, stats.shots_fired);
+        }
+    }
 }
 
-fn health_text(points: u32) -> String {
-    format!("♥ {points}")
+/// Updates the survival time and the wave it's in.
+pub fn update_run_timer(
+    stats: Res<RunStats>,
+    localization: Res<Localization>,
+    mut timer_query: Query<(&mut Text, &mut RunTimer), Without<WaveLabel>>,
+    mut wave_query: Query<(&mut Text, &mut WaveLabel), Without<RunTimer>>,
+) {
+    let seconds = (stats.ticks as f64 / FIXED_TICKS_PER_SECOND) as u32;
+    for (mut text, mut timer) in &mut timer_query {
+        if timer.0 != seconds {
+            timer.0 = seconds;
+            text.0 = time_text(&localization, seconds);
+        }
+    }
+    let wave = wave_at(seconds);
+    for (mut text, mut label) in &mut wave_query {
+        if label.0 != wave {
+            label.0 = wave;
+            text.0 = wave_text(&localization, wave);
+        }
+    }
 }
 
-fn score_text(points: u32) -> String {
-    format!("★ {points}")
-}
\ No newline at end of file
+pub fn toggle_fps_readout(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<Settings>,
+    mut readout_query: Query<&mut Visibility, With<FpsReadout>>,
+) {
+    if keyboard.just_pressed(KEY_FPS) {
+        settings.show_fps = !settings.show_fps;
+    }
+    if settings.is_changed() {
+        for mut visibility in &mut readout_query {
+            *visibility = if settings.show_fps { Visibility::Inherited } else { Visibility::Hidden };
+        }
+    }
+}
+
+pub fn update_fps_readout(
+    time: Res<Time>,
+    diagnostics: Res<DiagnosticsStore>,
+    localization: Res<Localization>,
+    mut readout_query: Query<(&mut Text, &mut FpsReadout, &Visibility)>,
+) {
+    for (mut text, mut readout, visibility) in &mut readout_query {
+        readout.timer.tick(time.delta());
+        if *visibility == Visibility::Hidden || !readout.timer.just_finished() {
+            continue;
+        }
+        let fps = diagnostics.get(&FrameTimeDiagnosticsPlugin::FPS).and_then(|fps| fps.smoothed()).unwrap_or(0.0);
+        let entities = diagnostics.get(&EntityCountDiagnosticsPlugin::ENTITY_COUNT).and_then(|count| count.value()).unwrap_or(0.0);
+        let args = FluentArgs::from_iter([("fps", fps.round() as u32), ("entities", entities as u32)]);
+        text.0 = localization.format("hud-fps", Some(&args));
+    }
+}
+
+/// Scales HUD texts with the window height and the text scale of the settings,
+/// when the window is resized, the settings are changed or texts are added.
+pub fn scale_hud_texts(
+    mut resized_events: EventReader<WindowResized>,
+    settings: Res<Settings>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    mut text_query: Query<(Ref<HudText>, &mut TextFont)>,
+) {
+    let resized = resized_events.read().count() > 0 || settings.is_changed();
+    let Ok(window) = window.get_single() else {
+        return;
+    };
+    let text_scale = settings.accessibility.text_scale.clamp(MIN_TEXT_SCALE, MAX_TEXT_SCALE);
+    let scale = window.height() / HUD_REFERENCE_HEIGHT * text_scale;
+    for (hud_text, mut font) in &mut text_query {
+        if resized || hud_text.is_added() {
+            font.font_size = hud_text.0 * scale;
+        }
+    }
+}
+
+/// Colors HUD parts when they are added or the palette changes.
+pub fn apply_hud_palette(
+    palette: Res<Palette>,
+    mut part_query: Query<(Ref<HudColor>, &mut BackgroundColor, Option<&mut TextColor>)>,
+    mut border_query: Query<(Ref<HudColor>, &mut BorderColor)>,
+) {
+    for (part, mut background, text) in &mut part_query {
+        if !palette.is_changed() && !part.is_added() {
+            continue;
+        }
+        match text {
+            Some(mut text) => text.0 = part.of(&palette),
+            None => background.0 = part.of(&palette),
+        }
+    }
+    for (part, mut border) in &mut border_query {
+        if (palette.is_changed() || part.is_added()) && *part == HudColor::Panel {
+            border.0 = palette.panel_border;
+        }
+    }
+}
+
+fn health_text(localization: &Localization, points: u32) -> String {
+    localization.format("hud-health", Some(&FluentArgs::from_iter([("points", points)])))
+}
+
+fn score_text(localization: &Localization, points: u32) -> String {
+    localization.format("hud-score", Some(&FluentArgs::from_iter([("points", points)])))
+}
+
+fn multiplier_text(localization: &Localization, multiplier: u32) -> String {
+    if multiplier > 1 {
+        localization.format("hud-multiplier", Some(&FluentArgs::from_iter([("multiplier", multiplier)])))
+    } else {
+        String::new()
+    }
+}
+
+fn wave_text(localization: &Localization, wave: u32) -> String {
+    localization.format("hud-wave", Some(&FluentArgs::from_iter([("wave", wave)])))
+}
+
+fn shots_text(localization: &Localization, shots: u32) -> String {
+    localization.format("hud-shots", Some(&FluentArgs::from_iter([("shots", shots)])))
+}
+
+fn time_text(localization: &Localization, seconds: u32) -> String {
+    // Strings keep the leading zero
+    let args = FluentArgs::from_iter([("minutes", (seconds / 60).to_string()), ("seconds", format!("{:02}", seconds % 60))]);
+    localization.format("hud-time", Some(&args))
+}
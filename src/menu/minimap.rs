@@ -0,0 +1,300 @@
+//! Map of the whole arena in a corner of the screen.
+//!
+//! The map is drawn on the CPU into a small texture, so it costs the same for any number of enemies:
+//! static colliders are drawn once per level, moving objects are plotted as single pixels a few times a second.
+
+use std::time::Duration;
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    accessibility::Palette,
+    gameplay::{
+        arena::ARENA_BOUNDS,
+        enemy::{EnemyKind, EnemySpawner, EnemyState},
+        player::PlayerInfo,
+        LevelComponents,
+    },
+    settings::{MinimapSettings, ScreenCorner, Settings},
+    GameState,
+};
+
+/// Width and height of the map texture.
+pub const MINIMAP_RESOLUTION: u32 = 128;
+
+pub const KEY_MINIMAP: KeyCode = KeyCode::KeyM;
+
+const MINIMAP_MARGIN: f32 = 12.0;
+
+const MINIMAP_UPDATE_INTERVAL: Duration = Duration::from_millis(66);
+
+const COLOR_BACKGROUND: [u8; 4] = [16, 16, 24, 200];
+const COLOR_BORDER: [u8; 4] = [200, 200, 200, 255];
+const COLOR_WALL: [u8; 4] = [110, 110, 120, 255];
+
+fn pixel_color(color: Color) -> [u8; 4] {
+    color.to_srgba().to_u8_array()
+}
+
+fn enemy_color(kind: EnemyKind, palette: &Palette) -> [u8; 4] {
+    match kind {
+        EnemyKind::Slime => pixel_color(palette.enemy),
+    }
+}
+
+/// Marks objects without their own minimap marker, which should be shown on the map.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MinimapIcon {
+    Pickup,
+}
+
+/// RGBA pixels of the map.
+#[derive(Debug, Clone)]
+pub struct MinimapCanvas {
+    pub pixels: Vec<u8>,
+    pub bounds: Rect,
+}
+
+impl MinimapCanvas {
+    pub fn new(bounds: Rect) -> Self {
+        MinimapCanvas { pixels: vec![0; (MINIMAP_RESOLUTION * MINIMAP_RESOLUTION * 4) as usize], bounds }
+    }
+
+    /// Pixel showing given world position, or none when it is out of the map.
+    pub fn pixel_of(&self, position: Vec2) -> Option<UVec2> {
+        let relative = (position - self.bounds.min) / self.bounds.size();
+        if !(0.0 .. 1.0).contains(&relative.x) || !(0.0 .. 1.0).contains(&relative.y) {
+            return None;
+        }
+        let x = (relative.x * MINIMAP_RESOLUTION as f32) as u32;
+        // Texture rows go from the top
+        let y = ((1.0 - relative.y) * MINIMAP_RESOLUTION as f32) as u32;
+        Some(UVec2::new(x, y.min(MINIMAP_RESOLUTION - 1)))
+    }
+
+    pub fn put(&mut self, pixel: UVec2, color: [u8; 4]) {
+        if pixel.x < MINIMAP_RESOLUTION && pixel.y < MINIMAP_RESOLUTION {
+            let i = ((pixel.y * MINIMAP_RESOLUTION + pixel.x) * 4) as usize;
+            self.pixels[i .. i + 4].copy_from_slice(&color);
+        }
+    }
+
+    pub fn get(&self, pixel: UVec2) -> [u8; 4] {
+        let i = ((pixel.y * MINIMAP_RESOLUTION + pixel.x) * 4) as usize;
+        self.pixels[i .. i + 4].try_into().unwrap()
+    }
+
+    /// Marker of given radius in pixels around the world position.
+    pub fn plot(&mut self, position: Vec2, radius: i32, color: [u8; 4]) {
+        let Some(center) = self.pixel_of(position) else {
+            return;
+        };
+        for dy in -radius ..= radius {
+            for dx in -radius ..= radius {
+                let pixel = center.as_ivec2() + IVec2::new(dx, dy);
+                if pixel.x >= 0 && pixel.y >= 0 {
+                    self.put(pixel.as_uvec2(), color);
+                }
+            }
+        }
+    }
+
+    /// Fills the world area, clipped to the map.
+    pub fn fill_rect(&mut self, area: Rect, color: [u8; 4]) {
+        let clipped = area.intersect(self.bounds);
+        if clipped.is_empty() {
+            return;
+        }
+        let scale = MINIMAP_RESOLUTION as f32 / self.bounds.size();
+        let min_x = ((clipped.min.x - self.bounds.min.x) * scale.x).floor() as u32;
+        let max_x = ((clipped.max.x - self.bounds.min.x) * scale.x).ceil() as u32;
+        let min_y = ((self.bounds.max.y - clipped.max.y) * scale.y).floor() as u32;
+        let max_y = ((self.bounds.max.y - clipped.min.y) * scale.y).ceil() as u32;
+        for y in min_y .. max_y.min(MINIMAP_RESOLUTION) {
+            for x in min_x .. max_x.min(MINIMAP_RESOLUTION) {
+                self.put(UVec2::new(x, y), color);
+            }
+        }
+    }
+
+    pub fn outline(&mut self, color: [u8; 4]) {
+        for i in 0 .. MINIMAP_RESOLUTION {
+            self.put(UVec2::new(i, 0), color);
+            self.put(UVec2::new(i, MINIMAP_RESOLUTION - 1), color);
+            self.put(UVec2::new(0, i), color);
+            self.put(UVec2::new(MINIMAP_RESOLUTION - 1, i), color);
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct Minimap {
+    pub image: Handle<Image>,
+    /// Arena and static colliders, drawn once they are spawned.
+    background: Option<MinimapCanvas>,
+    canvas: MinimapCanvas,
+    timer: Timer,
+}
+
+#[derive(Component)]
+pub struct MinimapPanel;
+
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(OnEnter(GameState::InGame), setup_minimap)
+            .add_systems(Update, (
+                toggle_minimap,
+                layout_minimap,
+                draw_minimap,
+            ).chain().run_if(in_state(GameState::InGame)));
+    }
+}
+
+fn setup_minimap(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    settings: Res<Settings>,
+) {
+    let image = images.add(Image::new_fill(
+        Extent3d { width: MINIMAP_RESOLUTION, height: MINIMAP_RESOLUTION, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    ));
+    commands.insert_resource(Minimap {
+        image: image.clone(),
+        background: None,
+        canvas: MinimapCanvas::new(ARENA_BOUNDS),
+        timer: Timer::new(MINIMAP_UPDATE_INTERVAL, TimerMode::Repeating),
+    });
+    commands.spawn((
+        ImageNode::new(image).with_color(panel_color(&settings.minimap)),
+        panel_node(&settings.minimap),
+        panel_visibility(&settings.minimap),
+        MinimapPanel,
+        LevelComponents,
+    ));
+}
+
+fn panel_color(settings: &MinimapSettings) -> Color {
+    Color::WHITE.with_alpha(settings.opacity)
+}
+
+fn panel_visibility(settings: &MinimapSettings) -> Visibility {
+    if settings.visible { Visibility::Inherited } else { Visibility::Hidden }
+}
+
+fn panel_node(settings: &MinimapSettings) -> Node {
+    let margin = Val::Px(MINIMAP_MARGIN);
+    let (top, bottom) = match settings.corner {
+        ScreenCorner::TopLeft | ScreenCorner::TopRight => (margin, Val::Auto),
+        ScreenCorner::BottomLeft | ScreenCorner::BottomRight => (Val::Auto, margin),
+    };
+    let (left, right) = match settings.corner {
+        ScreenCorner::TopLeft | ScreenCorner::BottomLeft => (margin, Val::Auto),
+        ScreenCorner::TopRight | ScreenCorner::BottomRight => (Val::Auto, margin),
+    };
+    Node {
+        position_type: PositionType::Absolute,
+        width: Val::Px(settings.size),
+        height: Val::Px(settings.size),
+        top,
+        bottom,
+        left,
+        right,
+        ..default()
+    }
+}
+
+fn toggle_minimap(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<Settings>,
+) {
+    if keyboard.just_pressed(KEY_MINIMAP) {
+        settings.minimap.visible = !settings.minimap.visible;
+    }
+}
+
+fn layout_minimap(
+    settings: Res<Settings>,
+    mut panel: Single<(&mut Node, &mut ImageNode, &mut Visibility), With<MinimapPanel>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    let (node, image, visibility) = &mut *panel;
+    **node = panel_node(&settings.minimap);
+    image.color = panel_color(&settings.minimap);
+    **visibility = panel_visibility(&settings.minimap);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_minimap(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    palette: Res<Palette>,
+    mut minimap: ResMut<Minimap>,
+    mut images: ResMut<Assets<Image>>,
+    spawner: Res<EnemySpawner>,
+    colliders: Query<(&RigidBody, &Collider, &Transform)>,
+    player_query: Query<&Transform, With<PlayerInfo>>,
+    enemy_query: Query<(&Transform, &EnemyKind, &EnemyState)>,
+    icon_query: Query<(&Transform, &MinimapIcon)>,
+) {
+    if !settings.minimap.visible || !minimap.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let minimap = &mut *minimap;
+
+    if minimap.background.is_none() {
+        let mut background = MinimapCanvas::new(ARENA_BOUNDS);
+        background.fill_rect(ARENA_BOUNDS, COLOR_BACKGROUND);
+        for (body, collider, transform) in &colliders {
+            if *body != RigidBody::Fixed {
+                continue;
+            }
+            if let Some(cuboid) = collider.as_cuboid() {
+                let area = Rect::from_center_half_size(transform.translation.xy(), cuboid.half_extents());
+                background.fill_rect(area, COLOR_WALL);
+            }
+        }
+        background.outline(COLOR_BORDER);
+        minimap.background = Some(background);
+    }
+    let Some(background) = &minimap.background else {
+        return;
+    };
+
+    let canvas = &mut minimap.canvas;
+    canvas.pixels.copy_from_slice(&background.pixels);
+    for location in &spawner.locations {
+        canvas.plot(location.xy(), 1, pixel_color(palette.spawn_point));
+    }
+    for (transform, icon) in &icon_query {
+        match icon {
+            MinimapIcon::Pickup => canvas.plot(transform.translation.xy(), 1, pixel_color(palette.pickup)),
+        }
+    }
+    // Dying enemies are no threat anymore
+    for (transform, kind, _) in enemy_query.iter().filter(|(_, _, state)| **state == EnemyState::Alive) {
+        canvas.plot(transform.translation.xy(), 0, enemy_color(*kind, &palette));
+    }
+    for transform in &player_query {
+        canvas.plot(transform.translation.xy(), 1, pixel_color(palette.player));
+    }
+
+    if let Some(image) = images.get_mut(&minimap.image) {
+        image.data.copy_from_slice(&canvas.pixels);
+    }
+}
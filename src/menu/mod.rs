@@ -1,3 +1,8 @@
+pub mod navigation;
 pub mod start_menu;
+pub mod options_menu;
 pub mod in_game_menu;
 pub mod end_menu;
+pub mod debug_overlay;
+pub mod minimap;
+pub mod threat_indicators;
@@ -0,0 +1,289 @@
+//! Focusable menu buttons, used with mouse, keyboard and gamepad.
+//!
+//! Buttons of the current screen are [[MenuButton]]s, one of them has the [[MenuFocus]].
+//! Arrows, WASD, the gamepad's D-pad or left stick move the focus, hovering the mouse over a button focuses it.
+//! Enter, Space, a click or the gamepad's south button activate the focused button, which sends [[MenuActivated]]
+//! for the screen to handle. Escape or the gamepad's east button activate the screen's [[MenuAction::Back]] button.
+//! Left and right change options (e.g. volume) without cycling through all their values.
+
+use bevy::prelude::*;
+
+use crate::{accessibility::ScaledText, audio::{PlaySound, SoundKind}};
+
+const BUTTON_COLOR: Color = Color::srgba(0.15, 0.15, 0.2, 0.85);
+const FOCUSED_BUTTON_COLOR: Color = Color::srgba(0.3, 0.3, 0.45, 0.95);
+const PRESSED_BUTTON_COLOR: Color = Color::srgba(0.45, 0.45, 0.65, 1.0);
+const BUTTON_BORDER_COLOR: Color = Color::srgba(0.4, 0.4, 0.5, 0.85);
+const FOCUSED_BUTTON_BORDER_COLOR: Color = Color::srgb(1.0, 0.85, 0.3);
+
+/// Font size of menu texts other than titles and buttons.
+pub const MENU_TEXT_SIZE: f32 = 20.0;
+
+const BUTTON_TEXT_SIZE: f32 = 28.0;
+
+/// Left stick deflection which counts as a press of the D-pad.
+const STICK_THRESHOLD: f32 = 0.5;
+
+/// What a button does. Buttons of a screen are navigated in the order of their actions here.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MenuAction {
+    Play,
+    Retry,
+    MainMenu,
+    Options,
+    HighScores,
+    Credits,
+    Quit,
+    Language,
+    MusicVolume,
+    SoundVolume,
+    Accessibility,
+    ColorScheme,
+    EnemyOutlines,
+    TextScale,
+    HighContrastHud,
+    FireMode,
+    AimAssist,
+    AimAssistStrength,
+    ScreenShake,
+    ReducedFlashes,
+    GameSpeed,
+    Back,
+}
+
+impl MenuAction {
+    /// Options change with left and right.
+    pub fn is_option(self) -> bool {
+        // Options of the accessibility page are listed after its button
+        matches!(self, MenuAction::Language | MenuAction::MusicVolume | MenuAction::SoundVolume)
+            || (MenuAction::ColorScheme ..= MenuAction::GameSpeed).contains(&self)
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy)]
+#[require(Button)]
+pub struct MenuButton(pub MenuAction);
+
+/// Focused button, none when the screen has no buttons.
+#[derive(Resource, Default, Debug, PartialEq)]
+pub struct MenuFocus(pub Option<Entity>);
+
+/// How an activated option changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptionStep {
+    /// Button was clicked or pressed, options cycle through their values.
+    Cycle,
+    Decrease,
+    Increase,
+}
+
+/// The button was activated.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MenuActivated {
+    pub action: MenuAction,
+    pub step: OptionStep,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MenuInput {
+    Up,
+    Down,
+    Left,
+    Right,
+    Activate,
+    Back,
+}
+
+pub struct MenuNavigationPlugin;
+
+impl Plugin for MenuNavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<MenuFocus>()
+            .add_event::<MenuActivated>()
+            .add_systems(Update, (navigate_menus, update_button_visuals).chain());
+    }
+}
+
+/// Button `step` places after the focused one (before it for negative steps), wrapping around.
+/// The first button when nothing is focused yet.
+pub fn next_focus(buttons: &[Entity], focused: Option<Entity>, step: isize) -> Option<Entity> {
+    let Some(index) = focused.and_then(|focused| buttons.iter().position(|button| *button == focused)) else {
+        return buttons.first().copied();
+    };
+    Some(buttons[(index as isize + step).rem_euclid(buttons.len() as isize) as usize])
+}
+
+/// Root of a menu screen, with its title, texts and buttons in a centered column.
+pub fn menu_screen() -> Node {
+    Node {
+        position_type: PositionType::Absolute,
+        width: Val::Percent(100.0),
+        height: Val::Percent(100.0),
+        flex_direction: FlexDirection::Column,
+        align_items: AlignItems::Center,
+        justify_content: JustifyContent::Center,
+        row_gap: Val::Px(16.0),
+        ..default()
+    }
+}
+
+/// Menu text with the font size, scaled by the text scale of the settings.
+pub fn menu_text(text: String, font: &Handle<Font>, size: f32) -> impl Bundle {
+    (
+        Text::new(text),
+        TextFont { font: font.clone(), font_size: size, ..default() },
+        ScaledText(size),
+    )
+}
+
+/// Button with a label. `label` is added to the text, e.g. [[crate::localization::LocalizedText]].
+pub fn spawn_button(
+    builder: &mut ChildBuilder,
+    action: MenuAction,
+    font: &Handle<Font>,
+    text: String,
+    label: impl Bundle,
+) {
+    builder.spawn((
+        Node {
+            min_width: Val::Px(320.0),
+            padding: UiRect::axes(Val::Px(24.0), Val::Px(10.0)),
+            border: UiRect::all(Val::Px(3.0)),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        BorderRadius::all(Val::Px(8.0)),
+        BackgroundColor(BUTTON_COLOR),
+        BorderColor(BUTTON_BORDER_COLOR),
+        MenuButton(action),
+    )).with_children(|builder| {
+        builder.spawn((menu_text(text, font, BUTTON_TEXT_SIZE), label));
+    });
+}
+
+/// Despawns the screen with marker `T`, when leaving it.
+pub fn despawn_screen<T: Component>(
+    mut commands: Commands,
+    query: Query<Entity, With<T>>,
+) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn read_menu_input(keys: &ButtonInput<KeyCode>, gamepads: &Query<&Gamepad>, stick_input: &mut Option<MenuInput>) -> Vec<MenuInput> {
+    let bindings = [
+        (MenuInput::Up, [KeyCode::ArrowUp, KeyCode::KeyW], GamepadButton::DPadUp),
+        (MenuInput::Down, [KeyCode::ArrowDown, KeyCode::KeyS], GamepadButton::DPadDown),
+        (MenuInput::Left, [KeyCode::ArrowLeft, KeyCode::KeyA], GamepadButton::DPadLeft),
+        (MenuInput::Right, [KeyCode::ArrowRight, KeyCode::KeyD], GamepadButton::DPadRight),
+        (MenuInput::Activate, [KeyCode::Enter, KeyCode::Space], GamepadButton::South),
+        (MenuInput::Back, [KeyCode::Escape, KeyCode::Backspace], GamepadButton::East),
+    ];
+    let mut inputs: Vec<MenuInput> = bindings.into_iter()
+        .filter(|(_, keys_of_input, button)| {
+            keys.any_just_pressed(*keys_of_input) || gamepads.iter().any(|gamepad| gamepad.just_pressed(*button))
+        })
+        .map(|(input, _, _)| input)
+        .collect();
+
+    // The stick works as a D-pad, moving once each time it's tilted
+    let stick = gamepads.iter().map(Gamepad::left_stick).find(|stick| stick.length() > STICK_THRESHOLD);
+    let tilted = stick.map(|stick| match stick.x.abs() > stick.y.abs() {
+        true if stick.x > 0.0 => MenuInput::Right,
+        true => MenuInput::Left,
+        false if stick.y > 0.0 => MenuInput::Up,
+        false => MenuInput::Down,
+    });
+    if tilted != *stick_input {
+        inputs.extend(tilted);
+        *stick_input = tilted;
+    }
+    inputs
+}
+
+fn navigate_menus(
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut stick_input: Local<Option<MenuInput>>,
+    buttons: Query<(Entity, &MenuButton, Ref<Interaction>)>,
+    mut focus: ResMut<MenuFocus>,
+    mut activated_writer: EventWriter<MenuActivated>,
+    mut sound_writer: EventWriter<PlaySound>,
+) {
+    let inputs = read_menu_input(&keys, &gamepads, &mut stick_input);
+    let mut ordered: Vec<(Entity, MenuAction)> = buttons.iter().map(|(entity, button, _)| (entity, button.0)).collect();
+    ordered.sort_by_key(|(_, action)| *action);
+    let entities: Vec<Entity> = ordered.iter().map(|(entity, _)| *entity).collect();
+    let action_of = |entity: Entity| ordered.iter().find(|(e, _)| *e == entity).map(|(_, action)| *action);
+
+    // Focus moves to the first button of a new screen
+    if focus.0.and_then(action_of).is_none() {
+        focus.set_if_neq(MenuFocus(entities.first().copied()));
+    }
+    if focus.0.is_none() {
+        return;
+    }
+
+    let mut focus_moved = false;
+    let mut activations = Vec::new();
+    for (entity, _, interaction) in buttons.iter().filter(|(_, _, interaction)| interaction.is_changed()) {
+        match *interaction {
+            Interaction::Hovered if focus.0 != Some(entity) => {
+                focus.0 = Some(entity);
+                focus_moved = true;
+            },
+            Interaction::Pressed => {
+                focus.0 = Some(entity);
+                activations.extend(action_of(entity).map(|action| (action, OptionStep::Cycle)));
+            },
+            _ => {},
+        }
+    }
+
+    for input in inputs {
+        let focused_action = focus.0.and_then(action_of).expect("focus is on a menu button");
+        match input {
+            MenuInput::Up => {
+                focus.0 = next_focus(&entities, focus.0, -1);
+                focus_moved = true;
+            },
+            MenuInput::Down => {
+                focus.0 = next_focus(&entities, focus.0, 1);
+                focus_moved = true;
+            },
+            MenuInput::Left if focused_action.is_option() => activations.push((focused_action, OptionStep::Decrease)),
+            MenuInput::Right if focused_action.is_option() => activations.push((focused_action, OptionStep::Increase)),
+            MenuInput::Activate => activations.push((focused_action, OptionStep::Cycle)),
+            MenuInput::Back if ordered.iter().any(|(_, action)| *action == MenuAction::Back) => {
+                activations.push((MenuAction::Back, OptionStep::Cycle));
+            },
+            _ => {},
+        }
+    }
+
+    if focus_moved {
+        sound_writer.send(PlaySound { kind: SoundKind::MenuFocus, position: None });
+    }
+    for (action, step) in activations {
+        activated_writer.send(MenuActivated { action, step });
+        let kind = if action == MenuAction::Back { SoundKind::MenuBack } else { SoundKind::MenuSelect };
+        sound_writer.send(PlaySound { kind, position: None });
+    }
+}
+
+fn update_button_visuals(
+    focus: Res<MenuFocus>,
+    mut buttons: Query<(Entity, &Interaction, &mut BackgroundColor, &mut BorderColor), With<MenuButton>>,
+) {
+    for (entity, interaction, mut background, mut border) in &mut buttons {
+        let focused = focus.0 == Some(entity);
+        background.0 = match (interaction, focused) {
+            (Interaction::Pressed, _) => PRESSED_BUTTON_COLOR,
+            (_, true) => FOCUSED_BUTTON_COLOR,
+            _ => BUTTON_COLOR,
+        };
+        border.0 = if focused { FOCUSED_BUTTON_BORDER_COLOR } else { BUTTON_BORDER_COLOR };
+    }
+}
@@ -0,0 +1,220 @@
+use bevy::prelude::*;
+
+use crate::{
+    accessibility::{MAX_GAME_SPEED, MAX_TEXT_SCALE, MIN_GAME_SPEED, MIN_TEXT_SCALE},
+    localization::{FluentArgs, LanguageLibrary, Languages, Localization, LocalizedText},
+    menu::{
+        navigation::{menu_screen, menu_text, spawn_button, MenuAction, MenuActivated, OptionStep},
+        start_menu::StartScreenElement,
+    },
+    settings::{ColorScheme, FireMode, Settings},
+};
+
+/// Volumes change by tenths.
+const VOLUME_STEPS: i32 = 10;
+
+/// Text scale changes by quarters.
+const TEXT_SCALE_STEPS: i32 = ((MAX_TEXT_SCALE - MIN_TEXT_SCALE) * 4.0) as i32;
+
+/// Game speed changes by quarters.
+const GAME_SPEED_STEPS: i32 = ((MAX_GAME_SPEED - MIN_GAME_SPEED) * 4.0) as i32;
+
+const COLOR_SCHEMES: [ColorScheme; 4] = [ColorScheme::Default, ColorScheme::Deuteranopia, ColorScheme::Protanopia, ColorScheme::Tritanopia];
+
+const FIRE_MODES: [FireMode; 3] = [FireMode::Click, FireMode::Hold, FireMode::Auto];
+
+/// Text of an option button, showing its current value.
+#[derive(Component)]
+pub struct OptionLabel(pub MenuAction);
+
+const OPTIONS: [MenuAction; 3] = [MenuAction::Language, MenuAction::MusicVolume, MenuAction::SoundVolume];
+
+const ACCESSIBILITY_OPTIONS: [MenuAction; 10] = [
+    MenuAction::ColorScheme,
+    MenuAction::EnemyOutlines,
+    MenuAction::TextScale,
+    MenuAction::HighContrastHud,
+    MenuAction::FireMode,
+    MenuAction::AimAssist,
+    MenuAction::AimAssistStrength,
+    MenuAction::ScreenShake,
+    MenuAction::ReducedFlashes,
+    MenuAction::GameSpeed,
+];
+
+pub fn setup_options_screen(
+    mut commands: Commands,
+    localization: Res<Localization>,
+    settings: Res<Settings>,
+    library: Res<LanguageLibrary>,
+    languages: Res<Assets<Languages>>,
+) {
+    let font = &localization.font;
+    let language_name = language_name(&localization, languages.get(&library.0));
+    commands.spawn((menu_screen(), StartScreenElement)).with_children(|builder| {
+        builder.spawn((
+            menu_text(localization.text("options-title"), font, 48.0),
+            LocalizedText("options-title"),
+        ));
+        for action in OPTIONS {
+            let text = option_text(&localization, &settings, &language_name, action);
+            spawn_button(builder, action, font, text, OptionLabel(action));
+        }
+        spawn_button(builder, MenuAction::Accessibility, font, localization.text("options-accessibility"), LocalizedText("options-accessibility"));
+        spawn_button(builder, MenuAction::Back, font, localization.text("menu-back"), LocalizedText("menu-back"));
+    });
+}
+
+pub fn setup_accessibility_screen(
+    mut commands: Commands,
+    localization: Res<Localization>,
+    settings: Res<Settings>,
+) {
+    let font = &localization.font;
+    commands.spawn((menu_screen(), StartScreenElement)).with_children(|builder| {
+        builder.spawn((
+            menu_text(localization.text("accessibility-title"), font, 48.0),
+            LocalizedText("accessibility-title"),
+        ));
+        for action in ACCESSIBILITY_OPTIONS {
+            let text = option_text(&localization, &settings, "", action);
+            spawn_button(builder, action, font, text, OptionLabel(action));
+        }
+        spawn_button(builder, MenuAction::Back, font, localization.text("menu-back"), LocalizedText("menu-back"));
+    });
+}
+
+/// Name of the current language in itself, e.g. "Deutsch".
+fn language_name(localization: &Localization, languages: Option<&Languages>) -> String {
+    languages.and_then(|languages| languages.info(&localization.language))
+        .map_or(localization.language.clone(), |info| info.name.clone())
+}
+
+fn option_text(localization: &Localization, settings: &Settings, language_name: &str, action: MenuAction) -> String {
+    let percent = |value: f32| FluentArgs::from_iter([("percent", (value * 100.0).round() as u32)]);
+    let enabled = |enabled: bool| FluentArgs::from_iter([("enabled", enabled.to_string())]);
+    let accessibility = &settings.accessibility;
+    let (key, args) = match action {
+        MenuAction::Language => ("options-language", FluentArgs::from_iter([("language", language_name)])),
+        MenuAction::MusicVolume => ("options-music", percent(settings.audio.music)),
+        MenuAction::SoundVolume => ("options-sounds", percent(settings.audio.sfx)),
+        MenuAction::ColorScheme => {
+            let scheme = match accessibility.color_scheme {
+                ColorScheme::Default => "default",
+                ColorScheme::Deuteranopia => "deuteranopia",
+                ColorScheme::Protanopia => "protanopia",
+                ColorScheme::Tritanopia => "tritanopia",
+            };
+            ("accessibility-colors", FluentArgs::from_iter([("scheme", scheme)]))
+        },
+        MenuAction::EnemyOutlines => ("accessibility-enemy-outlines", enabled(accessibility.enemy_outlines)),
+        MenuAction::TextScale => ("accessibility-text-size", percent(accessibility.text_scale)),
+        MenuAction::HighContrastHud => ("accessibility-high-contrast", enabled(accessibility.high_contrast_hud)),
+        MenuAction::FireMode => {
+            let mode = match accessibility.fire_mode {
+                FireMode::Click => "click",
+                FireMode::Hold => "hold",
+                FireMode::Auto => "auto",
+            };
+            ("accessibility-fire-mode", FluentArgs::from_iter([("mode", mode)]))
+        },
+        MenuAction::AimAssist => ("accessibility-aim-assist", enabled(accessibility.aim_assist)),
+        MenuAction::AimAssistStrength => ("accessibility-aim-assist-strength", percent(accessibility.aim_assist_strength)),
+        MenuAction::ScreenShake => ("accessibility-screen-shake", percent(accessibility.screen_shake)),
+        MenuAction::ReducedFlashes => ("accessibility-reduced-flashes", enabled(accessibility.reduced_flashes)),
+        MenuAction::GameSpeed => ("accessibility-game-speed", percent(accessibility.game_speed)),
+        _ => unreachable!("{action:?} isn't an option"),
+    };
+    localization.format(key, Some(&args))
+}
+
+/// Volume after the step, cycling from full volume back to silence.
+pub fn step_volume(volume: f32, step: OptionStep) -> f32 {
+    step_value(volume, 0.0, 1.0, VOLUME_STEPS, step)
+}
+
+/// Value after the step, with the range from `min` to `max` divided into `steps`, cycling from `max` back to `min`.
+pub fn step_value(value: f32, min: f32, max: f32, steps: i32, step: OptionStep) -> f32 {
+    let current = ((value - min) / (max - min) * steps as f32).round() as i32;
+    let next = match step {
+        OptionStep::Cycle => (current + 1).rem_euclid(steps + 1),
+        OptionStep::Decrease => (current - 1).clamp(0, steps),
+        OptionStep::Increase => (current + 1).clamp(0, steps),
+    };
+    min + next as f32 / steps as f32 * (max - min)
+}
+
+/// Choice before or after the current one, wrapping around.
+pub fn step_choice<T: Copy + PartialEq>(choices: &[T], current: T, step: OptionStep) -> T {
+    let index = choices.iter().position(|choice| *choice == current).unwrap_or(0);
+    let offset = if step == OptionStep::Decrease { choices.len() - 1 } else { 1 };
+    choices[(index + offset) % choices.len()]
+}
+
+/// Language before or after the current one, wrapping around.
+pub fn step_language(ids: &[String], current: &str, step: OptionStep) -> Option<String> {
+    let offset = if step == OptionStep::Decrease { ids.len().checked_sub(1)? } else { 1 };
+    let next = ids.iter().position(|id| id == current).map_or(0, |i| (i + offset) % ids.len());
+    ids.get(next).cloned()
+}
+
+pub fn handle_options(
+    mut activated: EventReader<MenuActivated>,
+    library: Res<LanguageLibrary>,
+    languages: Res<Assets<Languages>>,
+    localization: Res<Localization>,
+    mut settings: ResMut<Settings>,
+) {
+    for event in activated.read() {
+        match event.action {
+            MenuAction::Language => {
+                if let Some(languages) = languages.get(&library.0) {
+                    settings.language = step_language(&languages.ids(), &localization.language, event.step);
+                }
+            },
+            MenuAction::MusicVolume => settings.audio.music = step_volume(settings.audio.music, event.step),
+            MenuAction::SoundVolume => settings.audio.sfx = step_volume(settings.audio.sfx, event.step),
+            _ => step_accessibility_option(&mut settings, event.action, event.step),
+        }
+    }
+}
+
+fn step_accessibility_option(settings: &mut Settings, action: MenuAction, step: OptionStep) {
+    let accessibility = &mut settings.accessibility;
+    match action {
+        MenuAction::ColorScheme => accessibility.color_scheme = step_choice(&COLOR_SCHEMES, accessibility.color_scheme, step),
+        MenuAction::EnemyOutlines => accessibility.enemy_outlines = !accessibility.enemy_outlines,
+        MenuAction::TextScale => {
+            accessibility.text_scale = step_value(accessibility.text_scale, MIN_TEXT_SCALE, MAX_TEXT_SCALE, TEXT_SCALE_STEPS, step);
+        },
+        MenuAction::HighContrastHud => accessibility.high_contrast_hud = !accessibility.high_contrast_hud,
+        MenuAction::FireMode => accessibility.fire_mode = step_choice(&FIRE_MODES, accessibility.fire_mode, step),
+        MenuAction::AimAssist => accessibility.aim_assist = !accessibility.aim_assist,
+        MenuAction::AimAssistStrength => {
+            accessibility.aim_assist_strength = step_volume(accessibility.aim_assist_strength, step);
+        },
+        MenuAction::ScreenShake => accessibility.screen_shake = step_volume(accessibility.screen_shake, step),
+        MenuAction::ReducedFlashes => accessibility.reduced_flashes = !accessibility.reduced_flashes,
+        MenuAction::GameSpeed => {
+            accessibility.game_speed = step_value(accessibility.game_speed, MIN_GAME_SPEED, MAX_GAME_SPEED, GAME_SPEED_STEPS, step);
+        },
+        _ => {},
+    }
+}
+
+pub fn update_option_labels(
+    settings: Res<Settings>,
+    localization: Res<Localization>,
+    library: Res<LanguageLibrary>,
+    languages: Res<Assets<Languages>>,
+    mut label_query: Query<(&OptionLabel, &mut Text, &mut TextFont)>,
+) {
+    if !settings.is_changed() && !localization.is_changed() {
+        return;
+    }
+    let language_name = language_name(&localization, languages.get(&library.0));
+    for (label, mut text, mut font) in &mut label_query {
+        text.0 = option_text(&localization, &settings, &language_name, label.0);
+        font.font = localization.font.clone();
+    }
+}
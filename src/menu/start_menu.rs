@@ -1,36 +1,125 @@
 use bevy::prelude::*;
 
-use crate::GameState;
+use crate::{
+    high_scores::HighScores,
+    localization::{FluentArgs, Localization, LocalizedText},
+    menu::navigation::{menu_screen, menu_text, spawn_button, MenuAction, MenuActivated, MENU_TEXT_SIZE},
+    GameState,
+};
 
+/// Page of the main menu.
+#[derive(SubStates, Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[source(GameState = GameState::MainMenu)]
+pub enum MenuPage {
+    #[default]
+    Main,
+    Options,
+    Accessibility,
+    HighScores,
+    Credits,
+}
+
+/// Root of the current main menu page, despawned when leaving it.
 #[derive(Component)]
 pub struct StartScreenElement;
 
 pub fn setup_start_screen(
     mut commands: Commands,
+    localization: Res<Localization>,
 ) {
-    commands.spawn((
-        Text::new("Use WASD to move and Mouse to shoot.\nUse mouse wheel (on desktop) or Z ans X for camera zoom.\nPress Enter to start."),
-        TextLayout::new_with_justify(JustifyText::Center),
-        Node {
-            position_type: PositionType::Absolute,
-            top: Val::Percent(50.0),
-            width: Val::Vw(100.0),
-            justify_content: JustifyContent::Center,
-            ..default()
-        },
-        StartScreenElement
-    ));
+    let font = &localization.font;
+    commands.spawn((menu_screen(), StartScreenElement)).with_children(|builder| {
+        builder.spawn((
+            menu_text(localization.text("menu-title"), font, 56.0),
+            LocalizedText("menu-title"),
+        ));
+        builder.spawn((
+            menu_text(localization.text("start-instructions"), font, MENU_TEXT_SIZE),
+            TextLayout::new_with_justify(JustifyText::Center),
+            LocalizedText("start-instructions"),
+        ));
+        spawn_button(builder, MenuAction::Play, font, localization.text("menu-play"), LocalizedText("menu-play"));
+        spawn_button(builder, MenuAction::Options, font, localization.text("menu-options"), LocalizedText("menu-options"));
+        spawn_button(builder, MenuAction::HighScores, font, localization.text("menu-high-scores"), LocalizedText("menu-high-scores"));
+        spawn_button(builder, MenuAction::Credits, font, localization.text("menu-credits"), LocalizedText("menu-credits"));
+        // Browsers don't let pages close themselves
+        if !cfg!(target_arch = "wasm32") {
+            spawn_button(builder, MenuAction::Quit, font, localization.text("menu-quit"), LocalizedText("menu-quit"));
+        }
+    });
 }
 
-pub fn handle_start_game(
+pub fn setup_high_scores_screen(
     mut commands: Commands,
-    keys: Res<ButtonInput<KeyCode>>,
-    query: Query<Entity, With<StartScreenElement>>,
+    localization: Res<Localization>,
+    high_scores: Res<HighScores>,
 ) {
-    if keys.just_pressed(KeyCode::Enter) {
-        for entity in &query {
-            commands.entity(entity).despawn();
+    let font = &localization.font;
+    commands.spawn((menu_screen(), StartScreenElement)).with_children(|builder| {
+        builder.spawn((
+            menu_text(localization.text("high-scores-title"), font, 48.0),
+            LocalizedText("high-scores-title"),
+        ));
+        builder.spawn((
+            menu_text(high_scores_text(&localization, &high_scores), font, MENU_TEXT_SIZE),
+            TextLayout::new_with_justify(JustifyText::Center),
+        ));
+        spawn_button(builder, MenuAction::Back, font, localization.text("menu-back"), LocalizedText("menu-back"));
+    });
+}
+
+fn high_scores_text(localization: &Localization, high_scores: &HighScores) -> String {
+    if high_scores.0.is_empty() {
+        return localization.text("high-scores-empty");
+    }
+    high_scores.0.iter().enumerate()
+        .map(|(place, high_score)| {
+            let args = FluentArgs::from_iter([("place", place as u32 + 1), ("score", high_score.score), ("kills", high_score.kills)]);
+            localization.format("high-scores-entry", Some(&args))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn setup_credits_screen(
+    mut commands: Commands,
+    localization: Res<Localization>,
+) {
+    let font = &localization.font;
+    commands.spawn((menu_screen(), StartScreenElement)).with_children(|builder| {
+        builder.spawn((
+            menu_text(localization.text("credits-title"), font, 48.0),
+            LocalizedText("credits-title"),
+        ));
+        builder.spawn((
+            menu_text(localization.text("credits-text"), font, MENU_TEXT_SIZE),
+            TextLayout::new_with_justify(JustifyText::Center),
+            LocalizedText("credits-text"),
+        ));
+        spawn_button(builder, MenuAction::Back, font, localization.text("menu-back"), LocalizedText("menu-back"));
+    });
+}
+
+/// Buttons of the main menu pages, except options of the options page.
+pub fn handle_main_menu(
+    mut commands: Commands,
+    page: Res<State<MenuPage>>,
+    mut activated: EventReader<MenuActivated>,
+    mut exit_writer: EventWriter<AppExit>,
+) {
+    for event in activated.read() {
+        match event.action {
+            MenuAction::Play => commands.set_state(GameState::InGame),
+            MenuAction::Options => commands.set_state(MenuPage::Options),
+            MenuAction::HighScores => commands.set_state(MenuPage::HighScores),
+            MenuAction::Accessibility => commands.set_state(MenuPage::Accessibility),
+            MenuAction::Credits => commands.set_state(MenuPage::Credits),
+            MenuAction::Back if *page.get() == MenuPage::Accessibility => commands.set_state(MenuPage::Options),
+            MenuAction::Back => commands.set_state(MenuPage::Main),
+            MenuAction::Quit => {
+                exit_writer.send(AppExit::Success);
+            },
+            _ => {},
         }
-        commands.set_state(GameState::InGame);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,221 @@
+//! Arrows at the screen edges pointing at enemies outside of the view.
+
+use std::f32::consts::{PI, TAU};
+
+use bevy::prelude::*;
+
+use crate::{
+    accessibility::Palette,
+    coords::calc_world_viewport_coord,
+    gameplay::{enemy::{EnemyKind, EnemyState}, player::PlayerInfo, LevelComponents},
+    GameState,
+};
+
+/// Directions (angle sectors around the screen center), in which threats are grouped into one indicator.
+pub const THREAT_SECTORS: usize = 16;
+
+/// Distance of indicators from the screen edge, in logical pixels.
+const EDGE_INSET: f32 = 28.0;
+
+const INDICATOR_BOX: f32 = 56.0;
+
+const FONT_INDICATOR: &str = "fonts/DejaVuSans-Bold.ttf";
+
+/// Indicators of threats this far from the player (in world units) or further have the smallest size.
+const FAR_DISTANCE: f32 = 400.0;
+const NEAR_FONT_SIZE: f32 = 36.0;
+const FAR_FONT_SIZE: f32 = 16.0;
+
+/// How an off-screen enemy is shown. More dangerous icons win when threats are grouped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ThreatIcon {
+    Regular,
+    Ranged,
+    Boss,
+}
+
+impl ThreatIcon {
+    pub fn of_kind(kind: EnemyKind) -> Self {
+        match kind {
+            EnemyKind::Slime => ThreatIcon::Regular,
+        }
+    }
+
+    fn color(self, palette: &Palette) -> Color {
+        match self {
+            ThreatIcon::Regular => palette.threat_regular,
+            ThreatIcon::Ranged => palette.threat_ranged,
+            ThreatIcon::Boss => palette.threat_boss,
+        }
+    }
+
+    /// Shown after the arrow.
+    fn badge(self) -> &'static str {
+        match self {
+            ThreatIcon::Regular => "",
+            ThreatIcon::Ranged => "◎",
+            ThreatIcon::Boss => "☠",
+        }
+    }
+}
+
+/// Off-screen enemy.
+#[derive(Debug, Clone, Copy)]
+pub struct Threat {
+    /// Viewport position, outside of the viewport.
+    pub position: Vec2,
+    /// World distance from the player.
+    pub distance: f32,
+    pub icon: ThreatIcon,
+}
+
+/// Threats in the same direction, shown by a single indicator.
+#[derive(Debug, Clone, Copy)]
+pub struct ThreatGroup {
+    /// Nearest of the threats.
+    pub nearest: Threat,
+    pub icon: ThreatIcon,
+    pub count: usize,
+}
+
+/// Position on the screen edge (inset by `inset`) in the direction of the off-screen point,
+/// or none when the point is visible.
+pub fn edge_position(viewport_size: Vec2, point: Vec2, inset: f32) -> Option<Vec2> {
+    let visible = Rect::from_corners(Vec2::ZERO, viewport_size);
+    if visible.contains(point) {
+        return None;
+    }
+    let center = viewport_size / 2.0;
+    let half = (center - Vec2::splat(inset)).max(Vec2::ZERO);
+    let direction = point - center;
+    // Scale the direction so it touches the nearest edge of the inset rectangle
+    let scale_x = if direction.x != 0.0 { half.x / direction.x.abs() } else { f32::INFINITY };
+    let scale_y = if direction.y != 0.0 { half.y / direction.y.abs() } else { f32::INFINITY };
+    Some(center + direction * scale_x.min(scale_y))
+}
+
+/// Sector of the direction from the screen center, see [[THREAT_SECTORS]].
+pub fn threat_sector(viewport_size: Vec2, point: Vec2) -> usize {
+    let direction = point - viewport_size / 2.0;
+    let angle = direction.y.atan2(direction.x).rem_euclid(TAU);
+    ((angle / TAU * THREAT_SECTORS as f32).round() as usize) % THREAT_SECTORS
+}
+
+/// Groups threats by their direction from the screen center.
+pub fn group_threats(viewport_size: Vec2, threats: impl IntoIterator<Item = Threat>) -> Vec<ThreatGroup> {
+    let mut groups: [Option<ThreatGroup>; THREAT_SECTORS] = [None; THREAT_SECTORS];
+    for threat in threats {
+        let group = &mut groups[threat_sector(viewport_size, threat.position)];
+        match group {
+            None => *group = Some(ThreatGroup { nearest: threat, icon: threat.icon, count: 1 }),
+            Some(group) => {
+                group.count += 1;
+                group.icon = group.icon.max(threat.icon);
+                if threat.distance < group.nearest.distance {
+                    group.nearest = threat;
+                }
+            },
+        }
+    }
+    groups.into_iter().flatten().collect()
+}
+
+/// Arrow pointing in the viewport direction (y pointing down).
+pub fn arrow_glyph(direction: Vec2) -> char {
+    const ARROWS: [char; 8] = ['→', '↘', '↓', '↙', '←', '↖', '↑', '↗'];
+    let angle = direction.y.atan2(direction.x).rem_euclid(TAU);
+    ARROWS[((angle / (PI / 4.0)).round() as usize) % ARROWS.len()]
+}
+
+/// One of the indicators, reused for different groups every frame.
+#[derive(Component)]
+pub struct ThreatIndicator {
+    label: Entity,
+}
+
+pub struct ThreatIndicatorsPlugin;
+
+impl Plugin for ThreatIndicatorsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(OnEnter(GameState::InGame), setup_threat_indicators)
+            .add_systems(PostUpdate, update_threat_indicators
+                .after(TransformSystem::TransformPropagate)
+                .before(bevy::ui::UiSystem::Layout)
+                .run_if(in_state(GameState::InGame)));
+    }
+}
+
+fn setup_threat_indicators(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    let font = asset_server.load(FONT_INDICATOR);
+    for _ in 0 .. THREAT_SECTORS {
+        let label = commands.spawn((
+            Text::new(""),
+            TextFont { font: font.clone(), font_size: NEAR_FONT_SIZE, ..default() },
+            TextLayout::new_with_justify(JustifyText::Center),
+        )).id();
+        commands.spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Px(INDICATOR_BOX),
+                height: Val::Px(INDICATOR_BOX),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            Visibility::Hidden,
+            ThreatIndicator { label },
+            LevelComponents,
+        )).add_child(label);
+    }
+}
+
+fn update_threat_indicators(
+    camera: Single<(&Camera, &GlobalTransform)>,
+    player_transform: Single<&Transform, With<PlayerInfo>>,
+    palette: Res<Palette>,
+    enemy_query: Query<(&Transform, &EnemyKind, &EnemyState)>,
+    mut indicator_query: Query<(&ThreatIndicator, &mut Node, &mut Visibility)>,
+    mut label_query: Query<(&mut Text, &mut TextFont, &mut TextColor)>,
+) {
+    let (camera, camera_transform) = *camera;
+    let Some(viewport_size) = camera.logical_viewport_size() else {
+        return;
+    };
+    let player_position = player_transform.translation.xy();
+
+    // Dying enemies are no threat anymore
+    let alive = enemy_query.iter().filter(|(_, _, state)| **state == EnemyState::Alive);
+    let threats = alive.filter_map(|(transform, kind, _)| {
+        let world_position = transform.translation.xy();
+        let position = calc_world_viewport_coord(world_position.extend(0.0), camera, camera_transform)?;
+        edge_position(viewport_size, position, 0.0)?;
+        Some(Threat { position, distance: world_position.distance(player_position), icon: ThreatIcon::of_kind(*kind) })
+    });
+    let mut groups = group_threats(viewport_size, threats).into_iter();
+
+    for (indicator, mut node, mut visibility) in &mut indicator_query {
+        let Some(group) = groups.next() else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        let Some(position) = edge_position(viewport_size, group.nearest.position, EDGE_INSET) else {
+            continue;
+        };
+        *visibility = Visibility::Inherited;
+        node.left = Val::Px(position.x - INDICATOR_BOX / 2.0);
+        node.top = Val::Px(position.y - INDICATOR_BOX / 2.0);
+
+        if let Ok((mut text, mut font, mut color)) = label_query.get_mut(indicator.label) {
+            let arrow = arrow_glyph(group.nearest.position - viewport_size / 2.0);
+            let count = if group.count > 1 { format!("\n×{}", group.count) } else { String::new() };
+            text.0 = format!("{arrow}{}{count}", group.icon.badge());
+            let closeness = 1.0 - (group.nearest.distance / FAR_DISTANCE).min(1.0);
+            font.font_size = FAR_FONT_SIZE + (NEAR_FONT_SIZE - FAR_FONT_SIZE) * closeness;
+            color.0 = group.icon.color(&palette);
+        }
+    }
+}
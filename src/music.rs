@@ -0,0 +1,552 @@
+//! Background music following the game state, see [[MusicLibrary]].
+//!
+//! Each [[GameState]] has a playlist of tracks, switching the state crossfades into its playlist.
+//! Tracks loop between their loop points, either forever or a number of times before the next track.
+//! Instead of a playlist, a state can have layers: stems played together, each faded in
+//! over its own range of [[CombatIntensity]].
+//! Music is lowered while important sounds play (see [[MusicDucking]]) and stops in place while [[MusicPaused]].
+//!
+//! Browsers keep audio suspended until the first user input (`webapp/index.html` resumes it then),
+//! so on the web the music waits for [[AudioUnlocked]] to not run ahead of what's heard.
+
+use std::{error::Error, fmt, path::Path, time::Duration};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext, ReadAssetBytesError},
+    audio::{AddAudioSource, Decodable, Source, Volume},
+    input::touch::Touches,
+    prelude::*,
+    utils::HashMap,
+    window::PrimaryWindow,
+};
+use serde::Deserialize;
+
+use crate::{
+    audio::{AudioBus, MusicDucking},
+    gameplay::{effects::HitStop, intensity::CombatIntensity},
+    json_asset::{parse_json, read_asset, JsonAssetError},
+    settings::Settings,
+    GameState,
+};
+
+const MUSIC_LIBRARY: &str = "music/game.music.json";
+
+const DEFAULT_CROSSFADE_SECS: f32 = 1.5;
+
+const DEFAULT_LAYER_FADE_SECS: f32 = 2.0;
+
+/// Where a track loops.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct LoopPoints {
+    /// Seconds of intro played once before the loop.
+    #[serde(default, rename = "loop_start")]
+    pub start: f32,
+    /// End of the loop in seconds, none to loop at the end of the file.
+    #[serde(default, rename = "loop_end")]
+    pub end: Option<f32>,
+    /// Times the loop is played before the next track of the playlist, none to loop forever.
+    #[serde(default)]
+    pub plays: Option<u32>,
+}
+
+impl LoopPoints {
+    /// Seconds until the next track of the playlist starts, none for a track looping forever.
+    pub fn length(&self) -> Option<f32> {
+        let end = self.end?;
+        Some(self.start + (end - self.start).max(0.0) * self.plays? as f32)
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TrackJson {
+    /// Audio file, relative to the music JSON file.
+    pub file: String,
+    #[serde(flatten)]
+    pub loop_points: LoopPoints,
+}
+
+/// Layer of music, heard more as the intensity rises.
+#[derive(Component, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct MusicLayer {
+    /// Intensity, at which the layer starts to fade in, and at which it is at full volume.
+    pub intensity: (f32, f32),
+}
+
+impl MusicLayer {
+    /// Volume of the layer at the intensity.
+    pub fn volume(&self, intensity: f32) -> f32 {
+        let (from, to) = self.intensity;
+        if to <= from {
+            return if intensity >= from { 1.0 } else { 0.0 };
+        }
+        ((intensity - from) / (to - from)).clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct LayerJson {
+    pub track: String,
+    #[serde(flatten)]
+    pub layer: MusicLayer,
+}
+
+/// Music loaded from a `*.music.json` file:
+///
+/// ```json
+/// {
+///     "crossfade": 1.5,
+///     "layer_fade": 2.0,
+///     "tracks": {
+///         "menu": { "file": "menu.wav", "loop_start": 2.0, "loop_end": 8.0, "plays": 4 },
+///         "drums": { "file": "drums.wav", "loop_end": 8.0 },
+///         "lead": { "file": "lead.wav", "loop_end": 8.0 }
+///     },
+///     "playlists": {
+///         "MainMenu": ["menu"]
+///     },
+///     "layers": {
+///         "InGame": [
+///             { "track": "drums", "intensity": [0.0, 0.1] },
+///             { "track": "lead", "intensity": [0.5, 0.8] }
+///         ]
+///     }
+/// }
+/// ```
+#[derive(Deserialize, Debug)]
+pub struct MusicJson {
+    /// Seconds of crossfade between tracks.
+    #[serde(default = "default_crossfade")]
+    pub crossfade: f32,
+    /// Seconds for a layer to fade in or out completely when the intensity changes.
+    #[serde(default = "default_layer_fade")]
+    pub layer_fade: f32,
+    pub tracks: HashMap<String, TrackJson>,
+    #[serde(default)]
+    pub playlists: HashMap<GameState, Vec<String>>,
+    #[serde(default)]
+    pub layers: HashMap<GameState, Vec<LayerJson>>,
+}
+
+fn default_crossfade() -> f32 {
+    DEFAULT_CROSSFADE_SECS
+}
+
+fn default_layer_fade() -> f32 {
+    DEFAULT_LAYER_FADE_SECS
+}
+
+impl MusicJson {
+    pub fn parse(bytes: &[u8]) -> Result<Self, MusicError> {
+        let json: MusicJson = parse_json(bytes)?;
+        let layer_tracks = json.layers.values().flatten().map(|layer| &layer.track);
+        for name in json.playlists.values().flatten().chain(layer_tracks) {
+            if !json.tracks.contains_key(name) {
+                return Err(MusicError::UnknownTrack(name.clone()));
+            }
+        }
+        for state in json.layers.keys() {
+            if json.playlists.contains_key(state) {
+                return Err(MusicError::PlaylistAndLayers(state.clone()));
+            }
+        }
+        for (name, track) in &json.tracks {
+            if track.loop_points.plays.is_some() && track.loop_points.end.is_none() {
+                return Err(MusicError::MissingLoopEnd(name.clone()));
+            }
+        }
+        Ok(json)
+    }
+}
+
+#[derive(Debug)]
+pub enum MusicError {
+    Asset(JsonAssetError),
+    Track(String, ReadAssetBytesError),
+    UnknownTrack(String),
+    MissingLoopEnd(String),
+    PlaylistAndLayers(GameState),
+}
+
+impl fmt::Display for MusicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MusicError::Asset(e) => write!(f, "can't load music: {e}"),
+            MusicError::Track(name, e) => write!(f, "can't read track {name}: {e}"),
+            MusicError::UnknownTrack(name) => write!(f, "playlist refers to unknown track {name}"),
+            MusicError::MissingLoopEnd(name) => write!(f, "track {name} has plays without loop_end"),
+            MusicError::PlaylistAndLayers(state) => write!(f, "{state:?} has both a playlist and layers"),
+        }
+    }
+}
+
+impl Error for MusicError {}
+
+impl From<JsonAssetError> for MusicError {
+    fn from(e: JsonAssetError) -> Self {
+        MusicError::Asset(e)
+    }
+}
+
+/// Audio of a track, playing the intro once and then looping between its [[LoopPoints]].
+#[derive(Asset, TypePath, Debug, Clone)]
+pub struct MusicTrack {
+    pub audio: AudioSource,
+    pub loop_points: LoopPoints,
+}
+
+impl Decodable for MusicTrack {
+    type DecoderItem = i16;
+    type Decoder = LoopingDecoder;
+
+    fn decoder(&self) -> LoopingDecoder {
+        let decoder = self.audio.decoder();
+        // Loop points are counted in samples of all channels, so the loop is sample exact
+        let (channels, sample_rate) = (decoder.channels(), decoder.sample_rate());
+        let to_sample = |secs: f32| (secs.max(0.0) * sample_rate as f32).round() as usize * channels as usize;
+        LoopingDecoder {
+            audio: self.audio.clone(),
+            loop_start: to_sample(self.loop_points.start),
+            loop_end: self.loop_points.end.map(to_sample),
+            channels,
+            sample_rate,
+            position: 0,
+            decoder,
+        }
+    }
+}
+
+/// Decoder of a [[MusicTrack]], it goes back to the loop start at the loop end (or the end of the file).
+pub struct LoopingDecoder {
+    audio: AudioSource,
+    decoder: <AudioSource as Decodable>::Decoder,
+    /// Sample index of the loop start and end.
+    loop_start: usize,
+    loop_end: Option<usize>,
+    position: usize,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl LoopingDecoder {
+    fn restart_loop(&mut self) {
+        self.decoder = self.audio.decoder();
+        if self.loop_start > 0 {
+            self.decoder.nth(self.loop_start - 1);
+        }
+        self.position = self.loop_start;
+    }
+}
+
+impl Iterator for LoopingDecoder {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        if self.loop_end.is_some_and(|end| self.position >= end && end > self.loop_start) {
+            self.restart_loop();
+        }
+        let sample = match self.decoder.next() {
+            // End of the file, unless nothing is left to loop
+            None if self.position > self.loop_start => {
+                self.restart_loop();
+                self.decoder.next()
+            },
+            sample => sample,
+        };
+        self.position += 1;
+        sample
+    }
+}
+
+impl Source for LoopingDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+/// Playlists and layers of all game states.
+#[derive(Asset, TypePath, Debug)]
+pub struct MusicLibrary {
+    pub crossfade: f32,
+    pub layer_fade: f32,
+    pub playlists: HashMap<GameState, Vec<Handle<MusicTrack>>>,
+    pub layers: HashMap<GameState, Vec<(Handle<MusicTrack>, MusicLayer)>>,
+}
+
+/// Loads `*.music.json` files as [[MusicLibrary]]s, with tracks as labeled assets `track/<name>`.
+#[derive(Default)]
+pub struct MusicLibraryLoader;
+
+impl AssetLoader for MusicLibraryLoader {
+    type Asset = MusicLibrary;
+    type Settings = ();
+    type Error = MusicError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<MusicLibrary, MusicError> {
+        let bytes = read_asset(reader).await?;
+        let json = MusicJson::parse(&bytes)?;
+
+        // Track paths are relative to the JSON file
+        let directory = load_context.path().parent().unwrap_or(Path::new("")).to_path_buf();
+        let mut tracks = HashMap::new();
+        for (name, track) in json.tracks {
+            let audio = load_context.read_asset_bytes(directory.join(&track.file)).await
+                .map_err(|e| MusicError::Track(name.clone(), e))?;
+            let track = MusicTrack { audio: AudioSource { bytes: audio.into() }, loop_points: track.loop_points };
+            tracks.insert(name.clone(), load_context.add_labeled_asset(format!("track/{name}"), track));
+        }
+        let playlists = json.playlists.into_iter()
+            .map(|(state, names)| (state, names.iter().map(|name| tracks[name].clone()).collect()))
+            .collect();
+        let layers = json.layers.into_iter()
+            .map(|(state, layers)| (state, layers.iter().map(|layer| (tracks[&layer.track].clone(), layer.layer)).collect()))
+            .collect();
+        Ok(MusicLibrary { crossfade: json.crossfade, layer_fade: json.layer_fade, playlists, layers })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["music.json"]
+    }
+}
+
+/// Volume moving towards its target at a constant speed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fade {
+    pub volume: f32,
+    pub target: f32,
+    /// Volume change per second.
+    pub speed: f32,
+}
+
+impl Fade {
+    /// Fade from silence to full volume over the duration.
+    pub fn fade_in(secs: f32) -> Self {
+        Fade { volume: 0.0, target: 1.0, speed: 1.0 / secs.max(f32::EPSILON) }
+    }
+
+    /// Starts fading to silence over the duration.
+    pub fn fade_out(&mut self, secs: f32) {
+        self.target = 0.0;
+        self.speed = 1.0 / secs.max(f32::EPSILON);
+    }
+
+    pub fn tick(&mut self, delta_secs: f32) {
+        let step = self.speed * delta_secs;
+        self.volume = if self.volume < self.target {
+            (self.volume + step).min(self.target)
+        } else {
+            (self.volume - step).max(self.target)
+        };
+    }
+
+    pub fn is_silent(&self) -> bool {
+        self.volume <= 0.0 && self.target <= 0.0
+    }
+}
+
+/// Music stops where it is while true and continues from there afterwards.
+/// Set by [[follow_game_pause]] while the game is paused or the window isn't focused.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MusicPaused(pub bool);
+
+/// Whether audio can be heard. Browsers allow audio only after the first user input.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AudioUnlocked(pub bool);
+
+impl Default for AudioUnlocked {
+    fn default() -> Self {
+        AudioUnlocked(!cfg!(target_arch = "wasm32"))
+    }
+}
+
+/// Playing track, there are more of them during a crossfade or with layers.
+#[derive(Component, Debug)]
+pub struct MusicChannel {
+    pub fade: Fade,
+    /// Seconds played.
+    pub elapsed: f32,
+    /// Seconds, at which the next track of the playlist starts, none for a track looping forever.
+    pub next_at: Option<f32>,
+}
+
+/// Which playlist is playing.
+#[derive(Resource, Default, Debug)]
+pub struct MusicPlayer {
+    library: Handle<MusicLibrary>,
+    state: Option<GameState>,
+    index: usize,
+    /// Playing track or layers, without the ones fading out.
+    channels: Vec<Entity>,
+}
+
+pub struct MusicPlugin;
+
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_audio_source::<MusicTrack>()
+            .init_asset::<MusicLibrary>()
+            .init_asset_loader::<MusicLibraryLoader>()
+            .init_resource::<MusicPlayer>()
+            .init_resource::<MusicPaused>()
+            .init_resource::<AudioUnlocked>()
+            .add_systems(Startup, load_music)
+            .add_systems(Update, (
+                unlock_audio.run_if(resource_equals(AudioUnlocked(false))),
+                follow_game_pause,
+                play_playlists,
+                follow_combat_intensity,
+                update_music_channels,
+            ).chain());
+    }
+}
+
+fn load_music(
+    asset_server: Res<AssetServer>,
+    mut player: ResMut<MusicPlayer>,
+) {
+    player.library = asset_server.load(MUSIC_LIBRARY);
+}
+
+fn unlock_audio(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    touches: Option<Res<Touches>>,
+    mut unlocked: ResMut<AudioUnlocked>,
+) {
+    let touched = touches.is_some_and(|touches| touches.any_just_pressed());
+    if keys.get_just_pressed().next().is_some() || mouse_buttons.get_just_pressed().next().is_some() || touched {
+        unlocked.0 = true;
+    }
+}
+
+/// Pauses the music while the virtual time is paused, except for the short freeze of a [[HitStop]],
+/// and while the window is in the background.
+pub fn follow_game_pause(
+    time: Res<Time<Virtual>>,
+    hit_stop: Option<Res<HitStop>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut paused: ResMut<MusicPaused>,
+) {
+    let game_paused = time.is_paused() && hit_stop.is_none_or(|hit_stop| hit_stop.remaining.is_none());
+    let unfocused = window_query.iter().any(|window| !window.focused);
+    paused.set_if_neq(MusicPaused(game_paused || unfocused));
+}
+
+/// Starts the playlist (or layers) of a new state and moves to the next track when the current one ends.
+#[allow(clippy::too_many_arguments)]
+fn play_playlists(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    libraries: Res<Assets<MusicLibrary>>,
+    tracks: Res<Assets<MusicTrack>>,
+    unlocked: Res<AudioUnlocked>,
+    paused: Res<MusicPaused>,
+    mut player: ResMut<MusicPlayer>,
+    mut channels: Query<&mut MusicChannel>,
+) {
+    let Some(library) = libraries.get(&player.library).filter(|_| unlocked.0 && !paused.0) else {
+        return;
+    };
+    let state = state.get();
+    let current = player.channels.first().and_then(|entity| channels.get(*entity).ok());
+
+    let index = if player.state.as_ref() != Some(state) {
+        0
+    } else if current.is_some_and(|c| c.next_at.is_some_and(|next_at| c.elapsed >= next_at - library.crossfade)) {
+        player.index + 1
+    } else {
+        return;
+    };
+
+    player.state = Some(state.clone());
+    for entity in player.channels.drain(..) {
+        if let Ok(mut channel) = channels.get_mut(entity) {
+            channel.fade.fade_out(library.crossfade);
+            // Layers fading out don't follow the intensity anymore
+            commands.entity(entity).remove::<MusicLayer>();
+        }
+    }
+    if let Some(layers) = library.layers.get(state) {
+        for (track, layer) in layers {
+            player.channels.push(commands.spawn((
+                AudioPlayer(track.clone()),
+                PlaybackSettings { volume: Volume::new(0.0), ..PlaybackSettings::LOOP },
+                MusicChannel { fade: Fade::fade_in(library.layer_fade), elapsed: 0.0, next_at: None },
+                *layer,
+            )).id());
+        }
+        return;
+    }
+    let Some(playlist) = library.playlists.get(state).filter(|playlist| !playlist.is_empty()) else {
+        return;
+    };
+    player.index = index % playlist.len();
+    let track = playlist[player.index].clone();
+    let next_at = tracks.get(&track).and_then(|track| track.loop_points.length());
+    player.channels.push(commands.spawn((
+        AudioPlayer(track),
+        PlaybackSettings { volume: Volume::new(0.0), ..PlaybackSettings::LOOP },
+        MusicChannel { fade: Fade::fade_in(library.crossfade), elapsed: 0.0, next_at },
+    )).id());
+}
+
+/// Fades layers in and out with the intensity, at the speed of their initial fade in.
+fn follow_combat_intensity(
+    intensity: Res<CombatIntensity>,
+    mut layers: Query<(&MusicLayer, &mut MusicChannel)>,
+) {
+    for (layer, mut channel) in &mut layers {
+        channel.fade.target = layer.volume(intensity.value);
+    }
+}
+
+/// Applies fades, ducking and the music volume, keeps channels paused with the music.
+fn update_music_channels(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    settings: Res<Settings>,
+    paused: Res<MusicPaused>,
+    mut ducking: ResMut<MusicDucking>,
+    mut channels: Query<(Entity, &mut MusicChannel, Option<&AudioSink>, Has<MusicLayer>)>,
+) {
+    let delta_secs = time.delta_secs();
+    ducking.tick(delta_secs);
+    let bus_volume = settings.audio.bus_volume(AudioBus::Music);
+
+    for (entity, mut channel, sink, is_layer) in &mut channels {
+        // The sink exists once the track is loaded and playing
+        let Some(sink) = sink else {
+            continue;
+        };
+        if paused.0 {
+            sink.pause();
+            continue;
+        }
+        sink.play();
+        channel.elapsed += delta_secs;
+        channel.fade.tick(delta_secs);
+        // Silent layers keep playing to stay in sync with the others
+        if channel.fade.is_silent() && !is_layer {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        sink.set_volume(channel.fade.volume * ducking.level * bus_volume);
+    }
+}
@@ -0,0 +1,285 @@
+//! Lightweight CPU particles for visual effects (splats, impacts, trails).
+//!
+//! Effects are defined in `*.particle.json` asset files, see [[ParticleEffect]].
+//! A [[ParticleEmitter]] emits particles of an effect from its position, in the direction of its rotation.
+//! Particles are plain sprites reused through a [[ParticlePool]], they move on their own
+//! and don't follow the emitter.
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    prelude::*,
+};
+use rand::{Rng, RngCore};
+use serde::Deserialize;
+
+use crate::{
+    gameplay::pool::{EntityPool, Pooled},
+    json_asset::{parse_json, read_asset, JsonAssetError},
+    rng::GameRng,
+};
+
+/// Particles kept for reuse.
+pub const PARTICLE_POOL_CAPACITY: usize = 512;
+
+/// How particles of an effect are emitted.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Emission {
+    /// Number of particles emitted at once.
+    Burst(u32),
+    /// Particles per second, for as long as the emitter exists.
+    Continuous(f32),
+}
+
+/// Particle effect loaded from a `*.particle.json` file:
+///
+/// ```json
+/// {
+///     "emission": { "burst": 16 },
+///     "lifetime": [0.3, 0.6],
+///     "speed": [20.0, 60.0],
+///     "spread": 360.0,
+///     "drift": [0.0, 5.0],
+///     "damping": 3.0,
+///     "colors": [[0.4, 0.9, 0.3, 1.0], [0.2, 0.6, 0.1, 0.0]],
+///     "sizes": [3.0, 1.0]
+/// }
+/// ```
+///
+/// Ranges are `[min, max]`, each particle gets a random value from them.
+/// Colors (sRGBA) and sizes are spread evenly over particle's life and blended in between.
+#[derive(Asset, TypePath, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ParticleEffect {
+    pub emission: Emission,
+    /// Seconds a particle lives.
+    pub lifetime: (f32, f32),
+    /// Initial speed in world units per second.
+    pub speed: (f32, f32),
+    /// Angle in degrees of the cone around emitter's direction, particles are emitted within.
+    pub spread: f32,
+    /// Constant acceleration, e.g. smoke rising.
+    pub drift: [f32; 2],
+    /// How quickly particles slow down, per second.
+    pub damping: f32,
+    pub colors: Vec<[f32; 4]>,
+    pub sizes: Vec<f32>,
+}
+
+impl Default for ParticleEffect {
+    fn default() -> Self {
+        ParticleEffect {
+            emission: Emission::Burst(1),
+            lifetime: (1.0, 1.0),
+            speed: (0.0, 0.0),
+            spread: 0.0,
+            drift: [0.0, 0.0],
+            damping: 0.0,
+            colors: vec![[1.0, 1.0, 1.0, 1.0]],
+            sizes: vec![1.0],
+        }
+    }
+}
+
+/// Value at the point `t` (from 0 to 1) of keys spread evenly from 0 to 1.
+fn sample_keys<T: Copy>(keys: &[T], t: f32, mix: impl Fn(T, T, f32) -> T) -> Option<T> {
+    let last = keys.len().checked_sub(1)?;
+    let position = t.clamp(0.0, 1.0) * last as f32;
+    let i = (position.floor() as usize).min(last);
+    let next = (i + 1).min(last);
+    Some(mix(keys[i], keys[next], position - i as f32))
+}
+
+impl ParticleEffect {
+    pub fn parse(bytes: &[u8]) -> Result<Self, JsonAssetError> {
+        parse_json(bytes)
+    }
+
+    /// Color at given part (from 0 to 1) of particle's life.
+    pub fn color_at(&self, t: f32) -> Color {
+        sample_keys(&self.colors, t, |a, b, s| {
+            std::array::from_fn(|i| a[i] + (b[i] - a[i]) * s)
+        }).map(|[r, g, b, a]| Color::srgba(r, g, b, a)).unwrap_or(Color::WHITE)
+    }
+
+    /// Size at given part (from 0 to 1) of particle's life.
+    pub fn size_at(&self, t: f32) -> f32 {
+        sample_keys(&self.sizes, t, |a, b, s| a + (b - a) * s).unwrap_or(1.0)
+    }
+
+    /// Random initial velocity within the cone around the direction.
+    pub fn sample_velocity(&self, direction: Vec2, rng: &mut impl Rng) -> Vec2 {
+        let half_spread = self.spread.to_radians() / 2.0;
+        let angle = if half_spread > 0.0 { rng.gen_range(-half_spread ..= half_spread) } else { 0.0 };
+        Vec2::from_angle(angle).rotate(direction.normalize_or(Vec2::X)) * sample_range(self.speed, rng)
+    }
+
+    pub fn sample_lifetime(&self, rng: &mut impl Rng) -> f32 {
+        sample_range(self.lifetime, rng)
+    }
+}
+
+fn sample_range((min, max): (f32, f32), rng: &mut impl Rng) -> f32 {
+    if max > min { rng.gen_range(min ..= max) } else { min }
+}
+
+/// Loads `*.particle.json` files as [[ParticleEffect]]s.
+#[derive(Default)]
+pub struct ParticleEffectLoader;
+
+impl AssetLoader for ParticleEffectLoader {
+    type Asset = ParticleEffect;
+    type Settings = ();
+    type Error = JsonAssetError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<ParticleEffect, JsonAssetError> {
+        let bytes = read_asset(reader).await?;
+        ParticleEffect::parse(&bytes)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["particle.json"]
+    }
+}
+
+/// Emits particles of the effect from entity's position, in the direction of its rotation (x axis).
+#[derive(Component, Debug, Clone)]
+pub struct ParticleEmitter {
+    pub effect: Handle<ParticleEffect>,
+    /// Despawn the emitter entity once a burst is emitted, for one-off effects.
+    pub despawn_after_burst: bool,
+    burst_emitted: bool,
+    /// Part of a particle carried over to the next frame of continuous emission.
+    accumulated: f32,
+}
+
+impl ParticleEmitter {
+    /// Emitter attached to an object, e.g. a trail.
+    pub fn new(effect: Handle<ParticleEffect>) -> Self {
+        ParticleEmitter { effect, despawn_after_burst: false, burst_emitted: false, accumulated: 0.0 }
+    }
+
+    /// Emitter of a single burst, it is despawned once the burst is emitted.
+    pub fn once(effect: Handle<ParticleEffect>) -> Self {
+        ParticleEmitter { despawn_after_burst: true, ..Self::new(effect) }
+    }
+
+    /// Whether the burst of the effect was emitted, continuous emission is never done.
+    pub fn burst_emitted(&self) -> bool {
+        self.burst_emitted
+    }
+
+    /// Number of particles to emit in a frame of given length.
+    pub fn emit_count(&mut self, effect: &ParticleEffect, delta_secs: f32) -> u32 {
+        match effect.emission {
+            Emission::Burst(count) => {
+                if self.burst_emitted {
+                    return 0;
+                }
+                self.burst_emitted = true;
+                count
+            },
+            Emission::Continuous(rate) => {
+                self.accumulated += rate * delta_secs;
+                let count = self.accumulated.floor();
+                self.accumulated -= count;
+                count as u32
+            },
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct Particle {
+    pub velocity: Vec2,
+    pub age: f32,
+    pub lifetime: f32,
+    pub effect: Handle<ParticleEffect>,
+}
+
+pub type ParticlePool = EntityPool<Particle>;
+
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_asset::<ParticleEffect>()
+            .init_asset_loader::<ParticleEffectLoader>()
+            .insert_resource(ParticlePool::new(PARTICLE_POOL_CAPACITY))
+            .add_systems(Update, (emit_particles, update_particles).chain());
+    }
+}
+
+pub fn emit_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    effects: Res<Assets<ParticleEffect>>,
+    mut pool: ResMut<ParticlePool>,
+    mut game_rng: Option<ResMut<GameRng>>,
+    mut emitters: Query<(Entity, &mut ParticleEmitter, &GlobalTransform), Without<Pooled>>,
+) {
+    // There is no game RNG before the first run
+    let mut thread_rng = rand::thread_rng();
+    let mut rng: &mut dyn RngCore = match game_rng.as_deref_mut() {
+        Some(game_rng) => &mut game_rng.effects,
+        None => &mut thread_rng,
+    };
+    for (entity, mut emitter, transform) in &mut emitters {
+        // Emitters wait until their effect is loaded
+        let Some(effect) = effects.get(&emitter.effect) else {
+            continue;
+        };
+        let count = emitter.emit_count(effect, time.delta_secs());
+        let (_, rotation, position) = transform.to_scale_rotation_translation();
+        let direction = (rotation * Vec3::X).xy();
+        for _ in 0 .. count {
+            pool.acquire(&mut commands, (
+                Particle {
+                    velocity: effect.sample_velocity(direction, &mut rng),
+                    age: 0.0,
+                    lifetime: effect.sample_lifetime(&mut rng),
+                    effect: emitter.effect.clone(),
+                },
+                Sprite {
+                    color: effect.color_at(0.0),
+                    custom_size: Some(Vec2::splat(effect.size_at(0.0))),
+                    ..default()
+                },
+                Transform::from_translation(position),
+            ));
+        }
+        if emitter.despawn_after_burst && emitter.burst_emitted {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+pub fn update_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    effects: Res<Assets<ParticleEffect>>,
+    mut pool: ResMut<ParticlePool>,
+    mut particles: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>,
+) {
+    let delta_secs = time.delta_secs();
+    for (entity, mut particle, mut transform, mut sprite) in &mut particles {
+        particle.age += delta_secs;
+        let Some(effect) = effects.get(&particle.effect).filter(|_| particle.age < particle.lifetime) else {
+            pool.release(&mut commands, entity);
+            continue;
+        };
+        particle.velocity += Vec2::from(effect.drift) * delta_secs;
+        particle.velocity *= (-effect.damping * delta_secs).exp();
+        transform.translation += (particle.velocity * delta_secs).extend(0.0);
+
+        let t = particle.age / particle.lifetime;
+        sprite.color = effect.color_at(t);
+        sprite.custom_size = Some(Vec2::splat(effect.size_at(t)));
+    }
+}
@@ -0,0 +1,237 @@
+use std::{io, path::{Path, PathBuf}};
+
+use bevy::prelude::*;
+
+use crate::{control::{movement_from_axes, PlayerInput}, rng::GameRng};
+
+const REPLAY_MAGIC: &[u8; 4] = b"B2DR";
+/// Bumped whenever the gameplay rules change, because the same input no longer reproduces the run.
+const REPLAY_VERSION: u8 = 2;
+
+// Each tick is stored in a single flags byte, optionally followed by aim coordinates.
+const FLAG_MOVE_X_MASK: u8 = 0b0000_0011;
+const FLAG_MOVE_Y_SHIFT: u8 = 2;
+const FLAG_FIRE: u8 = 0b0001_0000;
+const FLAG_HAS_AIM: u8 = 0b0010_0000;
+const FLAG_AIM_CHANGED: u8 = 0b0100_0000;
+
+const MIN_PLAYBACK_SPEED: f32 = 0.25;
+const MAX_PLAYBACK_SPEED: f32 = 8.0;
+
+/// Everything needed to reproduce a run: the RNG seed and the player input of each fixed tick.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Replay {
+    pub seed: u64,
+    pub frames: Vec<PlayerInput>,
+}
+
+impl Replay {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(17 + self.frames.len());
+        bytes.extend_from_slice(REPLAY_MAGIC);
+        bytes.push(REPLAY_VERSION);
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.extend_from_slice(&(self.frames.len() as u32).to_le_bytes());
+
+        let mut prev_aim = None;
+        for frame in &self.frames {
+            let mut flags = axis_to_bits(frame.movement.x) | (axis_to_bits(frame.movement.y) << FLAG_MOVE_Y_SHIFT);
+            if frame.fire {
+                flags |= FLAG_FIRE;
+            }
+            if frame.aim.is_some() {
+                flags |= FLAG_HAS_AIM;
+            }
+            let aim_changed = frame.aim.is_some() && frame.aim != prev_aim;
+            if aim_changed {
+                flags |= FLAG_AIM_CHANGED;
+            }
+            bytes.push(flags);
+            if let (true, Some(aim)) = (aim_changed, frame.aim) {
+                bytes.extend_from_slice(&aim.x.to_le_bytes());
+                bytes.extend_from_slice(&aim.y.to_le_bytes());
+            }
+            prev_aim = frame.aim;
+        }
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> io::Result<Self> {
+        let mut reader = ByteReader(bytes);
+        if reader.take(4)? != REPLAY_MAGIC {
+            return Err(invalid_data("not a replay file"));
+        }
+        let version = reader.take(1)?[0];
+        if version != REPLAY_VERSION {
+            return Err(invalid_data(&format!("unsupported replay version {version}")));
+        }
+        let seed = u64::from_le_bytes(reader.take(8)?.try_into().unwrap());
+        let frames_count = u32::from_le_bytes(reader.take(4)?.try_into().unwrap()) as usize;
+
+        // The count comes from the file, each frame takes at least a byte of it
+        let mut frames = Vec::with_capacity(frames_count.min(reader.0.len()));
+        let mut prev_aim = None;
+        for _ in 0 .. frames_count {
+            let flags = reader.take(1)?[0];
+            let aim = if flags & FLAG_HAS_AIM == 0 {
+                None
+            } else if flags & FLAG_AIM_CHANGED != 0 {
+                let x = f32::from_le_bytes(reader.take(4)?.try_into().unwrap());
+                let y = f32::from_le_bytes(reader.take(4)?.try_into().unwrap());
+                Some(Vec2::new(x, y))
+            } else {
+                prev_aim
+            };
+            frames.push(PlayerInput {
+                movement: movement_from_axes(bits_to_axis(flags), bits_to_axis(flags >> FLAG_MOVE_Y_SHIFT)),
+                aim,
+                fire: flags & FLAG_FIRE != 0,
+            });
+            prev_aim = aim;
+        }
+
+        Ok(Replay { seed, frames })
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        std::fs::write(path, self.encode())
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Self::decode(&std::fs::read(path)?)
+    }
+}
+
+fn axis_to_bits(value: f32) -> u8 {
+    if value > 0.0 {
+        1
+    } else if value < 0.0 {
+        2
+    } else {
+        0
+    }
+}
+
+fn bits_to_axis(bits: u8) -> i8 {
+    match bits & FLAG_MOVE_X_MASK {
+        1 => 1,
+        2 => -1,
+        _ => 0,
+    }
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+struct ByteReader<'a>(&'a [u8]);
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        if self.0.len() < n {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "replay file is truncated"));
+        }
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+        Ok(head)
+    }
+}
+
+/// Collects player input of the current run. Saved to file, when the run is over or the game is closed.
+#[derive(Resource, Default)]
+pub struct ReplayRecorder {
+    pub path: Option<PathBuf>,
+    pub frames: Vec<PlayerInput>,
+    /// The frames of the run are already in the file.
+    pub saved: bool,
+}
+
+/// Replay that is being played back instead of live input.
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    pub replay: Replay,
+    pub tick: usize,
+}
+
+pub fn start_replay_recording(
+    mut recorder: ResMut<ReplayRecorder>,
+    playback: Option<ResMut<ReplayPlayback>>,
+) {
+    recorder.frames.clear();
+    recorder.saved = false;
+    if let Some(mut playback) = playback {
+        playback.tick = 0;
+    }
+}
+
+pub fn record_player_input(
+    input: Res<PlayerInput>,
+    mut recorder: ResMut<ReplayRecorder>,
+) {
+    recorder.frames.push(*input);
+}
+
+pub fn save_replay_recording(
+    mut recorder: ResMut<ReplayRecorder>,
+    rng: Res<GameRng>,
+) {
+    let Some(path) = &recorder.path else {
+        return;
+    };
+    let replay = Replay {
+        seed: rng.seed,
+        frames: recorder.frames.clone(),
+    };
+    match replay.save(path) {
+        Ok(()) => info!("Replay saved to {}", path.display()),
+        Err(e) => error!("Failed to save replay to {}: {e}", path.display()),
+    }
+    recorder.saved = true;
+}
+
+/// Saves the run that is still going when the game is closed.
+pub fn save_replay_on_exit(
+    mut exit_reader: EventReader<AppExit>,
+    recorder: ResMut<ReplayRecorder>,
+    rng: Res<GameRng>,
+) {
+    if exit_reader.read().count() == 0 || recorder.saved || recorder.frames.is_empty() {
+        return;
+    }
+    save_replay_recording(recorder, rng);
+}
+
+pub fn take_replay_input(
+    mut playback: ResMut<ReplayPlayback>,
+    mut input: ResMut<PlayerInput>,
+) {
+    let tick = playback.tick;
+    *input = match playback.replay.frames.get(tick) {
+        Some(frame) => *frame,
+        None => {
+            if tick == playback.replay.frames.len() {
+                info!("Replay finished");
+            }
+            PlayerInput::default()
+        }
+    };
+    playback.tick += 1;
+}
+
+/// Speeds up or slows down replay playback with `]` and `[` keys.
+pub fn control_playback_speed(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    let speed = time.relative_speed();
+    let new_speed = if keys.just_pressed(KeyCode::BracketRight) {
+        speed * 2.0
+    } else if keys.just_pressed(KeyCode::BracketLeft) {
+        speed / 2.0
+    } else {
+        return;
+    };
+    let new_speed = new_speed.clamp(MIN_PLAYBACK_SPEED, MAX_PLAYBACK_SPEED);
+    time.set_relative_speed(new_speed);
+    info!("Replay playback speed: {new_speed}x");
+}
@@ -0,0 +1,60 @@
+use bevy::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// Seed forced for every run (e.g. via `--seed` command line argument).
+/// When not set, each run gets a fresh random seed.
+#[derive(Resource, Default)]
+pub struct SeedOverride(pub Option<u64>);
+
+/// Source of all random decisions made during a run.
+///
+/// Every kind of decision has its own stream, so e.g. playing one more sound
+/// doesn't shift the sequence of enemy spawn locations.
+/// Two runs with the same seed and the same inputs play out identically.
+#[derive(Resource)]
+pub struct GameRng {
+    pub seed: u64,
+    pub spawning: ChaCha8Rng,
+    // There are no loot drops yet,
+    // but the stream is reserved so adding them doesn't change existing runs.
+    pub loot: ChaCha8Rng,
+    pub ai: ChaCha8Rng,
+    pub audio: ChaCha8Rng,
+    pub combat: ChaCha8Rng,
+    /// Cosmetic effects, e.g. particles and decals.
+    pub effects: ChaCha8Rng,
+    /// Input of the headless bot, which doesn't exist in regular runs.
+    pub bot: ChaCha8Rng,
+}
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        GameRng {
+            seed,
+            spawning: stream(seed, 1),
+            loot: stream(seed, 2),
+            ai: stream(seed, 3),
+            audio: stream(seed, 4),
+            combat: stream(seed, 5),
+            effects: stream(seed, 6),
+            bot: stream(seed, 7),
+        }
+    }
+}
+
+fn stream(seed: u64, stream_id: u64) -> ChaCha8Rng {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    rng.set_stream(stream_id);
+    rng
+}
+
+/// Re-creates [[GameRng]] at the beginning of each run.
+pub fn reseed_game_rng(
+    mut commands: Commands,
+    seed_override: Res<SeedOverride>,
+) {
+    let seed = seed_override.0.unwrap_or_else(rand::random);
+    info!("Starting run with seed {seed}");
+    commands.insert_resource(GameRng::from_seed(seed));
+}
@@ -0,0 +1,271 @@
+//! Player preferences, kept between runs in [[SETTINGS_FILE]].
+//! On the web there are no files, so they are kept in the local storage of the browser, under the name of the file.
+
+use std::{io, path::{Path, PathBuf}};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::audio::AudioBus;
+
+/// Settings file, in the working directory (the local storage key on the web).
+pub const SETTINGS_FILE: &str = "settings.json";
+
+/// Options of the game. Missing fields of the settings file get default values,
+/// so older files keep working when new options are added.
+#[derive(Resource, Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    /// Zoom only by whole numbers of screen pixels per sprite pixel, so sprites stay crisp.
+    pub pixel_perfect_zoom: bool,
+    /// Frames per second and number of entities in the HUD.
+    pub show_fps: bool,
+    pub minimap: MinimapSettings,
+    pub effects: EffectSettings,
+    pub audio: AudioSettings,
+    pub accessibility: AccessibilitySettings,
+    /// Language identifier (e.g. `de`), none to use the system language.
+    pub language: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ScreenCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct MinimapSettings {
+    pub visible: bool,
+    /// Width and height in logical pixels.
+    pub size: f32,
+    pub corner: ScreenCorner,
+    pub opacity: f32,
+}
+
+impl Default for MinimapSettings {
+    fn default() -> Self {
+        MinimapSettings { visible: true, size: 192.0, corner: ScreenCorner::default(), opacity: 0.8 }
+    }
+}
+
+/// Hit feedback effects, each can be turned off.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct EffectSettings {
+    /// Numbers of damage dealt, floating above hit enemies.
+    pub damage_numbers: bool,
+    /// Hit enemies blink white.
+    pub hit_flash: bool,
+    /// Game freezes for a moment on kills.
+    pub hit_stop: bool,
+    pub muzzle_flash: bool,
+}
+
+impl Default for EffectSettings {
+    fn default() -> Self {
+        EffectSettings { damage_numbers: true, hit_flash: true, hit_stop: true, muzzle_flash: true }
+    }
+}
+
+/// Colors of the HUD, minimap, threat indicators and enemy outlines.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ColorScheme {
+    #[default]
+    Default,
+    /// Red-green color blindness, with weak green.
+    Deuteranopia,
+    /// Red-green color blindness, with weak red.
+    Protanopia,
+    /// Blue-yellow color blindness.
+    Tritanopia,
+}
+
+/// How holding the fire button shoots.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FireMode {
+    /// One shot per click.
+    #[default]
+    Click,
+    /// Shoots repeatedly while the button is held.
+    Hold,
+    /// Shoots repeatedly without pressing anything.
+    Auto,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct AccessibilitySettings {
+    pub color_scheme: ColorScheme,
+    /// Enemies are circled with a color of the [[ColorScheme]].
+    pub enemy_outlines: bool,
+    /// Size of menu and HUD texts, relative to their normal size.
+    pub text_scale: f32,
+    /// Opaque HUD panels with borders.
+    pub high_contrast_hud: bool,
+    pub fire_mode: FireMode,
+    /// Shots turn towards the nearest enemy within [[AccessibilitySettings::aim_assist_angle]] of the cursor.
+    pub aim_assist: bool,
+    /// Half of the aim assist cone, in degrees.
+    pub aim_assist_angle: f32,
+    /// Part of the angle between the cursor and the enemy shots turn by, from 0 to 1 (right at the enemy).
+    pub aim_assist_strength: f32,
+    /// Strength of screen shake from 0 (none) to 1.
+    pub screen_shake: f32,
+    /// Dims hit flashes, muzzle flashes and the flash of the health bar.
+    pub reduced_flashes: bool,
+    /// Speed of the game relative to the normal speed.
+    pub game_speed: f32,
+}
+
+impl Default for AccessibilitySettings {
+    fn default() -> Self {
+        AccessibilitySettings {
+            color_scheme: ColorScheme::default(),
+            enemy_outlines: false,
+            text_scale: 1.0,
+            high_contrast_hud: false,
+            fire_mode: FireMode::default(),
+            aim_assist: false,
+            aim_assist_angle: 15.0,
+            aim_assist_strength: 0.5,
+            screen_shake: 1.0,
+            reduced_flashes: false,
+            game_speed: 1.0,
+        }
+    }
+}
+
+/// Volumes from 0 to 1.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct AudioSettings {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+    pub ui: f32,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        AudioSettings { master: 1.0, music: 0.6, sfx: 1.0, ui: 1.0 }
+    }
+}
+
+impl AudioSettings {
+    /// Volume of sounds of the bus, including the master volume.
+    pub fn bus_volume(&self, bus: AudioBus) -> f32 {
+        let bus_volume = match bus {
+            AudioBus::Music => self.music,
+            AudioBus::Sfx => self.sfx,
+            AudioBus::Ui => self.ui,
+        };
+        (self.master * bus_volume).clamp(0.0, 1.0)
+    }
+}
+
+/// Settings file, with options of older versions moved to their replacements.
+#[derive(Deserialize)]
+struct SettingsFile {
+    #[serde(flatten)]
+    settings: Settings,
+    /// Replaced by no [[AccessibilitySettings::screen_shake]].
+    #[serde(default)]
+    reduced_motion: bool,
+}
+
+impl Settings {
+    pub fn parse(bytes: &[u8]) -> serde_json::Result<Self> {
+        let SettingsFile { mut settings, reduced_motion } = serde_json::from_slice(bytes)?;
+        if reduced_motion {
+            settings.accessibility.screen_shake = 0.0;
+        }
+        Ok(settings)
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let bytes = read_stored_file(path)?;
+        Settings::parse(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self).expect("Settings are always serializable");
+        write_stored_file(path, &json)
+    }
+}
+
+/// Content of a file kept between runs, like the settings or [[crate::high_scores::HighScores]].
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn read_stored_file(path: &Path) -> io::Result<Vec<u8>> {
+    std::fs::read(path)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn write_stored_file(path: &Path, json: &str) -> io::Result<()> {
+    std::fs::write(path, json)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> io::Result<web_sys::Storage> {
+    web_sys::window()
+        .and_then(|window| window.local_storage().ok().flatten())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::Unsupported, "local storage isn't available"))
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn read_stored_file(path: &Path) -> io::Result<Vec<u8>> {
+    let item = local_storage()?.get_item(&path.to_string_lossy())
+        .map_err(|_| io::Error::other("can't read the local storage"))?;
+    item.map(String::into_bytes).ok_or_else(|| io::ErrorKind::NotFound.into())
+}
+
+/// Fails when the local storage is full or disabled (e.g. in private browsing of some browsers).
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn write_stored_file(path: &Path, json: &str) -> io::Result<()> {
+    local_storage()?.set_item(&path.to_string_lossy(), json)
+        .map_err(|_| io::Error::other("can't write to the local storage"))
+}
+
+/// Where [[Settings]] are saved to when changed. Not set (e.g. in tests) means they aren't saved.
+#[derive(Resource, Default)]
+pub struct SettingsPath(pub Option<PathBuf>);
+
+/// Loads settings from [[SETTINGS_FILE]] and saves them back whenever they are changed.
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let path = PathBuf::from(SETTINGS_FILE);
+        let settings = match Settings::load(&path) {
+            Ok(settings) => settings,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Settings::default(),
+            Err(e) => {
+                warn!("Failed to load {}, using default settings: {e}", path.display());
+                Settings::default()
+            },
+        };
+        app
+            .insert_resource(settings)
+            .insert_resource(SettingsPath(Some(path)))
+            .add_systems(PostUpdate, save_settings.run_if(resource_changed::<Settings>));
+    }
+}
+
+fn save_settings(
+    settings: Res<Settings>,
+    path: Res<SettingsPath>,
+) {
+    // Insertion of the loaded settings is a change too
+    if settings.is_added() {
+        return;
+    }
+    if let Some(path) = &path.0 {
+        if let Err(e) = settings.save(path) {
+            warn!("Failed to save settings to {}: {e}", path.display());
+        }
+    }
+}
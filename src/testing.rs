@@ -0,0 +1,163 @@
+//! Helpers for testing gameplay logic without window, renderer and audio.
+//!
+//! ```ignore
+//! let mut game = TestGame::new();
+//! let enemy = game.spawn_enemy(Vec2::new(-70.0, 0.0));
+//! game.step(30);
+//! assert_eq!(game.recorded::<DeathEvent>().len(), 1);
+//! ```
+
+use bevy::prelude::*;
+
+use crate::{
+    control::{InputSource, PlayerInput},
+    gameplay::{
+        damage::{DamageEvent, DeathEvent, Health},
+        enemy::{enemy_bundle, EnemySpawnConfig, EnemySpawner, EnemyState},
+        player::{bullet_bundle, BulletCollided, PlayerInfo},
+        pool::Pooled,
+        scoring::GameScore,
+    },
+    headless::simulation_app,
+    GameState,
+};
+
+/// Seed used by [[TestGame::new]].
+pub const TEST_SEED: u64 = 0;
+
+/// Events of type `E` sent since the test game started.
+#[derive(Resource)]
+pub struct RecordedEvents<E: Event>(pub Vec<E>);
+
+/// Gameplay running in a test app. Each [[TestGame::step]] is exactly one fixed tick.
+pub struct TestGame {
+    pub app: App,
+}
+
+impl Default for TestGame {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestGame {
+    /// Starts a run with [[TEST_SEED]] where enemies are spawned only by the test.
+    pub fn new() -> Self {
+        let mut game = Self::with_seed(TEST_SEED);
+        game.set_enemy_spawning(false);
+        game
+    }
+
+    /// Starts a run with given seed and regular enemy spawning.
+    pub fn with_seed(seed: u64) -> Self {
+        let mut app = simulation_app(Some(seed));
+        app.insert_resource(InputSource::Scripted);
+        let mut game = TestGame { app };
+        game.record_events::<DamageEvent>();
+        game.record_events::<DeathEvent>();
+        game.record_events::<BulletCollided>();
+        // Enters the game state and spawns the level
+        game.app.update();
+        game
+    }
+
+    /// Starts collecting events of type `E`, so they can be checked with [[TestGame::recorded]].
+    pub fn record_events<E: Event + Clone>(&mut self) {
+        self.app.insert_resource(RecordedEvents::<E>(Vec::new()));
+        self.app.add_systems(PostUpdate, |mut events: EventReader<E>, mut recorded: ResMut<RecordedEvents<E>>| {
+            recorded.0.extend(events.read().cloned());
+        });
+    }
+
+    pub fn recorded<E: Event>(&self) -> &[E] {
+        &self.app.world().resource::<RecordedEvents<E>>().0
+    }
+
+    pub fn step(&mut self, ticks: u32) {
+        for _ in 0 .. ticks {
+            self.app.update();
+        }
+    }
+
+    /// Steps until the condition is met, but no more than `max_ticks`.
+    /// Returns whether the condition was met.
+    pub fn step_until(&mut self, max_ticks: u32, condition: impl Fn(&TestGame) -> bool) -> bool {
+        for _ in 0 .. max_ticks {
+            if condition(self) {
+                return true;
+            }
+            self.app.update();
+        }
+        condition(self)
+    }
+
+    pub fn set_enemy_spawning(&mut self, enabled: bool) {
+        let mut spawner = self.app.world_mut().resource_mut::<EnemySpawner>();
+        if enabled {
+            spawner.timer.unpause();
+        } else {
+            spawner.timer.pause();
+        }
+    }
+
+    pub fn set_input(&mut self, input: PlayerInput) {
+        *self.app.world_mut().resource_mut::<PlayerInput>() = input;
+    }
+
+    pub fn spawn_enemy(&mut self, position: Vec2) -> Entity {
+        let spawn_config = self.app.world().resource::<EnemySpawnConfig>().clone();
+        self.app.world_mut().spawn(enemy_bundle(&spawn_config, position.extend(0.0))).id()
+    }
+
+    pub fn spawn_bullet(&mut self, position: Vec2, direction: Vec2) -> Entity {
+        self.app.world_mut().spawn(bullet_bundle(position.extend(0.0), direction.normalize())).id()
+    }
+
+    pub fn player(&mut self) -> Entity {
+        self.app.world_mut().query_filtered::<Entity, With<PlayerInfo>>().single(self.app.world())
+    }
+
+    pub fn set_player_position(&mut self, position: Vec2) {
+        let player = self.player();
+        self.app.world_mut().get_mut::<Transform>(player).unwrap().translation = position.extend(0.0);
+    }
+
+    pub fn player_health(&mut self) -> u32 {
+        let player = self.player();
+        self.app.world().get::<Health>(player).unwrap().current
+    }
+
+    pub fn set_player_health(&mut self, health: u32) {
+        let player = self.player();
+        self.app.world_mut().get_mut::<Health>(player).unwrap().current = health;
+    }
+
+    pub fn position(&self, entity: Entity) -> Option<Vec2> {
+        self.app.world().get::<Transform>(entity).map(|t| t.translation.xy())
+    }
+
+    pub fn exists(&self, entity: Entity) -> bool {
+        self.app.world().get_entity(entity).is_ok()
+    }
+
+    /// Entity is in the game, i.e. it exists and isn't waiting in a pool for reuse.
+    pub fn is_active(&self, entity: Entity) -> bool {
+        self.exists(entity) && self.app.world().get::<Pooled>(entity).is_none()
+    }
+
+    pub fn enemy_state(&self, entity: Entity) -> Option<&EnemyState> {
+        self.app.world().get::<EnemyState>(entity)
+    }
+
+    pub fn enemies_count(&mut self) -> usize {
+        self.app.world_mut().query::<&EnemyState>().iter(self.app.world()).count()
+    }
+
+    pub fn score(&self) -> u32 {
+        self.app.world().resource::<GameScore>().0
+    }
+
+    pub fn state(&self) -> GameState {
+        self.app.world().resource::<State<GameState>>().get().clone()
+    }
+}
@@ -0,0 +1,83 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_2d_test::{
+    accessibility::{assisted_aim, Palette},
+    control::{repeat_fire, REPEAT_FIRE_INTERVAL},
+    settings::{ColorScheme, FireMode},
+};
+
+#[test]
+fn aim_assist_picks_the_nearest_enemy_in_the_cone() {
+    let aim = Vec2::new(100.0, 0.0);
+    let near = Vec2::new(50.0, 5.0);
+    let far = Vec2::new(200.0, -10.0);
+    let outside = Vec2::new(20.0, 20.0);
+
+    let direction = |aim: Vec2| aim.normalize();
+
+    assert!(direction(assisted_aim(Vec2::ZERO, aim, [far, near, outside], 15.0, 1.0)).abs_diff_eq(direction(near), 1e-5));
+    assert_eq!(assisted_aim(Vec2::ZERO, aim, [outside], 15.0, 1.0), aim);
+    assert!(direction(assisted_aim(Vec2::ZERO, aim, [outside], 60.0, 1.0)).abs_diff_eq(direction(outside), 1e-5));
+    assert_eq!(assisted_aim(Vec2::ZERO, aim, [], 15.0, 1.0), aim);
+}
+
+#[test]
+fn aim_assist_turns_part_way_to_the_enemy() {
+    let origin = Vec2::new(10.0, 10.0);
+    let aim = origin + Vec2::new(100.0, 0.0);
+    let enemy = origin + Vec2::new(50.0, 10.0);
+    let enemy_angle = Vec2::X.angle_to(enemy - origin);
+
+    let assisted = assisted_aim(origin, aim, [enemy], 15.0, 0.5);
+    let angle = Vec2::X.angle_to(assisted - origin);
+    assert!(0.0 < angle && angle < enemy_angle, "{angle} isn't between 0 and {enemy_angle}");
+    assert!((angle - enemy_angle * 0.5).abs() < 1e-5);
+    // The cursor's distance is kept
+    assert!((assisted.distance(origin) - 100.0).abs() < 1e-3);
+
+    assert_eq!(assisted_aim(origin, aim, [enemy], 15.0, 0.0), aim);
+}
+
+/// Shots fired in frames of 1/64 s over a second.
+fn shots(mode: FireMode, clicked_frames: &[u32], held: bool) -> u32 {
+    let mut since_shot = Duration::ZERO;
+    let delta = Duration::from_secs(1) / 64;
+    (0 .. 64).filter(|frame| repeat_fire(mode, clicked_frames.contains(frame), held, &mut since_shot, delta)).count() as u32
+}
+
+#[test]
+fn fire_modes_repeat_shots() {
+    let repeated = (Duration::from_secs(1).as_secs_f32() / REPEAT_FIRE_INTERVAL.as_secs_f32()) as u32;
+
+    // Clicks always shoot, holding the button only in the hold mode
+    assert_eq!(shots(FireMode::Click, &[0, 1, 2], true), 3);
+    assert_eq!(shots(FireMode::Hold, &[], false), 0);
+    assert_eq!(shots(FireMode::Hold, &[], true), repeated);
+    assert_eq!(shots(FireMode::Auto, &[], false), repeated);
+}
+
+#[test]
+fn color_schemes_have_distinct_threat_colors() {
+    for scheme in [ColorScheme::Default, ColorScheme::Deuteranopia, ColorScheme::Protanopia, ColorScheme::Tritanopia] {
+        let palette = Palette::new(scheme, false);
+        let colors = [palette.threat_regular, palette.threat_ranged, palette.threat_boss, palette.player];
+        for (i, a) in colors.iter().enumerate() {
+            for b in &colors[i + 1 ..] {
+                assert_ne!(a, b, "{scheme:?}");
+            }
+        }
+        assert_ne!(palette.health, palette.health_trail, "{scheme:?}");
+    }
+    assert_ne!(Palette::new(ColorScheme::Deuteranopia, false).health, Palette::default().health);
+}
+
+#[test]
+fn high_contrast_hud_has_opaque_panels_with_borders() {
+    let normal = Palette::new(ColorScheme::Protanopia, false);
+    let high_contrast = Palette::new(ColorScheme::Protanopia, true);
+    assert!(high_contrast.panel.alpha() > normal.panel.alpha());
+    assert_eq!(normal.panel_border.alpha(), 0.0);
+    assert_eq!(high_contrast.panel_border.alpha(), 1.0);
+    assert_eq!(high_contrast.health, normal.health);
+}
@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+use bevy::asset::Handle;
+use bevy_2d_test::animation::{AnimationLibrary, AnimationPlayer, PlayMode, SpriteClip};
+
+const FRAME: Duration = Duration::from_millis(100);
+
+fn library(clip: SpriteClip) -> AnimationLibrary {
+    let mut library = AnimationLibrary::default();
+    library.insert("clip", clip);
+    library.insert("idle", SpriteClip::new(20 .. 21, PlayMode::Loop));
+    library
+}
+
+fn animator(library: &AnimationLibrary) -> AnimationPlayer {
+    AnimationPlayer::new(Handle::default(), library, "clip")
+}
+
+fn shown_frames(animator: &mut AnimationPlayer, library: &AnimationLibrary, ticks: usize) -> Vec<usize> {
+    (0 .. ticks)
+        .map(|_| {
+            animator.tick(library, FRAME);
+            animator.atlas_index()
+        })
+        .collect()
+}
+
+#[test]
+fn loop_clip_starts_over() {
+    let library = library(SpriteClip::new(10 .. 13, PlayMode::Loop));
+    let mut animator = animator(&library);
+    assert_eq!(animator.atlas_index(), 10);
+    assert_eq!(shown_frames(&mut animator, &library, 4), vec![11, 12, 10, 11]);
+}
+
+#[test]
+fn ping_pong_clip_goes_back_and_forth() {
+    let library = library(SpriteClip::new(10 .. 13, PlayMode::PingPong));
+    let mut animator = animator(&library);
+    assert_eq!(shown_frames(&mut animator, &library, 6), vec![11, 12, 11, 10, 11, 12]);
+}
+
+#[test]
+fn single_frame_clip_stays_on_its_frame() {
+    let library = library(SpriteClip::new(5 .. 6, PlayMode::Loop));
+    let mut animator = animator(&library);
+    assert_eq!(shown_frames(&mut animator, &library, 3), vec![5, 5, 5]);
+}
+
+#[test]
+fn once_clip_finishes_on_last_frame() {
+    let library = library(SpriteClip::new(10 .. 13, PlayMode::Once));
+    let mut animator = animator(&library);
+    assert!(!animator.tick(&library, FRAME).finished);
+    assert!(!animator.tick(&library, FRAME).finished);
+    let tick = animator.tick(&library, FRAME);
+    assert!(tick.finished);
+    assert_eq!(tick.clip, "clip");
+    assert!(animator.is_finished());
+    assert_eq!(shown_frames(&mut animator, &library, 2), vec![12, 12]);
+}
+
+#[test]
+fn once_clip_transitions_to_next_clip() {
+    let library = library(SpriteClip::new(10 .. 12, PlayMode::Once).then("idle"));
+    let mut animator = animator(&library);
+    animator.tick(&library, FRAME);
+    animator.tick(&library, FRAME);
+    assert_eq!(library.name(animator.clip()), "idle");
+    assert_eq!(animator.atlas_index(), 20);
+}
+
+#[test]
+fn frame_events_are_sent_when_frame_is_shown() {
+    let library = library(SpriteClip::new(10 .. 14, PlayMode::Loop)
+        .with_event(0, "start")
+        .with_event(2, "hit"));
+    let mut animator = animator(&library);
+
+    let events: Vec<Vec<&str>> = (0 .. 5).map(|_| animator.tick(&library, FRAME).frame_events).collect();
+
+    // The first frame is reported on the first tick, which also moves on to the second frame
+    assert_eq!(events, vec![vec!["start"], vec!["hit"], vec![], vec!["start"], vec![]]);
+}
+
+#[test]
+fn playing_current_clip_again_does_not_restart_it() {
+    let library = library(SpriteClip::new(10 .. 13, PlayMode::Loop));
+    let mut animator = animator(&library);
+    animator.tick(&library, FRAME);
+    animator.play(&library, "clip");
+    assert_eq!(animator.atlas_index(), 11);
+}
+
+#[test]
+fn players_of_shared_library_keep_own_state() {
+    let library = library(SpriteClip::new(10 .. 13, PlayMode::Loop));
+    let mut first = animator(&library);
+    let mut second = animator(&library);
+    first.tick(&library, FRAME);
+    second.play(&library, "idle");
+
+    assert_eq!(first.atlas_index(), 11);
+    assert_eq!(second.atlas_index(), 20);
+}
+
+#[test]
+fn updated_library_keeps_clip_ids() {
+    let mut library = library(SpriteClip::new(10 .. 13, PlayMode::Loop));
+    let mut animator = animator(&library);
+    let mut reloaded = AnimationLibrary::default();
+    reloaded.insert("idle", SpriteClip::new(30 .. 31, PlayMode::Loop));
+    reloaded.insert("clip", SpriteClip::new(40 .. 43, PlayMode::Loop));
+
+    library.update_from(reloaded);
+    animator.tick(&library, FRAME);
+
+    assert_eq!(library.name(animator.clip()), "clip");
+    assert_eq!(animator.atlas_index(), 41);
+}
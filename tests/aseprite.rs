@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+use bevy_2d_test::{
+    animation::{AnimationLibrary, PlayMode},
+    aseprite::{AsepriteError, AsepriteJson},
+    gameplay::{enemy::CLIP_DYING, player::FRAME_EVENT_FOOTSTEP, GameplayAnimations},
+    headless::simulation_app,
+};
+use bevy::prelude::*;
+
+const SHEET: &str = r#"{
+    "frames": [
+        { "filename": "a 0", "frame": { "x": 0, "y": 0, "w": 16, "h": 16 }, "duration": 100 },
+        { "filename": "a 1", "frame": { "x": 16, "y": 0, "w": 16, "h": 16 }, "duration": 250 },
+        { "filename": "a 2", "frame": { "x": 0, "y": 16, "w": 16, "h": 16 }, "duration": 50 }
+    ],
+    "meta": {
+        "image": "a.png",
+        "size": { "w": 32, "h": 32 },
+        "frameTags": [
+            { "name": "walk_left", "from": 0, "to": 2, "direction": "forward" },
+            { "name": "wobble", "from": 1, "to": 2, "direction": "pingpong" },
+            { "name": "die", "from": 2, "to": 2, "direction": "forward", "repeat": "1" }
+        ]
+    }
+}"#;
+
+#[test]
+fn layout_is_built_from_frame_rects() {
+    let layout = AsepriteJson::parse(SHEET.as_bytes()).unwrap().layout();
+
+    assert_eq!(layout.size.x, 32);
+    assert_eq!(layout.textures.len(), 3);
+    assert_eq!((layout.textures[2].min.x, layout.textures[2].min.y, layout.textures[2].max.x), (0, 16, 16));
+}
+
+#[test]
+fn tags_become_clips_with_frame_durations() {
+    let clips = AsepriteJson::parse(SHEET.as_bytes()).unwrap().library();
+
+    let walk = clips.get("walk_left").unwrap();
+    assert_eq!(walk.frames, 0 .. 3);
+    assert_eq!(walk.mode, PlayMode::Loop);
+    assert_eq!(walk.duration(), Duration::from_millis(400));
+    assert_eq!(walk.frame_show_time(1), Duration::from_millis(250));
+
+    assert_eq!(clips.get("wobble").unwrap().mode, PlayMode::PingPong);
+    assert_eq!(clips.get("die").unwrap().mode, PlayMode::Once);
+}
+
+#[test]
+fn tag_outside_of_frames_is_rejected() {
+    let sheet = SHEET.replace(r#""from": 2, "to": 2"#, r#""from": 2, "to": 3"#);
+    assert!(matches!(AsepriteJson::parse(sheet.as_bytes()), Err(AsepriteError::TagOutOfRange(tag)) if tag == "die"));
+}
+
+#[test]
+fn game_sheets_have_all_gameplay_clips() {
+    let app = simulation_app(Some(1));
+    let animations = app.world().resource::<GameplayAnimations>();
+    let libraries = app.world().resource::<Assets<AnimationLibrary>>();
+    let player = libraries.get(&animations.player).unwrap();
+    let slime = libraries.get(&animations.slime).unwrap();
+
+    let walk = player.get("walk_left").unwrap();
+    assert_eq!(walk.frames, 16 .. 24);
+    assert!(walk.events.iter().any(|&(_, name)| name == FRAME_EVENT_FOOTSTEP));
+    assert_eq!(player.get("idle_bottom").unwrap().frames, 0 .. 1);
+    assert_eq!(slime.get(CLIP_DYING).unwrap().mode, PlayMode::Once);
+}
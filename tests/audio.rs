@@ -0,0 +1,74 @@
+use bevy::prelude::*;
+use bevy_2d_test::{
+    audio::{AudioBus, SoundBankError, SoundBankJson, SoundKind, SpatialRange, VoiceSettings, Voices},
+    settings::AudioSettings,
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+#[test]
+fn oldest_voices_are_stolen_over_the_limit() {
+    let mut voices = Voices::default();
+    let [a, b, c, d] = [1, 2, 3, 4].map(Entity::from_raw);
+
+    assert!(voices.start(SoundKind::SlimeDeath, a, 2).is_empty());
+    assert!(voices.start(SoundKind::SlimeDeath, b, 2).is_empty());
+    assert_eq!(voices.start(SoundKind::SlimeDeath, c, 2), vec![a]);
+
+    // A finished sound frees its voice
+    voices.finished(b);
+    assert!(voices.start(SoundKind::SlimeDeath, d, 2).is_empty());
+    assert_eq!(voices.count(SoundKind::SlimeDeath), 2);
+}
+
+#[test]
+fn sounds_fade_with_distance_and_pan_to_their_side() {
+    let range = SpatialRange { full_volume_distance: 100.0, silent_distance: 300.0, pan_distance: 200.0 };
+
+    assert_eq!(range.attenuation(50.0), 1.0);
+    assert_eq!(range.attenuation(200.0), 0.5);
+    assert_eq!(range.attenuation(1000.0), 0.0);
+
+    assert_eq!(range.pan(Vec2::new(100.0, 500.0)), 0.5);
+    assert_eq!(range.pan(Vec2::new(-400.0, 0.0)), -1.0);
+}
+
+#[test]
+fn volume_and_pitch_vary_within_limits() {
+    let voice = VoiceSettings { volume: 0.5, volume_variation: 0.2, pitch_variation: 0.1, ..Default::default() };
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+
+    let samples: Vec<(f32, f32)> = (0 .. 100).map(|_| voice.sample(&mut rng)).collect();
+
+    assert!(samples.iter().all(|(volume, _)| (0.4 ..= 0.6).contains(volume)));
+    assert!(samples.iter().all(|(_, speed)| (0.9 ..= 1.1).contains(speed)));
+    assert!(samples.windows(2).any(|s| s[0] != s[1]));
+}
+
+#[test]
+fn bus_volume_includes_master_volume() {
+    let settings = AudioSettings { master: 0.5, music: 0.4, sfx: 1.0, ui: 0.0 };
+
+    assert_eq!(settings.bus_volume(AudioBus::Music), 0.2);
+    assert_eq!(settings.bus_volume(AudioBus::Sfx), 0.5);
+    assert_eq!(settings.bus_volume(AudioBus::Ui), 0.0);
+}
+
+#[test]
+fn shipped_sound_bank_has_sounds_for_every_kind() {
+    let json = SoundBankJson::parse(&std::fs::read("assets/sounds/game.sounds.json").unwrap()).unwrap();
+
+    for kind in SoundKind::ALL {
+        let pool = json.0.get(&kind).unwrap_or_else(|| panic!("{kind:?} has no sounds"));
+        for file in &pool.files {
+            assert!(std::path::Path::new("assets/sounds").join(file).exists(), "{file} is missing");
+        }
+    }
+    assert!(json.0[&SoundKind::PlayerHurt].voice.ducks_music);
+}
+
+#[test]
+fn sound_pools_without_files_are_rejected() {
+    let json = br#"{ "footstep": { "files": [], "volume": 0.5 } }"#;
+    assert!(matches!(SoundBankJson::parse(json), Err(SoundBankError::EmptyPool(SoundKind::Footstep))));
+}
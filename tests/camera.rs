@@ -0,0 +1,94 @@
+use bevy::prelude::*;
+use bevy_2d_test::camera::{clamp_to_bounds, CameraRig, CameraShake, CameraZoom, ZOOM_STEP};
+
+const BOUNDS: Rect = Rect { min: Vec2::new(-100.0, -100.0), max: Vec2::new(100.0, 100.0) };
+
+fn rig_at(position: Vec2) -> CameraRig {
+    CameraRig { position, snap: false, ..default() }
+}
+
+#[test]
+fn camera_does_not_move_while_target_is_in_dead_zone() {
+    let mut rig = rig_at(Vec2::ZERO);
+    rig.follow(rig.dead_zone * 0.9, 0.1);
+    assert_eq!(rig.position, Vec2::ZERO);
+
+    rig.follow(Vec2::new(100.0, 0.0), 0.1);
+    assert!(rig.position.x > 0.0 && rig.position.x < 100.0 - rig.dead_zone.x);
+    assert_eq!(rig.position.y, 0.0);
+}
+
+#[test]
+fn camera_looks_ahead_towards_aim_up_to_the_limit() {
+    let rig = rig_at(Vec2::ZERO);
+    assert_eq!(rig.target(Vec2::ONE, None), Vec2::ONE);
+    assert_eq!(rig.target(Vec2::ZERO, Some(Vec2::new(40.0, 0.0))), Vec2::new(40.0 * rig.look_ahead, 0.0));
+    assert_eq!(rig.target(Vec2::ZERO, Some(Vec2::new(0.0, -1000.0))), Vec2::new(0.0, -rig.max_look_ahead));
+}
+
+#[test]
+fn following_keeps_the_zoom_offset() {
+    let mut rig = rig_at(Vec2::ZERO);
+    rig.zoom_offset = Vec2::new(30.0, -20.0);
+    for _ in 0 .. 100 {
+        rig.follow(Vec2::new(50.0, 0.0), 0.1);
+    }
+    assert!(rig.position.distance(Vec2::new(50.0, 0.0)) <= rig.dead_zone.length());
+    assert_eq!(rig.zoom_offset, Vec2::new(30.0, -20.0));
+    assert_eq!(rig.view_position(), rig.position + Vec2::new(30.0, -20.0));
+}
+
+#[test]
+fn camera_view_stays_inside_bounds() {
+    assert_eq!(clamp_to_bounds(Vec2::new(90.0, -95.0), Vec2::new(20.0, 10.0), BOUNDS), Vec2::new(80.0, -90.0));
+    assert_eq!(clamp_to_bounds(Vec2::new(10.0, 10.0), Vec2::new(20.0, 10.0), BOUNDS), Vec2::new(10.0, 10.0));
+    // View wider than the bounds is centered
+    assert_eq!(clamp_to_bounds(Vec2::new(50.0, 0.0), Vec2::new(150.0, 10.0), BOUNDS), Vec2::ZERO);
+}
+
+#[test]
+fn shake_fades_with_trauma() {
+    let mut shake = CameraShake::default();
+    assert_eq!(shake.offset(), Vec2::ZERO);
+
+    shake.add_trauma(2.0);
+    assert_eq!(shake.trauma, 1.0);
+    shake.tick(0.05);
+    assert!(shake.offset().length() > 0.0);
+    assert!(shake.offset().length() <= shake.max_offset * 2.0_f32.sqrt());
+
+    shake.tick(1.0);
+    assert_eq!(shake.offset(), Vec2::ZERO);
+}
+
+#[test]
+fn zoom_moves_smoothly_to_bounded_target() {
+    let mut zoom = CameraZoom { zoom: 5.0, target: 5.0, min: 2.0, max: 10.0, ..default() };
+    zoom.scroll(1.0, false);
+    assert_eq!(zoom.target, 5.0 * ZOOM_STEP);
+
+    zoom.tick(0.016, false);
+    assert!(zoom.zoom > 5.0 && zoom.zoom < zoom.target);
+    zoom.tick(2.0, false);
+    assert_eq!(zoom.zoom, zoom.target);
+
+    zoom.scroll(100.0, false);
+    assert_eq!(zoom.target, 10.0);
+    zoom.scroll(-100.0, false);
+    assert_eq!(zoom.target, 2.0);
+}
+
+#[test]
+fn pixel_perfect_zoom_stops_at_whole_zoom() {
+    let mut zoom = CameraZoom { zoom: 5.0, target: 5.0, min: 2.5, max: 10.0, ..default() };
+    // Trackpad scrolls by fractions of a line
+    zoom.scroll(0.3, true);
+    assert_eq!(zoom.effective_target(true), 5.0);
+    zoom.scroll(0.3, true);
+    assert_eq!(zoom.effective_target(true), 6.0);
+    zoom.tick(2.0, true);
+    assert_eq!(zoom.zoom, 6.0);
+
+    zoom.scroll(-100.0, true);
+    assert_eq!(zoom.effective_target(true), 3.0);
+}
@@ -0,0 +1,65 @@
+use bevy::prelude::*;
+use bevy_2d_test::{
+    animation::{AnimationLibrary, AnimationPlayer, PlayMode, SpriteClip},
+    direction::{DirectionSet, Facing},
+};
+
+fn name_of(set: DirectionSet, x: f32, y: f32) -> &'static str {
+    Facing::of_vector(set, Vec2::new(x, y)).name()
+}
+
+#[test]
+fn vectors_are_quantized_into_equal_sectors() {
+    assert_eq!(name_of(DirectionSet::Four, 0.0, -1.0), "bottom");
+    assert_eq!(name_of(DirectionSet::Four, -1.0, 0.2), "left");
+    assert_eq!(name_of(DirectionSet::Four, 0.9, 1.0), "top");
+
+    assert_eq!(name_of(DirectionSet::Eight, 1.0, 1.0), "top_right");
+    assert_eq!(name_of(DirectionSet::Eight, -1.0, -1.0), "bottom_left");
+    assert_eq!(name_of(DirectionSet::Eight, 1.0, -0.1), "right");
+
+    assert_eq!(name_of(DirectionSet::Sixteen, -1.0, 0.4), "left_top_left");
+    assert_eq!(name_of(DirectionSet::Sixteen, 0.2, -1.0), "bottom_bottom_right");
+}
+
+#[test]
+fn mirrored_facing_swaps_left_and_right() {
+    let mirrored = |set, x, y| Facing::of_vector(set, Vec2::new(x, y)).mirrored().name();
+    assert_eq!(mirrored(DirectionSet::Four, 1.0, 0.0), "left");
+    assert_eq!(mirrored(DirectionSet::Eight, -1.0, 1.0), "top_right");
+    assert_eq!(mirrored(DirectionSet::Sixteen, 0.2, -1.0), "bottom_bottom_left");
+    assert_eq!(mirrored(DirectionSet::Eight, 0.0, 1.0), "top");
+}
+
+#[test]
+fn facing_does_not_flicker_on_sector_boundary() {
+    // Right and top_right sectors meet at 22.5 degrees
+    let boundary = 22.5_f32.to_radians();
+    let just_below = Vec2::from_angle(boundary - 0.05);
+    let just_above = Vec2::from_angle(boundary + 0.05);
+
+    let mut facing = Facing::of_vector(DirectionSet::Eight, just_below);
+    assert_eq!(facing.name(), "right");
+    facing.turn_towards(just_above);
+    assert_eq!(facing.name(), "right");
+    facing.turn_towards(Vec2::from_angle(boundary + 0.2));
+    assert_eq!(facing.name(), "top_right");
+    facing.turn_towards(just_below);
+    assert_eq!(facing.name(), "top_right");
+}
+
+#[test]
+fn missing_left_clip_is_mirrored_right_clip() {
+    let mut library = AnimationLibrary::default();
+    library.insert("walk_right", SpriteClip::new(0 .. 4, PlayMode::Loop));
+    library.insert("walk_bottom", SpriteClip::new(4 .. 8, PlayMode::Loop));
+
+    let facing = Facing::of_vector(DirectionSet::Four, Vec2::NEG_X);
+    let mut animator = AnimationPlayer::facing(Handle::default(), &library, "walk", &facing);
+    assert_eq!(library.name(animator.clip()), "walk_right");
+    assert!(animator.flip_x());
+
+    animator.play_facing(&library, "walk", &Facing::new(DirectionSet::Four));
+    assert_eq!(library.name(animator.clip()), "walk_bottom");
+    assert!(!animator.flip_x());
+}
@@ -0,0 +1,224 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::CollisionEvent;
+use bevy_2d_test::{
+    control::PlayerInput,
+    gameplay::{
+        damage::{DamageEvent, DamageRules, DeathEvent, Faction, Hitbox, CRIT_MULTIPLIER},
+        enemy::{EnemyState, DAMAGE_SLIME, POINTS_SLIME},
+        player::{Bullet, BulletCollided, BulletHitWall, BULLET_DAMAGE, PLAYER_HEALTH},
+        FIXED_TICKS_PER_SECOND,
+    },
+    testing::TestGame,
+    GameState,
+};
+
+const ONE_SECOND: u32 = FIXED_TICKS_PER_SECOND as u32;
+
+fn is_dying(game: &TestGame, enemy: Entity) -> bool {
+    game.enemy_state(enemy) == Some(&EnemyState::Dying)
+}
+
+#[test]
+fn enemy_touching_player_damages_player_and_dies() {
+    let mut game = TestGame::new();
+    game.set_player_position(Vec2::ZERO);
+    let enemy = game.spawn_enemy(Vec2::new(30.0, 0.0));
+
+    assert!(game.step_until(ONE_SECOND, |g| is_dying(g, enemy)));
+
+    assert_eq!(game.player_health(), PLAYER_HEALTH - DAMAGE_SLIME);
+    let player = game.player();
+    let damage = game.recorded::<DamageEvent>();
+    assert_eq!(damage.len(), 1);
+    assert_eq!((damage[0].source, damage[0].target, damage[0].amount), (enemy, player, DAMAGE_SLIME));
+    assert_eq!(game.recorded::<DeathEvent>()[0].entity, enemy);
+    assert_eq!(game.score(), 0);
+}
+
+#[test]
+fn bullet_hitting_enemy_kills_it_and_increments_score() {
+    let mut game = TestGame::new();
+    game.set_player_position(Vec2::new(-300.0, 0.0));
+    let enemy = game.spawn_enemy(Vec2::new(100.0, 100.0));
+    let bullet = game.spawn_bullet(Vec2::new(60.0, 100.0), Vec2::X);
+
+    assert!(game.step_until(ONE_SECOND, |g| is_dying(g, enemy)));
+    game.step(1);
+
+    assert!(!game.is_active(bullet));
+    assert_eq!(game.recorded::<BulletCollided>()[0].0, bullet);
+    assert_eq!(game.score(), POINTS_SLIME);
+    assert_eq!(game.player_health(), PLAYER_HEALTH);
+}
+
+#[test]
+fn bullet_hitting_wall_is_destroyed_without_score() {
+    let mut game = TestGame::new();
+    let bullet = game.spawn_bullet(Vec2::new(300.0, 0.0), Vec2::X);
+
+    assert!(game.step_until(ONE_SECOND, |g| !g.is_active(bullet)));
+
+    assert_eq!(game.recorded::<BulletCollided>().len(), 1);
+    assert!(game.recorded::<DeathEvent>().is_empty());
+    assert_eq!(game.score(), 0);
+}
+
+#[test]
+fn bullet_hitting_wall_reports_impact_in_flight_direction() {
+    let mut game = TestGame::new();
+    game.record_events::<BulletHitWall>();
+    let bullet = game.spawn_bullet(Vec2::new(300.0, 0.0), Vec2::X);
+
+    assert!(game.step_until(ONE_SECOND, |g| !g.is_active(bullet)));
+
+    let impacts = game.recorded::<BulletHitWall>();
+    assert_eq!(impacts.len(), 1);
+    assert!(impacts[0].position.x > 300.0);
+    assert_eq!(impacts[0].direction, Vec2::X);
+}
+
+#[test]
+fn bullet_disappears_after_its_lifetime() {
+    let mut game = TestGame::new();
+    // Flies along the arena, too slow to reach a wall within its lifetime
+    let bullet = game.spawn_bullet(Vec2::new(-300.0, 200.0), Vec2::X);
+
+    game.step(ONE_SECOND - 2);
+    assert!(game.is_active(bullet));
+    game.step(3);
+    assert!(!game.is_active(bullet));
+    assert!(game.recorded::<BulletCollided>().is_empty());
+}
+
+#[test]
+fn dying_enemy_is_removed_after_dying_animation() {
+    let mut game = TestGame::new();
+    game.set_player_position(Vec2::ZERO);
+    let enemy = game.spawn_enemy(Vec2::new(30.0, 0.0));
+    assert!(game.step_until(ONE_SECOND, |g| is_dying(g, enemy)));
+
+    // It doesn't collide anymore while dying
+    game.step(2);
+    assert_eq!(game.recorded::<DamageEvent>().len(), 1);
+
+    assert!(game.step_until(ONE_SECOND, |g| !g.is_active(enemy)));
+}
+
+#[test]
+fn enemies_move_towards_player() {
+    let mut game = TestGame::new();
+    game.set_player_position(Vec2::new(-200.0, 0.0));
+    let enemy = game.spawn_enemy(Vec2::new(200.0, 0.0));
+
+    game.step(ONE_SECOND);
+
+    let position = game.position(enemy).unwrap();
+    assert!(position.x < 160.0, "enemy should approach the player, but it is at {position}");
+    assert!(position.y.abs() < 1.0);
+}
+
+#[test]
+fn player_death_ends_the_game() {
+    let mut game = TestGame::new();
+    game.set_player_health(DAMAGE_SLIME);
+    game.set_player_position(Vec2::ZERO);
+    game.spawn_enemy(Vec2::new(30.0, 0.0));
+
+    assert!(game.step_until(ONE_SECOND, |g| g.state() == GameState::End));
+}
+
+#[test]
+fn fire_input_shoots_bullet_towards_aim() {
+    let mut game = TestGame::new();
+    game.set_player_position(Vec2::ZERO);
+    game.set_input(PlayerInput {
+        movement: Vec2::ZERO,
+        aim: Some(Vec2::new(0.0, 100.0)),
+        fire: true,
+    });
+    game.step(1);
+    game.set_input(PlayerInput::default());
+    game.step(10);
+
+    let bullets: Vec<Vec3> = game.app.world_mut()
+        .query_filtered::<&Transform, With<Bullet>>()
+        .iter(game.app.world())
+        .map(|t| t.translation)
+        .collect();
+    assert_eq!(bullets.len(), 1);
+    assert!(bullets[0].y > 10.0 && bullets[0].x.abs() < 1.0);
+}
+
+#[test]
+fn enemies_are_spawned_the_same_way_for_the_same_seed() {
+    let spawn_positions = |seed: u64| {
+        let mut game = TestGame::with_seed(seed);
+        game.step(5 * ONE_SECOND);
+        let mut positions: Vec<(i32, i32)> = game.app.world_mut()
+            .query_filtered::<&Transform, With<EnemyState>>()
+            .iter(game.app.world())
+            .map(|t| (t.translation.x as i32, t.translation.y as i32))
+            .collect();
+        positions.sort();
+        positions
+    };
+
+    let first = spawn_positions(7);
+    assert!(first.len() >= 3);
+    assert_eq!(first, spawn_positions(7));
+}
+
+#[test]
+fn enemies_do_not_damage_each_other_by_default() {
+    let mut game = TestGame::new();
+    game.record_events::<CollisionEvent>();
+    game.set_player_position(Vec2::new(-300.0, 0.0));
+    let first = game.spawn_enemy(Vec2::new(100.0, 0.0));
+    let second = game.spawn_enemy(Vec2::new(115.0, 0.0));
+
+    game.step(10);
+
+    assert!(game.recorded::<DamageEvent>().is_empty());
+    // Touching slimes don't even report the collision
+    assert!(game.recorded::<CollisionEvent>().is_empty());
+    assert!(!is_dying(&game, first));
+    assert!(!is_dying(&game, second));
+}
+
+#[test]
+fn enemies_damage_each_other_when_friendly_fire_is_enabled() {
+    let mut game = TestGame::new();
+    game.app.world_mut().resource_mut::<DamageRules>().set_can_damage(Faction::Enemy, Faction::Enemy, true);
+    game.set_player_position(Vec2::new(-300.0, 0.0));
+    let first = game.spawn_enemy(Vec2::new(100.0, 0.0));
+    let second = game.spawn_enemy(Vec2::new(115.0, 0.0));
+
+    assert!(game.step_until(10, |g| is_dying(g, first) && is_dying(g, second)));
+    assert_eq!(game.score(), 0);
+}
+
+#[test]
+fn critical_hit_multiplies_bullet_damage() {
+    let mut game = TestGame::new();
+    game.set_player_position(Vec2::new(-300.0, 0.0));
+    let enemy = game.spawn_enemy(Vec2::new(100.0, 100.0));
+    let bullet = game.spawn_bullet(Vec2::new(60.0, 100.0), Vec2::X);
+    game.app.world_mut().get_mut::<Hitbox>(bullet).unwrap().crit_chance = 1.0;
+
+    assert!(game.step_until(ONE_SECOND, |g| is_dying(g, enemy)));
+
+    let damage = &game.recorded::<DamageEvent>()[0];
+    assert!(damage.critical);
+    assert_eq!(damage.amount, BULLET_DAMAGE * CRIT_MULTIPLIER);
+}
+
+#[test]
+fn enemy_contact_never_crits() {
+    let mut game = TestGame::new();
+    game.set_player_position(Vec2::ZERO);
+    let enemy = game.spawn_enemy(Vec2::new(30.0, 0.0));
+
+    assert!(game.step_until(ONE_SECOND, |g| is_dying(g, enemy)));
+
+    assert!(!game.recorded::<DamageEvent>()[0].critical);
+}
@@ -0,0 +1,36 @@
+use bevy_2d_test::menu::in_game_menu::{segment_fill, wave_at, HealthBar, HEALTH_PER_SEGMENT, WAVE_DURATION_SECS};
+
+#[test]
+fn health_fills_segments_in_order() {
+    let fills: Vec<f32> = (0 .. 4).map(|segment| segment_fill(25.0, segment, HEALTH_PER_SEGMENT)).collect();
+    assert_eq!(fills, [1.0, 1.0, 0.5, 0.0]);
+}
+
+#[test]
+fn lost_health_stays_as_a_shrinking_trail() {
+    let mut bar = HealthBar::new(100);
+    bar.set_health(70);
+    assert!(bar.is_animating());
+    assert_eq!(bar.flash(), 1.0);
+
+    // The trail waits a moment before shrinking
+    bar.tick(0.1);
+    assert_eq!((bar.health, bar.trail), (70.0, 100.0));
+    for _ in 0 .. 100 {
+        bar.tick(0.1);
+    }
+    assert_eq!(bar.trail, 70.0);
+    assert!(!bar.is_animating());
+
+    // Healing isn't animated
+    bar.set_health(90);
+    assert_eq!(bar.trail, 90.0);
+}
+
+#[test]
+fn waves_number_parts_of_the_run() {
+    assert_eq!(wave_at(0), 1);
+    assert_eq!(wave_at(WAVE_DURATION_SECS - 1), 1);
+    assert_eq!(wave_at(WAVE_DURATION_SECS), 2);
+    assert_eq!(wave_at(WAVE_DURATION_SECS * 3 + 5), 4);
+}
@@ -0,0 +1,45 @@
+use bevy::prelude::*;
+use bevy_2d_test::{
+    gameplay::intensity::{CombatIntensity, INTENSITY_FAR_DISTANCE, INTENSITY_FULL_ENEMIES},
+    testing::TestGame,
+};
+
+fn intensity(game: &TestGame) -> f32 {
+    game.app.world().resource::<CombatIntensity>().value
+}
+
+#[test]
+fn intensity_grows_with_enemies_damage_and_proximity() {
+    let calm = CombatIntensity::target(0, 0.0, None);
+    let far = CombatIntensity::target(1, 0.0, Some(INTENSITY_FAR_DISTANCE * 2.0));
+    let near = CombatIntensity::target(1, 0.0, Some(0.0));
+    let hurt = CombatIntensity::target(1, 0.5, Some(0.0));
+    let overwhelmed = CombatIntensity::target(INTENSITY_FULL_ENEMIES * 2, 1.0, Some(0.0));
+
+    assert_eq!(calm, 0.0);
+    assert!(calm < far && far < near && near < hurt, "{calm} {far} {near} {hurt}");
+    assert_eq!(overwhelmed, 1.0);
+}
+
+#[test]
+fn intensity_rises_quickly_and_calms_down_slowly() {
+    let mut intensity = CombatIntensity::default();
+    intensity.approach(1.0, 1.0);
+    let risen = intensity.value;
+    intensity.approach(0.0, 1.0);
+    let fallen = risen - intensity.value;
+
+    assert!(risen > 0.0 && fallen > 0.0 && fallen < risen, "rose by {risen}, fell by {fallen}");
+}
+
+#[test]
+fn nearby_enemies_raise_intensity_during_the_run() {
+    let mut game = TestGame::new();
+    game.step(10);
+    assert_eq!(intensity(&game), 0.0);
+
+    game.spawn_enemy(Vec2::new(-70.0, 0.0));
+    game.spawn_enemy(Vec2::new(70.0, 0.0));
+    game.step(64);
+    assert!(intensity(&game) > 0.0);
+}
@@ -0,0 +1,87 @@
+use std::path::Path;
+
+use regex::Regex;
+
+use bevy_2d_test::localization::{
+    message_ids, messages_path, missing_keys, negotiate_language, FluentArgs, LanguagesJson, Localization,
+    FALLBACK_LANGUAGE,
+};
+
+const LOCALES: &str = "assets/locales";
+
+fn messages(language: &str) -> String {
+    std::fs::read_to_string(Path::new(LOCALES).join(messages_path(language))).unwrap()
+}
+
+fn shipped_languages() -> Vec<String> {
+    let json = LanguagesJson::parse(&std::fs::read(Path::new(LOCALES).join("game.languages.json")).unwrap()).unwrap();
+    json.languages.into_iter().map(|language| language.id).collect()
+}
+
+/// Message keys used in the source code, e.g. `localization.text("end-title")`.
+/// Every string literal that looks like a key (lowercase words joined by dashes) counts,
+/// so keys passed through variables and closures (e.g. `message("end-score", …)`) are found too.
+fn keys_used_in_code(directory: &Path, key_literal: &Regex, keys: &mut Vec<String>) {
+    for entry in std::fs::read_dir(directory).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            keys_used_in_code(&path, key_literal, keys);
+            continue;
+        }
+        let source = std::fs::read_to_string(&path).unwrap();
+        keys.extend(key_literal.captures_iter(&source).map(|captures| captures[1].to_string()));
+    }
+}
+
+#[test]
+fn every_language_has_all_messages() {
+    let reference = messages(FALLBACK_LANGUAGE);
+    let languages = shipped_languages();
+    assert!(languages.iter().any(|id| id == FALLBACK_LANGUAGE));
+
+    for language in languages {
+        let source = messages(&language);
+        let missing = missing_keys(&reference, &source).unwrap();
+        assert!(missing.is_empty(), "{language} is missing {missing:?}");
+        let unknown = missing_keys(&source, &reference).unwrap();
+        assert!(unknown.is_empty(), "{language} has messages not in {FALLBACK_LANGUAGE}: {unknown:?}");
+    }
+}
+
+#[test]
+fn every_message_used_in_code_exists() {
+    let ids = message_ids(&messages(FALLBACK_LANGUAGE)).unwrap();
+    let mut keys = Vec::new();
+    keys_used_in_code(Path::new("src"), &Regex::new(r#""([a-z]+(?:-[a-z]+)+)""#).unwrap(), &mut keys);
+    assert!(!keys.is_empty());
+    assert!(keys.iter().any(|key| key == "end-score"), "keys passed to closures aren't found");
+
+    let missing: Vec<_> = keys.iter().filter(|key| !ids.contains(key)).collect();
+    assert!(missing.is_empty(), "missing in {FALLBACK_LANGUAGE}: {missing:?}");
+}
+
+#[test]
+fn system_locales_are_matched_to_available_languages() {
+    let available = ["en-US", "de", "ru"].map(String::from);
+
+    assert_eq!(negotiate_language(["de_AT.UTF-8"], &available).as_deref(), Some("de"));
+    assert_eq!(negotiate_language(["en-GB"], &available).as_deref(), Some("en-US"));
+    assert_eq!(negotiate_language(["fr-FR", "ru-RU"], &available).as_deref(), Some("ru"));
+    assert_eq!(negotiate_language(["fr-FR"], &available), None);
+    assert_eq!(negotiate_language(["not a locale"], &available), None);
+}
+
+#[test]
+fn missing_messages_fall_back_to_the_fallback_language() {
+    let localization = Localization::new(
+        "de",
+        "end-title = Spiel vorbei",
+        "end-title = Game over\nend-score = Score: { $score }",
+    ).unwrap();
+
+    assert_eq!(localization.text("end-title"), "Spiel vorbei");
+    let args = FluentArgs::from_iter([("score", 42)]);
+    // No Unicode isolation marks around arguments
+    assert_eq!(localization.format("end-score", Some(&args)), "Score: 42");
+    assert_eq!(localization.text("no-such-message"), "no-such-message");
+}
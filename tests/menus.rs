@@ -0,0 +1,147 @@
+use bevy::{ecs::system::RunSystemOnce, prelude::*};
+use bevy_2d_test::{
+    audio::PlaySound,
+    gameplay::scoring::{GameScore, ScoreBreakdown},
+    high_scores::{record_high_score, HighScore, HighScores, LastRunPlace, MAX_HIGH_SCORES},
+    menu::{
+        navigation::{next_focus, MenuAction, MenuActivated, MenuButton, MenuFocus, MenuNavigationPlugin, OptionStep},
+        options_menu::{step_choice, step_language, step_value, step_volume},
+    },
+    rng::GameRng,
+};
+
+/// App with menu navigation and buttons of the actions.
+fn menu_app(actions: &[MenuAction]) -> App {
+    let mut app = App::new();
+    app
+        .add_plugins((MinimalPlugins, MenuNavigationPlugin))
+        .init_resource::<ButtonInput<KeyCode>>()
+        .add_event::<PlaySound>();
+    for action in actions {
+        app.world_mut().spawn(MenuButton(*action));
+    }
+    app.update();
+    app
+}
+
+fn press(app: &mut App, key: KeyCode) {
+    app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(key);
+    app.update();
+    let mut keys = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+    keys.release(key);
+    keys.clear();
+}
+
+/// Buttons activated since the last call.
+fn activated(app: &mut App) -> Vec<MenuActivated> {
+    app.world_mut().resource_mut::<Events<MenuActivated>>().drain().collect()
+}
+
+fn focused_action(app: &mut App) -> MenuAction {
+    let focused = app.world().resource::<MenuFocus>().0.unwrap();
+    app.world().get::<MenuButton>(focused).unwrap().0
+}
+
+#[test]
+fn focus_wraps_around_the_buttons() {
+    let [a, b, c] = [1, 2, 3].map(Entity::from_raw);
+    let buttons = [a, b, c];
+
+    assert_eq!(next_focus(&buttons, None, 1), Some(a));
+    assert_eq!(next_focus(&buttons, Some(a), 1), Some(b));
+    assert_eq!(next_focus(&buttons, Some(c), 1), Some(a));
+    assert_eq!(next_focus(&buttons, Some(a), -1), Some(c));
+    assert_eq!(next_focus(&[], None, 1), None);
+}
+
+#[test]
+fn keyboard_moves_focus_and_activates_buttons() {
+    // Buttons are navigated in the order of their actions, not of spawning
+    let mut app = menu_app(&[MenuAction::Quit, MenuAction::Play, MenuAction::Options]);
+    assert_eq!(focused_action(&mut app), MenuAction::Play);
+
+    press(&mut app, KeyCode::ArrowDown);
+    assert_eq!(focused_action(&mut app), MenuAction::Options);
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(activated(&mut app), [MenuActivated { action: MenuAction::Options, step: OptionStep::Cycle }]);
+
+    press(&mut app, KeyCode::ArrowUp);
+    press(&mut app, KeyCode::ArrowUp);
+    assert_eq!(focused_action(&mut app), MenuAction::Quit);
+}
+
+#[test]
+fn escape_presses_the_back_button_and_arrows_change_options() {
+    let mut app = menu_app(&[MenuAction::MusicVolume, MenuAction::Back]);
+
+    press(&mut app, KeyCode::ArrowLeft);
+    assert_eq!(activated(&mut app), [MenuActivated { action: MenuAction::MusicVolume, step: OptionStep::Decrease }]);
+    press(&mut app, KeyCode::Escape);
+    assert_eq!(activated(&mut app), [MenuActivated { action: MenuAction::Back, step: OptionStep::Cycle }]);
+
+    // Screens without a back button ignore it, and left and right only change options
+    let mut app = menu_app(&[MenuAction::Play]);
+    press(&mut app, KeyCode::Escape);
+    press(&mut app, KeyCode::ArrowRight);
+    assert!(activated(&mut app).is_empty());
+}
+
+#[test]
+fn options_step_through_their_values() {
+    assert_eq!(step_volume(0.6, OptionStep::Increase), 0.7);
+    assert_eq!(step_volume(1.0, OptionStep::Increase), 1.0);
+    assert_eq!(step_volume(0.0, OptionStep::Decrease), 0.0);
+    assert_eq!(step_volume(1.0, OptionStep::Cycle), 0.0);
+
+    let ids = ["en-US", "de", "ru"].map(String::from);
+    assert_eq!(step_language(&ids, "de", OptionStep::Cycle).as_deref(), Some("ru"));
+    assert_eq!(step_language(&ids, "ru", OptionStep::Increase).as_deref(), Some("en-US"));
+    assert_eq!(step_language(&ids, "en-US", OptionStep::Decrease).as_deref(), Some("ru"));
+
+    assert_eq!(step_value(1.0, 0.25, 1.0, 3, OptionStep::Decrease), 0.75);
+    assert_eq!(step_value(1.0, 0.25, 1.0, 3, OptionStep::Cycle), 0.25);
+    assert_eq!(step_value(0.25, 0.25, 1.0, 3, OptionStep::Decrease), 0.25);
+    assert_eq!(step_choice(&['a', 'b', 'c'], 'a', OptionStep::Decrease), 'c');
+    assert_eq!(step_choice(&['a', 'b', 'c'], 'c', OptionStep::Cycle), 'a');
+}
+
+#[test]
+fn high_scores_keep_the_best_runs() {
+    let run = |score| HighScore { score, kills: 0, seed: score as u64 };
+    let mut high_scores = HighScores::default();
+
+    assert_eq!(high_scores.record(run(100)), Some(0));
+    assert_eq!(high_scores.record(run(300)), Some(0));
+    // Earlier runs stay ahead of later ones with the same score
+    assert_eq!(high_scores.record(run(100)), Some(2));
+    assert_eq!(high_scores.0[1].seed, 100);
+
+    for score in 1000 .. 1000 + MAX_HIGH_SCORES as u32 {
+        high_scores.record(run(score));
+    }
+    assert_eq!(high_scores.0.len(), MAX_HIGH_SCORES);
+    assert_eq!(high_scores.record(run(1)), None);
+    assert_eq!(high_scores.0[0].score, 1000 + MAX_HIGH_SCORES as u32 - 1);
+}
+
+#[test]
+fn high_scores_change_only_when_a_run_gets_there() {
+    let mut world = World::new();
+    let best = (1 ..= MAX_HIGH_SCORES as u32).rev().map(|score| HighScore { score: score * 100, kills: 0, seed: 0 });
+    world.insert_resource(HighScores(best.collect()));
+    world.insert_resource(GameScore(50));
+    world.init_resource::<ScoreBreakdown>();
+    world.init_resource::<LastRunPlace>();
+    world.insert_resource(GameRng::from_seed(1));
+    world.clear_trackers();
+
+    world.run_system_once(record_high_score).unwrap();
+    assert_eq!(world.resource::<LastRunPlace>().0, None);
+    assert!(!world.resource_ref::<HighScores>().is_changed());
+
+    world.resource_mut::<GameScore>().0 = 250;
+    world.clear_trackers();
+    world.run_system_once(record_high_score).unwrap();
+    assert_eq!(world.resource::<LastRunPlace>().0, Some(8));
+    assert!(world.resource_ref::<HighScores>().is_changed());
+}
@@ -0,0 +1,45 @@
+use bevy::prelude::*;
+use bevy_2d_test::menu::minimap::{MinimapCanvas, MINIMAP_RESOLUTION};
+
+const RED: [u8; 4] = [255, 0, 0, 255];
+const BLANK: [u8; 4] = [0, 0, 0, 0];
+
+fn canvas() -> MinimapCanvas {
+    // One world unit per map pixel
+    let half = MINIMAP_RESOLUTION as f32 / 2.0;
+    MinimapCanvas::new(Rect::new(-half, -half, half, half))
+}
+
+#[test]
+fn world_positions_map_to_pixels_with_y_pointing_up() {
+    let canvas = canvas();
+    let last = MINIMAP_RESOLUTION - 1;
+    let half = MINIMAP_RESOLUTION as f32 / 2.0;
+
+    assert_eq!(canvas.pixel_of(Vec2::new(-half, half - 0.5)), Some(UVec2::new(0, 0)));
+    assert_eq!(canvas.pixel_of(Vec2::new(half - 0.5, -half)), Some(UVec2::new(last, last)));
+    assert_eq!(canvas.pixel_of(Vec2::new(half + 1.0, 0.0)), None);
+}
+
+#[test]
+fn markers_are_clipped_to_the_map() {
+    let mut canvas = canvas();
+    let half = MINIMAP_RESOLUTION as f32 / 2.0;
+
+    canvas.plot(Vec2::new(-half, half - 0.5), 1, RED);
+    assert_eq!(canvas.get(UVec2::new(0, 0)), RED);
+    assert_eq!(canvas.get(UVec2::new(1, 1)), RED);
+    assert_eq!(canvas.get(UVec2::new(2, 2)), BLANK);
+}
+
+#[test]
+fn rects_are_filled_in_map_pixels() {
+    let mut canvas = canvas();
+    canvas.fill_rect(Rect::new(0.0, 0.0, 2.0, 1.0), RED);
+
+    let center = MINIMAP_RESOLUTION / 2;
+    assert_eq!(canvas.get(UVec2::new(center, center - 1)), RED);
+    assert_eq!(canvas.get(UVec2::new(center + 1, center - 1)), RED);
+    assert_eq!(canvas.get(UVec2::new(center + 2, center - 1)), BLANK);
+    assert_eq!(canvas.get(UVec2::new(center, center)), BLANK);
+}
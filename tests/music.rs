@@ -0,0 +1,161 @@
+use std::time::Duration;
+
+use bevy::{
+    audio::{AudioSource, Decodable},
+    prelude::*,
+    window::PrimaryWindow,
+};
+use bevy_2d_test::{
+    audio::{MusicDucking, DUCKED_MUSIC_LEVEL, DUCK_HOLD_SECS},
+    gameplay::effects::HitStop,
+    json_asset::JsonAssetError,
+    music::{follow_game_pause, Fade, LoopPoints, MusicError, MusicJson, MusicLayer, MusicPaused, MusicTrack},
+    GameState,
+};
+
+/// Mono 16-bit WAV at 1000 Hz, with each sample equal to its index.
+fn counting_wav(samples: i16) -> Vec<u8> {
+    let data_len = samples as u32 * 2;
+    let mut wav = Vec::new();
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&1u16.to_le_bytes()); // channels
+    wav.extend_from_slice(&1000u32.to_le_bytes()); // sample rate
+    wav.extend_from_slice(&2000u32.to_le_bytes()); // byte rate
+    wav.extend_from_slice(&2u16.to_le_bytes()); // block align
+    wav.extend_from_slice(&16u16.to_le_bytes()); // bits per sample
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for sample in 0 .. samples {
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+    wav
+}
+
+#[test]
+fn shipped_music_has_tracks_for_every_state() {
+    let json = MusicJson::parse(&std::fs::read("assets/music/game.music.json").unwrap()).unwrap();
+
+    for state in [GameState::MainMenu, GameState::InGame, GameState::End] {
+        let layers = json.layers.get(&state).into_iter().flatten().map(|layer| &layer.track);
+        let tracks: Vec<_> = json.playlists.get(&state).into_iter().flatten().chain(layers).collect();
+        assert!(!tracks.is_empty(), "{state:?} has no music");
+        for name in tracks {
+            let file = &json.tracks[name].file;
+            assert!(std::path::Path::new("assets/music").join(file).exists(), "{file} is missing");
+        }
+    }
+}
+
+#[test]
+fn invalid_playlists_are_rejected() {
+    assert!(matches!(MusicJson::parse(b"{ \"tracks\": "), Err(MusicError::Asset(JsonAssetError::Json(_)))));
+
+    let unknown = br#"{ "tracks": {}, "playlists": { "InGame": ["battle"] } }"#;
+    assert!(matches!(MusicJson::parse(unknown), Err(MusicError::UnknownTrack(name)) if name == "battle"));
+
+    let endless = br#"{ "tracks": { "battle": { "file": "battle.wav", "plays": 2 } }, "playlists": {} }"#;
+    assert!(matches!(MusicJson::parse(endless), Err(MusicError::MissingLoopEnd(name)) if name == "battle"));
+
+    let both = br#"{
+        "tracks": { "battle": { "file": "battle.wav" } },
+        "playlists": { "InGame": ["battle"] },
+        "layers": { "InGame": [{ "track": "battle", "intensity": [0.0, 1.0] }] }
+    }"#;
+    assert!(matches!(MusicJson::parse(both), Err(MusicError::PlaylistAndLayers(GameState::InGame))));
+}
+
+#[test]
+fn tracks_end_after_intro_and_loop_plays() {
+    assert_eq!(LoopPoints { start: 2.0, end: Some(8.0), plays: Some(3) }.length(), Some(20.0));
+    assert_eq!(LoopPoints { start: 2.0, end: Some(8.0), plays: None }.length(), None);
+    assert_eq!(LoopPoints { start: 0.0, end: None, plays: None }.length(), None);
+}
+
+#[test]
+fn tracks_play_intro_once_and_then_loop() {
+    let track = MusicTrack {
+        audio: AudioSource { bytes: counting_wav(1000).into() },
+        loop_points: LoopPoints { start: 0.25, end: Some(0.5), plays: None },
+    };
+
+    let samples: Vec<i16> = track.decoder().take(750).collect();
+    let expected: Vec<i16> = (0 .. 250).chain(250 .. 500).chain(250 .. 500).collect();
+    assert_eq!(samples, expected);
+}
+
+#[test]
+fn fades_reach_their_target_and_stop() {
+    let mut fade = Fade::fade_in(2.0);
+    fade.tick(1.0);
+    assert_eq!(fade.volume, 0.5);
+    fade.tick(5.0);
+    assert_eq!(fade.volume, 1.0);
+
+    fade.fade_out(0.5);
+    fade.tick(0.25);
+    assert_eq!(fade.volume, 0.5);
+    assert!(!fade.is_silent());
+    fade.tick(0.25);
+    assert!(fade.is_silent());
+}
+
+#[test]
+fn music_is_ducked_while_important_sounds_play() {
+    let mut ducking = MusicDucking::default();
+    ducking.duck();
+    ducking.tick(DUCK_HOLD_SECS / 2.0);
+    assert_eq!(ducking.level, DUCKED_MUSIC_LEVEL);
+
+    // Music comes back after the hold
+    ducking.tick(DUCK_HOLD_SECS);
+    for _ in 0 .. 100 {
+        ducking.tick(0.1);
+    }
+    assert_eq!(ducking.level, 1.0);
+}
+
+#[test]
+fn layers_fade_in_over_their_intensity_range() {
+    let layer = MusicLayer { intensity: (0.5, 1.0) };
+    assert_eq!(layer.volume(0.25), 0.0);
+    assert_eq!(layer.volume(0.75), 0.5);
+    assert_eq!(layer.volume(1.0), 1.0);
+
+    // Base layers are always heard
+    assert_eq!(MusicLayer { intensity: (0.0, 0.0) }.volume(0.0), 1.0);
+}
+
+#[test]
+fn music_pauses_with_the_game_but_not_during_hit_stops() {
+    let mut app = App::new();
+    app
+        .add_plugins(MinimalPlugins)
+        .init_resource::<HitStop>()
+        .init_resource::<MusicPaused>()
+        .add_systems(Update, follow_game_pause);
+    let paused = |app: &mut App| {
+        app.update();
+        app.world().resource::<MusicPaused>().0
+    };
+    assert!(!paused(&mut app));
+
+    app.world_mut().resource_mut::<Time<Virtual>>().pause();
+    app.world_mut().resource_mut::<HitStop>().remaining = Some(Duration::from_millis(50));
+    assert!(!paused(&mut app), "a hit-stop shouldn't pause the music");
+
+    app.world_mut().resource_mut::<HitStop>().remaining = None;
+    assert!(paused(&mut app));
+
+    app.world_mut().resource_mut::<Time<Virtual>>().unpause();
+    assert!(!paused(&mut app));
+
+    let window = app.world_mut().spawn((Window { focused: false, ..default() }, PrimaryWindow)).id();
+    assert!(paused(&mut app), "music should pause in the background");
+
+    app.world_mut().get_mut::<Window>(window).unwrap().focused = true;
+    assert!(!paused(&mut app));
+}
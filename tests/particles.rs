@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+use bevy_2d_test::{
+    gameplay::effects::Decals,
+    particles::{Emission, ParticleEffect, ParticleEmitter},
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+fn effect(json: &str) -> ParticleEffect {
+    ParticleEffect::parse(json.as_bytes()).unwrap()
+}
+
+#[test]
+fn game_particle_effects_are_valid() {
+    for name in ["slime_splat", "bullet_impact", "bullet_trail"] {
+        let path = format!("{}/assets/particles/{name}.particle.json", env!("CARGO_MANIFEST_DIR"));
+        let effect = ParticleEffect::parse(&std::fs::read(path).unwrap());
+        assert!(effect.is_ok(), "{name}: {:?}", effect.err());
+    }
+}
+
+#[test]
+fn colors_and_sizes_are_blended_over_life() {
+    let effect = effect(r#"{ "colors": [[1, 0, 0, 1], [0, 0, 1, 0]], "sizes": [4, 2, 0] }"#);
+
+    assert_eq!(effect.color_at(0.0), Color::srgba(1.0, 0.0, 0.0, 1.0));
+    assert_eq!(effect.color_at(0.5), Color::srgba(0.5, 0.0, 0.5, 0.5));
+    assert_eq!(effect.color_at(1.0), Color::srgba(0.0, 0.0, 1.0, 0.0));
+    assert_eq!(effect.size_at(0.25), 3.0);
+    assert_eq!(effect.size_at(0.75), 1.0);
+}
+
+#[test]
+fn particles_are_emitted_within_the_cone() {
+    let effect = effect(r#"{ "speed": [10, 20], "spread": 90 }"#);
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+
+    for _ in 0 .. 100 {
+        let velocity = effect.sample_velocity(Vec2::Y, &mut rng);
+        assert!((10.0 ..= 20.0).contains(&velocity.length()));
+        assert!(velocity.angle_to(Vec2::Y).abs() <= 45f32.to_radians() + 0.001);
+    }
+}
+
+#[test]
+fn bursts_are_emitted_once_and_continuous_emission_keeps_fractions() {
+    let burst = effect(r#"{ "emission": { "burst": 12 } }"#);
+    let mut emitter = ParticleEmitter::new(Handle::default());
+    assert_eq!(emitter.emit_count(&burst, 0.1), 12);
+    assert_eq!(emitter.emit_count(&burst, 0.1), 0);
+
+    let continuous = effect(r#"{ "emission": { "continuous": 30.0 } }"#);
+    assert_eq!(continuous.emission, Emission::Continuous(30.0));
+    let mut emitter = ParticleEmitter::new(Handle::default());
+    // 0.5 particle per frame
+    let emitted: u32 = (0 .. 10).map(|_| emitter.emit_count(&continuous, 1.0 / 60.0)).sum();
+    assert_eq!(emitted, 5);
+}
+
+#[test]
+fn oldest_decals_are_removed_over_capacity() {
+    let mut decals = Decals::new(2);
+    let [a, b, c] = [Entity::from_raw(1), Entity::from_raw(2), Entity::from_raw(3)];
+
+    assert_eq!(decals.push(a), None);
+    assert_eq!(decals.push(b), None);
+    assert_eq!(decals.push(c), Some(a));
+    decals.remove(b);
+    assert_eq!(decals.len(), 1);
+}
@@ -0,0 +1,131 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use bevy_2d_test::{
+    control::PlayerInput,
+    gameplay::{
+        player::Bullet,
+        pool::{BulletPool, EnemyPool, EntityPool, PoolConfig, Pooled},
+        FIXED_TICKS_PER_SECOND,
+    },
+    testing::TestGame,
+};
+
+const ONE_SECOND: u32 = FIXED_TICKS_PER_SECOND as u32;
+
+fn shoot_up(game: &mut TestGame) -> Entity {
+    game.set_player_position(Vec2::ZERO);
+    game.set_input(PlayerInput {
+        movement: Vec2::ZERO,
+        aim: Some(Vec2::new(0.0, 100.0)),
+        fire: true,
+    });
+    game.step(1);
+    game.set_input(PlayerInput::default());
+    game.app.world_mut().query_filtered::<Entity, With<Bullet>>().single(game.app.world())
+}
+
+#[test]
+fn pools_are_filled_on_level_start() {
+    let game = TestGame::new();
+    let config = PoolConfig::default();
+
+    let bullets = game.app.world().resource::<BulletPool>().stats;
+    assert_eq!((bullets.active, bullets.free, bullets.spawned), (0, config.bullets, config.bullets as u32));
+    let enemies = game.app.world().resource::<EnemyPool>().stats;
+    assert_eq!((enemies.active, enemies.free), (0, config.enemies));
+}
+
+#[test]
+fn fired_bullet_reuses_pooled_entity_and_returns_to_pool() {
+    let mut game = TestGame::new();
+    let bullet = shoot_up(&mut game);
+
+    let stats = game.app.world().resource::<BulletPool>().stats;
+    assert_eq!((stats.active, stats.reused, stats.spawned), (1, 1, PoolConfig::default().bullets as u32));
+    game.step(5);
+    assert!(game.position(bullet).unwrap().y > 5.0);
+
+    assert!(game.step_until(2 * ONE_SECOND, |g| !g.is_active(bullet)));
+    assert!(game.exists(bullet));
+    let stats = game.app.world().resource::<BulletPool>().stats;
+    assert_eq!((stats.active, stats.free), (0, PoolConfig::default().bullets));
+
+    // The same entity is shot again
+    assert_eq!(shoot_up(&mut game), bullet);
+}
+
+#[test]
+fn released_entities_over_capacity_are_despawned() {
+    let mut game = TestGame::new();
+    let bullet = game.spawn_bullet(Vec2::new(300.0, 0.0), Vec2::X);
+
+    assert!(game.step_until(ONE_SECOND, |g| !g.is_active(bullet)));
+    assert!(!game.exists(bullet));
+}
+
+#[test]
+fn dead_enemy_returns_to_pool() {
+    let mut game = TestGame::new();
+    game.app.world_mut().resource_mut::<EnemyPool>().capacity += 1;
+    game.set_player_position(Vec2::ZERO);
+    let enemy = game.spawn_enemy(Vec2::new(30.0, 0.0));
+
+    assert!(game.step_until(2 * ONE_SECOND, |g| !g.is_active(enemy)));
+    assert!(game.exists(enemy));
+    assert_eq!(game.enemies_count(), 0);
+}
+
+#[derive(Component)]
+struct Effect;
+
+#[test]
+fn objects_without_physics_are_pooled_without_physics_components() {
+    let mut game = TestGame::new();
+    let world = game.app.world_mut();
+    let mut pool = EntityPool::<Effect>::new(4);
+
+    let mut commands = world.commands();
+    let effect = pool.acquire(&mut commands, (Effect, Transform::default()));
+    pool.release(&mut commands, effect);
+    world.flush();
+
+    assert!(world.get::<Pooled>(effect).is_some());
+    assert!(world.get::<Effect>(effect).is_none());
+    assert!(world.get::<RigidBodyDisabled>(effect).is_none());
+    assert!(world.get::<Velocity>(effect).is_none());
+
+    let mut commands = world.commands();
+    let reused = pool.acquire(&mut commands, (Effect, Transform::default()));
+    world.flush();
+    assert_eq!(reused, effect);
+    assert!(world.get::<Effect>(effect).is_some());
+}
+
+#[test]
+fn releasing_twice_or_after_despawn_does_nothing() {
+    let mut game = TestGame::new();
+    let world = game.app.world_mut();
+    let mut pool = EntityPool::<Effect>::new(1);
+
+    let mut commands = world.commands();
+    let pooled = pool.acquire(&mut commands, Effect);
+    let despawned = pool.acquire(&mut commands, Effect);
+    let active = pool.acquire(&mut commands, Effect);
+    world.flush();
+
+    // The second entity is over the capacity, both releases happen before its despawn is applied
+    let mut commands = world.commands();
+    for entity in [pooled, pooled, despawned, despawned] {
+        pool.release(&mut commands, entity);
+    }
+    world.flush();
+    assert_eq!((pool.stats.active, pool.stats.free), (1, 1));
+    assert!(world.get_entity(despawned).is_err());
+
+    let mut commands = world.commands();
+    pool.release(&mut commands, despawned);
+    pool.release(&mut commands, pooled);
+    world.flush();
+    assert_eq!((pool.stats.active, pool.stats.free), (1, 1));
+    assert!(world.get::<Pooled>(active).is_none());
+}
@@ -0,0 +1,111 @@
+use std::io;
+
+use bevy::prelude::*;
+use bevy_2d_test::{
+    control::{movement_from_axes, InputSource, PlayerInput},
+    headless::{bot_player_input, simulation_app},
+    replay::{record_player_input, Replay, ReplayPlayback, ReplayRecorder},
+    testing::TestGame,
+    GameState,
+};
+
+fn frame(x: i8, y: i8, aim: Option<Vec2>, fire: bool) -> PlayerInput {
+    PlayerInput { movement: movement_from_axes(x, y), aim, fire }
+}
+
+#[test]
+fn replay_survives_encoding() {
+    let aim = Some(Vec2::new(12.5, -3.0));
+    let replay = Replay {
+        seed: 42,
+        frames: vec![
+            frame(0, 0, None, false),
+            frame(1, 1, aim, true),
+            // Unchanged aim isn't stored again
+            frame(-1, 1, aim, false),
+            frame(0, -1, None, true),
+            frame(1, -1, Some(Vec2::new(-100.0, 7.25)), false),
+        ],
+    };
+
+    let bytes = replay.encode();
+    // Header, a byte per frame and two changed aims
+    assert_eq!(bytes.len(), 17 + 5 + 2 * 8);
+    assert_eq!(Replay::decode(&bytes).unwrap(), replay);
+}
+
+#[test]
+fn bad_replays_are_rejected() {
+    let bytes = Replay { seed: 1, frames: vec![frame(1, 0, Some(Vec2::ONE), true)] }.encode();
+    let error_kind = |bytes: &[u8]| Replay::decode(bytes).unwrap_err().kind();
+
+    let mut bad_magic = bytes.clone();
+    bad_magic[0] = b'X';
+    assert_eq!(error_kind(&bad_magic), io::ErrorKind::InvalidData);
+
+    let mut bad_version = bytes.clone();
+    bad_version[4] += 1;
+    assert_eq!(error_kind(&bad_version), io::ErrorKind::InvalidData);
+
+    // Replays of older rules can't be reproduced
+    let mut old_version = bytes.clone();
+    old_version[4] = 1;
+    let error = Replay::decode(&old_version).unwrap_err();
+    assert_eq!(error.to_string(), "unsupported replay version 1");
+
+    assert_eq!(error_kind(&bytes[.. bytes.len() - 1]), io::ErrorKind::UnexpectedEof);
+    assert_eq!(error_kind(&bytes[.. 10]), io::ErrorKind::UnexpectedEof);
+
+    // Huge frame count of a corrupt header doesn't allocate the frames up front
+    let mut huge_count = bytes.clone();
+    huge_count[13 .. 17].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert_eq!(error_kind(&huge_count), io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn replay_reproduces_the_run() {
+    const SEED: u64 = 7;
+    const TICKS: u32 = 64 * 20;
+
+    let mut app = simulation_app(Some(SEED));
+    app
+        .insert_resource(InputSource::Bot)
+        .add_systems(FixedUpdate,
+            bot_player_input
+                .before(record_player_input)
+                .run_if(resource_equals(InputSource::Bot))
+                .run_if(in_state(GameState::InGame))
+        );
+    let mut played = TestGame { app };
+    played.step(TICKS);
+    let frames = played.app.world().resource::<ReplayRecorder>().frames.clone();
+    assert!(played.score() > 0);
+
+    let mut app = simulation_app(Some(SEED));
+    app
+        .insert_resource(InputSource::Replay)
+        .insert_resource(ReplayPlayback { replay: Replay { seed: SEED, frames: frames.clone() }, tick: 0 });
+    let mut replayed = TestGame { app };
+    replayed.step(TICKS);
+
+    assert_eq!(replayed.app.world().resource::<ReplayRecorder>().frames, frames);
+    assert_eq!(replayed.score(), played.score());
+    assert_eq!(replayed.player_health(), played.player_health());
+}
+
+#[test]
+fn unfinished_run_is_saved_when_the_game_is_closed() {
+    let path = std::env::temp_dir().join(format!("bevy-2d-test-exit-{}.replay", std::process::id()));
+    let mut game = TestGame::with_seed(3);
+    game.app.insert_resource(ReplayRecorder { path: Some(path.clone()), ..default() });
+    game.step(10);
+    assert!(!path.exists());
+
+    game.app.world_mut().send_event(AppExit::Success);
+    game.app.update();
+    let replay = Replay::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(replay.seed, 3);
+    assert_eq!(replay.frames, game.app.world().resource::<ReplayRecorder>().frames);
+    assert!(!replay.frames.is_empty());
+}
@@ -0,0 +1,85 @@
+use bevy::prelude::*;
+use bevy_2d_test::{
+    gameplay::{
+        enemy::{EnemyState, POINTS_SLIME},
+        scoring::{Combo, KillStreak, ScoreBreakdown, ScoreEvent, COMBO_WINDOW, KILLS_PER_MULTIPLIER, STREAK_BONUS_EVERY, STREAK_BONUS_PER_KILL},
+        FIXED_TICKS_PER_SECOND,
+    },
+    testing::TestGame,
+};
+
+const ONE_SECOND: u32 = FIXED_TICKS_PER_SECOND as u32;
+
+/// Spawns enemies in a column with a bullet right in front of each of them.
+fn shoot_enemies(game: &mut TestGame, count: usize) -> Vec<Entity> {
+    game.set_player_position(Vec2::new(-300.0, 0.0));
+    (0 .. count)
+        .map(|i| {
+            let y = 100.0 - 60.0 * i as f32;
+            game.spawn_bullet(Vec2::new(60.0, y), Vec2::X);
+            game.spawn_enemy(Vec2::new(100.0, y))
+        })
+        .collect()
+}
+
+fn all_dying(game: &TestGame, enemies: &[Entity]) -> bool {
+    enemies.iter().all(|&e| game.enemy_state(e) == Some(&EnemyState::Dying))
+}
+
+#[test]
+fn quick_kills_raise_the_multiplier() {
+    let mut game = TestGame::new();
+    game.record_events::<ScoreEvent>();
+    let enemies = shoot_enemies(&mut game, KILLS_PER_MULTIPLIER as usize);
+
+    assert!(game.step_until(ONE_SECOND, |g| all_dying(g, &enemies)));
+
+    let multipliers: Vec<u32> = game.recorded::<ScoreEvent>().iter().map(|e| e.multiplier).collect();
+    assert_eq!(multipliers, vec![1, 1, 2]);
+    assert_eq!(game.score(), POINTS_SLIME * 4);
+    let breakdown = game.app.world().resource::<ScoreBreakdown>();
+    assert_eq!((breakdown.kills, breakdown.kill_points, breakdown.combo_bonus), (3, POINTS_SLIME * 3, POINTS_SLIME));
+}
+
+#[test]
+fn multiplier_decays_without_kills() {
+    let mut combo = Combo::default();
+    for _ in 0 .. 2 * KILLS_PER_MULTIPLIER {
+        combo.register_kill();
+    }
+    assert_eq!(combo.multiplier, 3);
+
+    combo.decay(COMBO_WINDOW / 2);
+    assert_eq!(combo.multiplier, 3);
+    combo.decay(COMBO_WINDOW / 2);
+    assert_eq!(combo.multiplier, 2);
+    combo.decay(COMBO_WINDOW);
+    assert_eq!(combo.multiplier, 1);
+    assert_eq!(combo.register_kill(), 1);
+}
+
+#[test]
+fn kill_streak_gives_bonus() {
+    let mut game = TestGame::new();
+    game.app.world_mut().resource_mut::<KillStreak>().0 = STREAK_BONUS_EVERY - 1;
+    let enemies = shoot_enemies(&mut game, 1);
+
+    assert!(game.step_until(ONE_SECOND, |g| all_dying(g, &enemies)));
+
+    let bonus = STREAK_BONUS_EVERY * STREAK_BONUS_PER_KILL;
+    assert_eq!(game.score(), POINTS_SLIME + bonus);
+    assert_eq!(game.app.world().resource::<ScoreBreakdown>().streak_bonus, bonus);
+}
+
+#[test]
+fn taking_damage_breaks_kill_streak() {
+    let mut game = TestGame::new();
+    game.app.world_mut().resource_mut::<KillStreak>().0 = 5;
+    game.set_player_position(Vec2::ZERO);
+    let enemy = game.spawn_enemy(Vec2::new(30.0, 0.0));
+
+    assert!(game.step_until(ONE_SECOND, |g| g.enemy_state(enemy) == Some(&EnemyState::Dying)));
+
+    assert_eq!(game.app.world().resource::<KillStreak>().0, 0);
+    assert_eq!(game.score(), 0);
+}
@@ -0,0 +1,43 @@
+use bevy_2d_test::settings::{AccessibilitySettings, AudioSettings, ColorScheme, EffectSettings, FireMode, MinimapSettings, ScreenCorner, Settings};
+
+#[test]
+fn settings_are_saved_and_loaded() {
+    let path = std::env::temp_dir().join(format!("bevy-2d-test-settings-{}.json", std::process::id()));
+    let settings = Settings {
+        pixel_perfect_zoom: true,
+        show_fps: true,
+        minimap: MinimapSettings { corner: ScreenCorner::TopLeft, ..Default::default() },
+        effects: EffectSettings { hit_stop: false, ..Default::default() },
+        audio: AudioSettings { music: 0.25, ..Default::default() },
+        accessibility: AccessibilitySettings {
+            color_scheme: ColorScheme::Tritanopia,
+            fire_mode: FireMode::Hold,
+            game_speed: 0.5,
+            screen_shake: 0.5,
+            ..Default::default()
+        },
+        language: Some("de".to_string()),
+    };
+
+    settings.save(&path).unwrap();
+    let loaded = Settings::load(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.unwrap(), settings);
+}
+
+#[test]
+fn missing_settings_get_defaults() {
+    let settings: Settings = serde_json::from_str("{}").unwrap();
+    assert_eq!(settings, Settings::default());
+}
+
+#[test]
+fn reduced_motion_of_older_settings_turns_off_screen_shake() {
+    let settings = Settings::parse(br#"{ "reduced_motion": true, "show_fps": true }"#).unwrap();
+    assert_eq!(settings.accessibility.screen_shake, 0.0);
+    assert!(settings.show_fps);
+
+    let settings = Settings::parse(br#"{ "reduced_motion": false }"#).unwrap();
+    assert_eq!(settings, Settings::default());
+}
@@ -0,0 +1,43 @@
+use bevy::prelude::*;
+use bevy_2d_test::{
+    control::PlayerInput,
+    gameplay::{
+        enemy::DAMAGE_SLIME,
+        player::{Footstep, PlayerHurt},
+        FIXED_TICKS_PER_SECOND,
+    },
+    testing::TestGame,
+};
+
+const ONE_SECOND: u32 = FIXED_TICKS_PER_SECOND as u32;
+
+#[test]
+fn footsteps_follow_the_walk_animation() {
+    let mut game = TestGame::new();
+    game.record_events::<Footstep>();
+    game.set_player_position(Vec2::ZERO);
+
+    game.step(ONE_SECOND);
+    assert!(game.recorded::<Footstep>().is_empty(), "no footsteps while standing");
+
+    game.set_input(PlayerInput { movement: Vec2::X, aim: Some(Vec2::new(1000.0, 0.0)), fire: false });
+    game.step(ONE_SECOND);
+    let steps = game.recorded::<Footstep>();
+    assert!(steps.len() >= 2, "{} footsteps in a second of walking", steps.len());
+    // Footsteps are reported where the player is
+    assert!(steps.windows(2).all(|s| s[1].position.x > s[0].position.x));
+}
+
+#[test]
+fn enemy_touching_player_reports_player_hurt() {
+    let mut game = TestGame::new();
+    game.record_events::<PlayerHurt>();
+    game.set_player_position(Vec2::ZERO);
+    game.spawn_enemy(Vec2::new(30.0, 0.0));
+
+    assert!(game.step_until(ONE_SECOND, |g| !g.recorded::<PlayerHurt>().is_empty()));
+
+    let hurt = &game.recorded::<PlayerHurt>()[0];
+    assert_eq!(hurt.amount, DAMAGE_SLIME);
+    assert!(hurt.position.length() < 1.0);
+}
@@ -0,0 +1,54 @@
+use bevy::prelude::*;
+use bevy_2d_test::menu::threat_indicators::{
+    arrow_glyph, edge_position, group_threats, threat_sector, Threat, ThreatIcon,
+};
+
+const VIEWPORT: Vec2 = Vec2::new(800.0, 600.0);
+
+fn threat(position: Vec2, distance: f32, icon: ThreatIcon) -> Threat {
+    Threat { position, distance, icon }
+}
+
+#[test]
+fn visible_points_have_no_edge_position() {
+    assert_eq!(edge_position(VIEWPORT, Vec2::new(10.0, 590.0), 20.0), None);
+}
+
+#[test]
+fn off_screen_points_are_clamped_to_the_inset_edge_towards_them() {
+    // Straight to the right
+    assert_eq!(edge_position(VIEWPORT, Vec2::new(2000.0, 300.0), 20.0), Some(Vec2::new(780.0, 300.0)));
+    // Far above, slightly to the left: hits the top edge
+    let position = edge_position(VIEWPORT, Vec2::new(300.0, -700.0), 20.0).unwrap();
+    assert_eq!(position.y, 20.0);
+    assert!(position.x < 400.0 && position.x > 300.0);
+    // Diagonal beyond the corner stays inside the inset rectangle
+    let position = edge_position(VIEWPORT, Vec2::new(-1000.0, 1300.0), 20.0).unwrap();
+    assert!((position.x - 20.0).abs() < 0.001 && position.y <= 580.0);
+}
+
+#[test]
+fn threats_in_the_same_direction_are_grouped() {
+    let groups = group_threats(VIEWPORT, [
+        threat(Vec2::new(1000.0, 300.0), 300.0, ThreatIcon::Regular),
+        threat(Vec2::new(1200.0, 310.0), 200.0, ThreatIcon::Boss),
+        threat(Vec2::new(1100.0, 290.0), 250.0, ThreatIcon::Ranged),
+        threat(Vec2::new(-500.0, 300.0), 100.0, ThreatIcon::Regular),
+    ]);
+
+    assert_eq!(groups.len(), 2);
+    let right = groups.iter().find(|g| g.count == 3).unwrap();
+    assert_eq!(right.nearest.distance, 200.0);
+    assert_eq!(right.icon, ThreatIcon::Boss);
+    let left = groups.iter().find(|g| g.count == 1).unwrap();
+    assert_eq!(left.icon, ThreatIcon::Regular);
+    assert_ne!(threat_sector(VIEWPORT, left.nearest.position), threat_sector(VIEWPORT, right.nearest.position));
+}
+
+#[test]
+fn arrows_point_in_viewport_directions() {
+    assert_eq!(arrow_glyph(Vec2::new(1.0, 0.0)), '→');
+    assert_eq!(arrow_glyph(Vec2::new(0.0, -1.0)), '↑');
+    assert_eq!(arrow_glyph(Vec2::new(-1.0, 1.0)), '↙');
+    assert_eq!(arrow_glyph(Vec2::new(1.0, -1.0)), '↗');
+}